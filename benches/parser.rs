@@ -0,0 +1,38 @@
+//! Parser allocation benchmark
+//!
+//! Counts heap allocations and time spent parsing a large, operator-heavy
+//! program. Run with `cargo bench --bench parser`.
+
+mod support;
+
+use std::time::Instant;
+
+use rlox::parser::Parser;
+use rlox::scanner::Scanner;
+
+fn main() {
+    let line = "var x = (1 + 2) * 3 - 4 / 5 + a * (b - c) % 7 == 8 or d < e and f >= g;\n";
+    let source = line.repeat(2_000);
+    let tokens = Scanner::new(source).scan_tokens();
+    let token_count = tokens.len();
+
+    let allocations_before = support::allocations();
+    let start = Instant::now();
+    let statements = Parser::new(tokens)
+        .parse_program()
+        .expect("benchmark source parses");
+    let elapsed = start.elapsed();
+    let allocations = support::allocations() - allocations_before;
+
+    println!(
+        "parsed {} statements ({} tokens) in {:?}",
+        statements.len(),
+        token_count,
+        elapsed
+    );
+    println!(
+        "{} allocations, {:.2} per token",
+        allocations,
+        allocations as f64 / token_count as f64
+    );
+}
@@ -0,0 +1,31 @@
+//! AST printer allocation benchmark
+//!
+//! Counts heap allocations and time spent printing a large expression
+//! tree. Run with `cargo bench --bench printer`.
+
+mod support;
+
+use std::time::Instant;
+
+use rlox::ast_printer::AstPrinter;
+use rlox::parser::Parser;
+use rlox::scanner::Scanner;
+
+fn main() {
+    let term = "(1 + 2) * -3 - f(a, b / 4) % 7 == !c";
+    let source = vec![term; 500].join(" or ");
+    let tokens = Scanner::new(source).scan_tokens();
+    let expr = Parser::new(tokens)
+        .parse_expression()
+        .expect("benchmark source parses");
+
+    let printer = AstPrinter::new();
+    let allocations_before = support::allocations();
+    let start = Instant::now();
+    let printed = printer.print(&expr);
+    let elapsed = start.elapsed();
+    let allocations = support::allocations() - allocations_before;
+
+    println!("printed {} bytes in {:?}", printed.len(), elapsed);
+    println!("{} allocations", allocations);
+}
@@ -0,0 +1,20 @@
+//! Scanner + parser fuzz target
+//!
+//! Invariant: no input, however malformed, may make the front end panic.
+//! Bad source must come back as scan errors or an `Err` from the parser.
+//!
+//! Run with `cargo fuzz run parse` from the repository root; the seed
+//! corpus lives in `fuzz/corpus/parse`.
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use rlox::parser::Parser;
+use rlox::scanner::Scanner;
+
+fuzz_target!(|data: &[u8]| {
+    let source = String::from_utf8_lossy(data).into_owned();
+    let tokens = Scanner::new(source).scan_tokens();
+
+    let _ = Parser::new(tokens.clone()).parse();
+    let _ = Parser::new(tokens).parse_program();
+});
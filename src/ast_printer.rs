@@ -1,4 +1,11 @@
-use crate::expr::{Binary, Expr, Grouping, Literal, LiteralValue, Unary};
+use std::fmt;
+
+use crate::expr::{
+    self, Assign, Binary, Call, Expr, Function, Get, Grouping, Literal, LiteralValue, Logical,
+    Postfix, Set, Unary, Variable,
+};
+use crate::stmt::{self, Stmt};
+use crate::token::Token;
 
 /// Printer that converts AST to human-readable strings
 ///
@@ -24,13 +31,244 @@ impl AstPrinter {
     /// println!("{}", result); // "(+ 1 2)"
     /// ```
     pub fn print(&self, expr: &Expr) -> String {
+        let mut out = String::new();
+        self.write(&mut out, expr)
+            .expect("writing to a String cannot fail");
+        out
+    }
+
+    /// Write an expression into `out`, the whole tree sharing one buffer
+    ///
+    /// # Example
+    /// ```ignore
+    /// let mut out = String::from("expr: ");
+    /// AstPrinter::new().write(&mut out, &expr)?;
+    /// // out == "expr: (+ 1 2)"
+    /// ```
+    pub fn write<W: fmt::Write>(&self, out: &mut W, expr: &Expr) -> fmt::Result {
         match expr {
-            Expr::Binary(binary) => self.visit_binary(binary),
-            Expr::Grouping(grouping) => self.visit_grouping(grouping),
-            Expr::Literal(literal) => self.visit_literal(literal),
-            Expr::Unary(unary) => self.visit_unary(unary),
-            _ => String::from("(not implemented)"),
+            Expr::Binary(binary) => self.visit_binary(out, binary),
+            Expr::Grouping(grouping) => self.visit_grouping(out, grouping),
+            Expr::Literal(literal) => self.visit_literal(out, literal),
+            Expr::Unary(unary) => self.visit_unary(out, unary),
+            Expr::Variable(variable) => self.visit_variable(out, variable),
+            Expr::Assign(assign) => self.visit_assign(out, assign),
+            Expr::Postfix(postfix) => self.visit_postfix(out, postfix),
+            Expr::Call(call) => self.visit_call(out, call),
+            Expr::Logical(logical) => self.visit_logical(out, logical),
+            Expr::Function(function) => self.visit_function(out, function),
+            Expr::Get(get) => self.visit_get(out, get),
+            Expr::Set(set) => self.visit_set(out, set),
+            Expr::This(_) => out.write_str("this"),
+            Expr::Block(block) => self.visit_block_expr(out, block),
+            _ => out.write_str("(not implemented)"),
+        }
+    }
+
+    /// Convert a statement to a string
+    ///
+    /// # Examples
+    /// - `print 1 + 2;` -> `(print (+ 1 2))`
+    /// - `if (a) b(); else c();` -> `(if a (expr (call b)) (expr (call c)))`
+    pub fn print_stmt(&self, stmt: &Stmt) -> String {
+        let mut out = String::new();
+        self.write_stmt(&mut out, stmt)
+            .expect("writing to a String cannot fail");
+        out
+    }
+
+    /// Write a statement into `out`, like `write` does for expressions
+    pub fn write_stmt<W: fmt::Write>(&self, out: &mut W, stmt: &Stmt) -> fmt::Result {
+        match stmt {
+            Stmt::Block(block) => {
+                out.write_str("(block")?;
+                self.write_stmts(out, &block.statements)?;
+                out.write_char(')')
+            }
+            Stmt::Break(break_stmt) => self.visit_loop_control(out, "break", &break_stmt.label),
+            Stmt::Class(class) => self.visit_class(out, class),
+            Stmt::Continue(continue_stmt) => {
+                self.visit_loop_control(out, "continue", &continue_stmt.label)
+            }
+            Stmt::Expression(expression) => {
+                self.parenthesize(out, "expr", &[&expression.expression])
+            }
+            Stmt::Fallthrough(_) => out.write_str("(fallthrough)"),
+            Stmt::Function(function) => self.visit_function_stmt(out, "fun", function),
+            Stmt::If(if_stmt) => self.visit_if(out, if_stmt),
+            Stmt::Print(print) => {
+                let expressions: Vec<&Expr> = print.expressions.iter().collect();
+                self.parenthesize(out, "print", &expressions)
+            }
+            Stmt::Return(return_stmt) => match &return_stmt.value {
+                Some(value) => self.parenthesize(out, "return", &[value]),
+                None => out.write_str("(return)"),
+            },
+            Stmt::Switch(switch) => self.visit_switch(out, switch),
+            Stmt::Var(var) => {
+                write!(out, "(var {}", var.name.lexeme)?;
+                if let Some(initializer) = &var.initializer {
+                    out.write_char(' ')?;
+                    self.write(out, initializer)?;
+                }
+                out.write_char(')')
+            }
+            Stmt::While(while_stmt) => self.visit_while(out, while_stmt),
+        }
+    }
+
+    // Each statement preceded by a space
+    fn write_stmts<W: fmt::Write>(&self, out: &mut W, stmts: &[Stmt]) -> fmt::Result {
+        for stmt in stmts {
+            out.write_char(' ')?;
+            self.write_stmt(out, stmt)?;
+        }
+        Ok(())
+    }
+
+    /// Process `break` and `continue`, with the label only when present
+    ///
+    /// # Examples
+    /// - 'break;' -> '(break)'
+    /// - 'continue outer;' -> '(continue outer)'
+    fn visit_loop_control<W: fmt::Write>(
+        &self,
+        out: &mut W,
+        keyword: &str,
+        label: &Option<Token>,
+    ) -> fmt::Result {
+        match label {
+            Some(label) => write!(out, "({} {})", keyword, label.lexeme),
+            None => write!(out, "({})", keyword),
+        }
+    }
+
+    /// Process while loops; a label wraps the loop and a `for` increment
+    /// follows the body
+    ///
+    /// # Examples
+    /// - 'while (a) f();' -> '(while a (expr (call f)))'
+    /// - 'outer: for (; i < 3; i++) f();'
+    ///   -> '(label outer (while (< i 3) (expr (call f)) (i ++)))'
+    fn visit_while<W: fmt::Write>(&self, out: &mut W, stmt: &stmt::While) -> fmt::Result {
+        if let Some(label) = &stmt.label {
+            write!(out, "(label {} ", label.lexeme)?;
+        }
+        out.write_str("(while ")?;
+        self.write(out, &stmt.condition)?;
+        out.write_char(' ')?;
+        self.write_stmt(out, &stmt.body)?;
+        if let Some(increment) = &stmt.increment {
+            out.write_char(' ')?;
+            self.write(out, increment)?;
+        }
+        out.write_char(')')?;
+        if stmt.label.is_some() {
+            out.write_char(')')?;
+        }
+        Ok(())
+    }
+
+    /// Process class declarations, one entry per method
+    ///
+    /// # Examples
+    /// - 'class A { f() {} area { return 1; } class g() {} }'
+    ///   -> '(class A (fun f ()) (getter area (return 1)) (class-fun g ()))'
+    fn visit_class<W: fmt::Write>(&self, out: &mut W, stmt: &stmt::Class) -> fmt::Result {
+        write!(out, "(class {}", stmt.name.lexeme)?;
+        for method in &stmt.methods {
+            out.write_char(' ')?;
+            self.visit_function_stmt(out, "fun", method)?;
+        }
+        for getter in &stmt.getters {
+            write!(out, " (getter {}", getter.name.lexeme)?;
+            self.write_stmts(out, &getter.body)?;
+            out.write_char(')')?;
+        }
+        for method in &stmt.class_methods {
+            out.write_char(' ')?;
+            self.visit_function_stmt(out, "class-fun", method)?;
+        }
+        out.write_char(')')
+    }
+
+    /// Process function declarations
+    ///
+    /// # Examples
+    /// - 'fun add(a, b) { return a + b; }' -> '(fun add (a b) (return (+ a b)))'
+    fn visit_function_stmt<W: fmt::Write>(
+        &self,
+        out: &mut W,
+        keyword: &str,
+        stmt: &stmt::Function,
+    ) -> fmt::Result {
+        write!(out, "({} {} (", keyword, stmt.name.lexeme)?;
+        for (i, param) in stmt.params.iter().enumerate() {
+            if i > 0 {
+                out.write_char(' ')?;
+            }
+            out.write_str(&param.lexeme)?;
+        }
+        out.write_char(')')?;
+        self.write_stmts(out, &stmt.body)?;
+        out.write_char(')')
+    }
+
+    /// Process if statements, with the else branch only when present
+    ///
+    /// An `else if` chain prints flat, one condition and branch pair per
+    /// link, with the final else branch marked by `else`.
+    ///
+    /// # Examples
+    /// - 'if (a) print 1;' -> '(if a (print 1))'
+    /// - 'if (a) print 1; else print 2;' -> '(if a (print 1) (print 2))'
+    /// - 'if (a) print 1; else if (b) print 2; else print 3;'
+    ///   -> '(if a (print 1) b (print 2) else (print 3))'
+    fn visit_if<W: fmt::Write>(&self, out: &mut W, stmt: &stmt::If) -> fmt::Result {
+        out.write_str("(if")?;
+        let mut link = stmt;
+        loop {
+            out.write_char(' ')?;
+            self.write(out, &link.condition)?;
+            out.write_char(' ')?;
+            self.write_stmt(out, &link.then_branch)?;
+            match link.else_branch.as_deref() {
+                Some(Stmt::If(next)) => link = next,
+                Some(else_branch) => {
+                    out.write_str(if std::ptr::eq(link, stmt) {
+                        " "
+                    } else {
+                        " else "
+                    })?;
+                    self.write_stmt(out, else_branch)?;
+                    break;
+                }
+                None => break,
+            }
+        }
+        out.write_char(')')
+    }
+
+    /// Process switch statements
+    ///
+    /// # Examples
+    /// - 'switch (x) { case 1: print 1; default: print 0; }'
+    ///   -> '(switch x (case 1 (print 1)) (default (print 0)))'
+    fn visit_switch<W: fmt::Write>(&self, out: &mut W, stmt: &stmt::Switch) -> fmt::Result {
+        out.write_str("(switch ")?;
+        self.write(out, &stmt.discriminant)?;
+        for (value, body) in &stmt.cases {
+            out.write_str(" (case ")?;
+            self.write(out, value)?;
+            self.write_stmts(out, body)?;
+            out.write_char(')')?;
         }
+        if let Some(default) = &stmt.default {
+            out.write_str(" (default")?;
+            self.write_stmts(out, default)?;
+            out.write_char(')')?;
+        }
+        out.write_char(')')
     }
 
     /// Process binary operator expressions
@@ -38,16 +276,16 @@ impl AstPrinter {
     /// # Examples
     /// - '1 + 2' -> '(+ 1 2)'
     /// - '3 * 4' -> '(* 3 4)'
-    fn visit_binary(&self, expr: &Binary) -> String {
-        self.parenthesize(&expr.operator.lexeme, &[&expr.left, &expr.right])
+    fn visit_binary<W: fmt::Write>(&self, out: &mut W, expr: &Binary) -> fmt::Result {
+        self.parenthesize(out, &expr.operator.lexeme, &[&expr.left, &expr.right])
     }
 
     /// Process grouping (parentheses) expressions
     ///
     /// # Examples
     /// - '(1 + 2)' -> '(group (+ 1 2))'
-    fn visit_grouping(&self, expr: &Grouping) -> String {
-        self.parenthesize("group", &[&expr.expression])
+    fn visit_grouping<W: fmt::Write>(&self, out: &mut W, expr: &Grouping) -> fmt::Result {
+        self.parenthesize(out, "group", &[&expr.expression])
     }
 
     /// Process literal value expressions
@@ -57,12 +295,12 @@ impl AstPrinter {
     /// - `"hello"` -> `"hello"`
     /// - `true` -> `"true"`
     /// - `nil` -> `"nil"`
-    fn visit_literal(&self, expr: &Literal) -> String {
+    fn visit_literal<W: fmt::Write>(&self, out: &mut W, expr: &Literal) -> fmt::Result {
         match &expr.value {
-            LiteralValue::Number(n) => n.to_string(),
-            LiteralValue::String(s) => s.clone(),
-            LiteralValue::Bool(b) => b.to_string(),
-            LiteralValue::Nil => String::from("nil"),
+            LiteralValue::Number(n) => write!(out, "{}", n),
+            LiteralValue::String(s) => out.write_str(s),
+            LiteralValue::Bool(b) => write!(out, "{}", b),
+            LiteralValue::Nil => out.write_str("nil"),
         }
     }
 
@@ -71,26 +309,148 @@ impl AstPrinter {
     /// # Examples
     /// - '-5' -> '(- 5)'
     /// - '!true' -> '(! true)'
-    fn visit_unary(&self, expr: &Unary) -> String {
-        self.parenthesize(&expr.operator.lexeme, &[&expr.right])
+    fn visit_unary<W: fmt::Write>(&self, out: &mut W, expr: &Unary) -> fmt::Result {
+        self.parenthesize(out, &expr.operator.lexeme, &[&expr.right])
+    }
+
+    /// Process variable reference expressions
+    ///
+    /// # Examples
+    /// - 'x' -> 'x'
+    fn visit_variable<W: fmt::Write>(&self, out: &mut W, expr: &Variable) -> fmt::Result {
+        out.write_str(&expr.name.lexeme)
+    }
+
+    /// Process assignment expressions
+    ///
+    /// # Examples
+    /// - 'x = 1' -> '(= x 1)'
+    fn visit_assign<W: fmt::Write>(&self, out: &mut W, expr: &Assign) -> fmt::Result {
+        write!(out, "(= {} ", expr.name.lexeme)?;
+        self.write(out, &expr.value)?;
+        out.write_char(')')
+    }
+
+    /// Process postfix increment/decrement expressions
+    ///
+    /// # Examples
+    /// - 'x++' -> '(x ++)'
+    fn visit_postfix<W: fmt::Write>(&self, out: &mut W, expr: &Postfix) -> fmt::Result {
+        write!(out, "({} {})", expr.name.lexeme, expr.operator.lexeme)
+    }
+
+    /// Process logical expressions
+    ///
+    /// # Examples
+    /// - 'a and b' -> '(and a b)'
+    /// - 'a ?? b' -> '(?? a b)'
+    fn visit_logical<W: fmt::Write>(&self, out: &mut W, expr: &Logical) -> fmt::Result {
+        self.parenthesize(out, &expr.operator.lexeme, &[&expr.left, &expr.right])
+    }
+
+    /// Process call expressions
+    ///
+    /// # Examples
+    /// - 'f(1, 2)' -> '(call f 1 2)'
+    fn visit_call<W: fmt::Write>(&self, out: &mut W, expr: &Call) -> fmt::Result {
+        out.write_str("(call ")?;
+        self.write(out, &expr.callee)?;
+        for argument in &expr.arguments {
+            out.write_char(' ')?;
+            self.write(out, argument)?;
+        }
+        out.write_char(')')
+    }
+
+    /// Process anonymous function expressions, listing only the parameters
+    ///
+    /// # Examples
+    /// - 'fun (a, b) { return a; }' -> '(fun (a b))'
+    fn visit_function<W: fmt::Write>(&self, out: &mut W, expr: &Function) -> fmt::Result {
+        out.write_str("(fun (")?;
+        for (i, param) in expr.params.iter().enumerate() {
+            if i > 0 {
+                out.write_char(' ')?;
+            }
+            out.write_str(&param.lexeme)?;
+        }
+        out.write_str("))")
+    }
+
+    /// Process property access expressions
+    ///
+    /// # Examples
+    /// - 'point.x' -> '(. point x)'
+    fn visit_get<W: fmt::Write>(&self, out: &mut W, expr: &Get) -> fmt::Result {
+        out.write_str("(. ")?;
+        self.write(out, &expr.object)?;
+        write!(out, " {})", expr.name.lexeme)
+    }
+
+    /// Process property assignment expressions
+    ///
+    /// # Examples
+    /// - 'point.x = 1' -> '(= (. point x) 1)'
+    /// - 'point.x += 1' -> '(+= (. point x) 1)'
+    fn visit_set<W: fmt::Write>(&self, out: &mut W, expr: &Set) -> fmt::Result {
+        let operator = expr
+            .operator
+            .as_ref()
+            .map_or("", |operator| &operator.lexeme);
+        write!(out, "({}= (. ", operator)?;
+        self.write(out, &expr.object)?;
+        write!(out, " {}) ", expr.name.lexeme)?;
+        self.write(out, &expr.value)?;
+        out.write_char(')')
+    }
+
+    /// Process block expressions, the value last
+    ///
+    /// # Examples
+    /// - '{ var t = 1; t }' -> '(block-expr (var t 1) t)'
+    fn visit_block_expr<W: fmt::Write>(&self, out: &mut W, expr: &expr::Block) -> fmt::Result {
+        out.write_str("(block-expr")?;
+        self.write_stmts(out, &expr.statements)?;
+        if let Some(value) = &expr.value {
+            out.write_char(' ')?;
+            self.write(out, value)?;
+        }
+        out.write_char(')')
     }
 
     /// Examples
     /// - 'parenthesize("+", &[1,2])' -> "(+ 1 2)"
     /// - 'parenthesize("group", &[expr]) -> "(group ...)"
-    fn parenthesize(&self, name: &str, exprs: &[&Box<Expr>]) -> String {
-        let mut result = String::new();
-
-        result.push('(');
-        result.push_str(name);
+    fn parenthesize<W: fmt::Write>(&self, out: &mut W, name: &str, exprs: &[&Expr]) -> fmt::Result {
+        out.write_char('(')?;
+        out.write_str(name)?;
 
         for expr in exprs {
-            result.push(' ');
-            result.push_str(&self.print(expr));
+            out.write_char(' ')?;
+            self.write(out, expr)?;
         }
 
-        result.push(')');
-        result
+        out.write_char(')')
+    }
+}
+
+impl Default for AstPrinter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// These live here rather than in expr.rs and stmt.rs so the AST does not
+// depend on the printer
+impl fmt::Display for Expr {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        AstPrinter::new().write(f, self)
+    }
+}
+
+impl fmt::Display for Stmt {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        AstPrinter::new().write_stmt(f, self)
     }
 }
 
@@ -134,11 +494,11 @@ mod tests {
     fn test_literal_bool() {
         // Given
         let true_expr = Expr::Literal(Literal {
-            value: LiteralValue::Bool((true)),
+            value: LiteralValue::Bool(true),
         });
 
         let false_expr = Expr::Literal(Literal {
-            value: LiteralValue::Bool((false)),
+            value: LiteralValue::Bool(false),
         });
 
         // When
@@ -179,6 +539,7 @@ mod tests {
                 lexeme: "+".to_string(),
                 literal: None,
                 line: 1,
+                source_name: None,
             },
             right: Box::new(Expr::Literal(Literal {
                 value: LiteralValue::Number(2.0),
@@ -206,6 +567,7 @@ mod tests {
                 lexeme: "==".to_string(),
                 literal: None,
                 line: 1,
+                source_name: None,
             },
             right: Box::new(Expr::Literal(Literal {
                 value: LiteralValue::Number(5.0),
@@ -229,6 +591,7 @@ mod tests {
                 lexeme: "-".to_string(),
                 literal: None,
                 line: 1,
+                source_name: None,
             },
             right: Box::new(Expr::Literal(Literal {
                 value: LiteralValue::Number(5.0),
@@ -251,6 +614,7 @@ mod tests {
                 literal: None,
                 lexeme: "!".to_string(),
                 line: 1,
+                source_name: None,
             },
             right: Box::new(Expr::Literal(Literal {
                 value: LiteralValue::Bool(true),
@@ -290,6 +654,7 @@ mod tests {
                 lexeme: "-".to_string(),
                 literal: None,
                 line: 1,
+                source_name: None,
             },
             right: Box::new(Expr::Unary(Unary {
                 operator: Token {
@@ -297,6 +662,7 @@ mod tests {
                     lexeme: "-".to_string(),
                     literal: None,
                     line: 1,
+                    source_name: None,
                 },
                 right: Box::new(Expr::Literal(Literal {
                     value: LiteralValue::Number(5.0),
@@ -321,6 +687,7 @@ mod tests {
                 lexeme: "-".to_string(),
                 literal: None,
                 line: 1,
+                source_name: None,
             },
             right: Box::new(Expr::Grouping(Grouping {
                 expression: Box::new(Expr::Binary(Binary {
@@ -332,6 +699,7 @@ mod tests {
                         lexeme: "+".to_string(),
                         literal: None,
                         line: 1,
+                        source_name: None,
                     },
                     right: Box::new(Expr::Literal(Literal {
                         value: LiteralValue::Number(2.0),
@@ -358,6 +726,7 @@ mod tests {
                     lexeme: "-".to_string(),
                     literal: None,
                     line: 1,
+                    source_name: None,
                 },
                 right: Box::new(Expr::Literal(Literal {
                     value: LiteralValue::Number(123.0),
@@ -368,6 +737,7 @@ mod tests {
                 lexeme: "*".to_string(),
                 literal: None,
                 line: 1,
+                source_name: None,
             },
             right: Box::new(Expr::Grouping(Grouping {
                 expression: Box::new(Expr::Literal(Literal {
@@ -383,4 +753,185 @@ mod tests {
         // Then
         assert_eq!(result, "(* (- 123) (group 45.67))");
     }
+
+    #[test]
+    fn test_display_matches_print() {
+        // Given
+        // -123 * (45.67)
+        let expr = Expr::Binary(Binary {
+            left: Box::new(Expr::Unary(Unary {
+                operator: Token {
+                    token_type: TokenType::Minus,
+                    lexeme: "-".to_string(),
+                    literal: None,
+                    line: 1,
+                    source_name: None,
+                },
+                right: Box::new(Expr::Literal(Literal {
+                    value: LiteralValue::Number(123.0),
+                })),
+            })),
+            operator: Token {
+                token_type: TokenType::Star,
+                lexeme: "*".to_string(),
+                literal: None,
+                line: 1,
+                source_name: None,
+            },
+            right: Box::new(Expr::Grouping(Grouping {
+                expression: Box::new(Expr::Literal(Literal {
+                    value: LiteralValue::Number(45.67),
+                })),
+            })),
+        });
+
+        // When
+        let displayed = format!("{}", expr);
+
+        // Then
+        assert_eq!(displayed, AstPrinter::new().print(&expr));
+        assert_eq!(displayed, "(* (- 123) (group 45.67))");
+    }
+
+    #[test]
+    fn test_default_matches_new() {
+        let expr = Expr::Literal(Literal {
+            value: LiteralValue::Nil,
+        });
+
+        let printer: AstPrinter = Default::default();
+
+        assert_eq!(printer.print(&expr), AstPrinter::new().print(&expr));
+    }
+
+    // ===== Statements =====
+
+    fn print_program(source: &str) -> Vec<String> {
+        let tokens = crate::scanner::Scanner::new(source.to_string()).scan_tokens();
+        let statements = crate::parser::Parser::new(tokens).parse_program().unwrap();
+        let printer = AstPrinter::new();
+        statements
+            .iter()
+            .map(|stmt| printer.print_stmt(stmt))
+            .collect()
+    }
+
+    #[test]
+    fn test_print_stmt_print_and_expression() {
+        assert_eq!(
+            print_program("print 1 + 2, x; f(1);"),
+            vec!["(print (+ 1 2) x)", "(expr (call f 1))"]
+        );
+    }
+
+    #[test]
+    fn test_print_stmt_var() {
+        assert_eq!(
+            print_program("var a = 1; var b;"),
+            vec!["(var a 1)", "(var b)"]
+        );
+    }
+
+    #[test]
+    fn test_stmt_display_matches_print_stmt() {
+        let tokens =
+            crate::scanner::Scanner::new("print 1 + 2; var a = \"x\";".to_string()).scan_tokens();
+        let statements = crate::parser::Parser::new(tokens).parse_program().unwrap();
+
+        assert_eq!(statements[0].to_string(), "(print (+ 1 2))");
+        assert_eq!(statements[1].to_string(), "(var a x)");
+        assert_eq!(
+            statements[1].to_string(),
+            AstPrinter::new().print_stmt(&statements[1])
+        );
+    }
+
+    #[test]
+    fn test_print_stmt_block() {
+        assert_eq!(
+            print_program("{ var a = 1; print a; } {}"),
+            vec!["(block (var a 1) (print a))", "(block)"]
+        );
+    }
+
+    #[test]
+    fn test_print_stmt_if() {
+        assert_eq!(
+            print_program("if (a) print 1; else print 2; if (b) {}"),
+            vec!["(if a (print 1) (print 2))", "(if b (block))"]
+        );
+    }
+
+    #[test]
+    fn test_print_stmt_else_if_chain_is_flat() {
+        let source = "if (a) print 1; else if (b) print 2; else print 3;";
+
+        assert_eq!(
+            print_program(source),
+            vec!["(if a (print 1) b (print 2) else (print 3))"]
+        );
+    }
+
+    #[test]
+    fn test_print_stmt_else_if_chain_without_else() {
+        assert_eq!(
+            print_program("if (a) print 1; else if (b) print 2;"),
+            vec!["(if a (print 1) b (print 2))"]
+        );
+    }
+
+    #[test]
+    fn test_print_stmt_if_inside_braced_else_stays_nested() {
+        assert_eq!(
+            print_program("if (a) print 1; else { if (b) print 2; }"),
+            vec!["(if a (print 1) (block (if b (print 2))))"]
+        );
+    }
+
+    #[test]
+    fn test_print_stmt_while_and_desugared_for() {
+        assert_eq!(
+            print_program("while (i < 3) i = i + 1; for (var j = 0; j < 2; j++) print j;"),
+            vec![
+                "(while (< i 3) (expr (= i (+ i 1))))",
+                "(block (var j 0) (while (< j 2) (print j) (j ++)))"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_print_stmt_function_and_return() {
+        assert_eq!(
+            print_program("fun add(a, b) { return a + b; } fun f() { return; }"),
+            vec!["(fun add (a b) (return (+ a b)))", "(fun f () (return))"]
+        );
+    }
+
+    #[test]
+    fn test_print_stmt_switch() {
+        assert_eq!(
+            print_program("switch (x) { case 1: print 1; case 2: default: print 0; }"),
+            vec!["(switch x (case 1 (print 1)) (case 2) (default (print 0)))"]
+        );
+    }
+
+    #[test]
+    fn test_print_stmt_class() {
+        assert_eq!(
+            print_program(
+                "class A { init(x) { this.x = x; } area { return this.x; } class make() { return A(1); } }"
+            ),
+            vec![
+                "(class A (fun init (x) (expr (= (. this x) x))) (getter area (return (. this x))) (class-fun make () (return (call A 1))))"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_print_block_expression() {
+        assert_eq!(
+            print_program("var v = { var t = 1; t };"),
+            vec!["(var v (block-expr (var t 1) t))"]
+        );
+    }
 }
@@ -0,0 +1,299 @@
+use std::fmt;
+
+use crate::expr::Expr;
+use crate::lox_value::LoxValue;
+use crate::token::TokenType;
+
+/// Instruction of the experimental stack machine
+///
+/// Each operator pops its operands, the right one on top, and pushes
+/// its result.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Op {
+    Const(LoxValue),
+    Add,
+    Sub,
+    Negate,
+    Mul,
+    Div,
+    Equal,
+    Greater,
+    Less,
+}
+
+/// Expression outside the supported subset, or a failed operation
+///
+/// # Examples
+/// - `compile` of `a + 1` -> `Can't compile variable expressions.`
+/// - `run` of `[Const(1), Const(nil), Add]`
+///   -> `Operands must be two numbers or two strings.`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BytecodeError {
+    pub message: String,
+}
+
+impl BytecodeError {
+    fn new(message: &str) -> Self {
+        Self {
+            message: message.to_string(),
+        }
+    }
+}
+
+impl fmt::Display for BytecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+impl std::error::Error for BytecodeError {}
+
+/// Lower an expression into stack operations, operands before operators
+///
+/// Only literals, grouping, unary `-` and the binary `+ - * / == > <`
+/// are supported.
+///
+/// # Examples
+/// - `1 + 2 * 3` -> `[Const(1), Const(2), Const(3), Mul, Add]`
+/// - `-(4 - 1)` -> `[Const(4), Const(1), Sub, Negate]`
+pub fn compile(expr: &Expr) -> Result<Vec<Op>, BytecodeError> {
+    let mut ops = Vec::new();
+    lower(expr, &mut ops)?;
+    Ok(ops)
+}
+
+fn lower(expr: &Expr, ops: &mut Vec<Op>) -> Result<(), BytecodeError> {
+    match expr {
+        Expr::Literal(literal) => ops.push(Op::Const(literal.value.clone().into())),
+        Expr::Grouping(grouping) => lower(&grouping.expression, ops)?,
+        Expr::Unary(unary) if unary.operator.token_type == TokenType::Minus => {
+            lower(&unary.right, ops)?;
+            ops.push(Op::Negate);
+        }
+        Expr::Binary(binary) => {
+            let op = match binary.operator.token_type {
+                TokenType::Plus => Op::Add,
+                TokenType::Minus => Op::Sub,
+                TokenType::Star => Op::Mul,
+                TokenType::Slash => Op::Div,
+                TokenType::EqualEqual => Op::Equal,
+                TokenType::Greater => Op::Greater,
+                TokenType::Less => Op::Less,
+                _ => {
+                    return Err(BytecodeError::new(&format!(
+                        "Can't compile operator '{}'.",
+                        binary.operator.lexeme
+                    )));
+                }
+            };
+            lower(&binary.left, ops)?;
+            lower(&binary.right, ops)?;
+            ops.push(op);
+        }
+        Expr::Unary(unary) => {
+            return Err(BytecodeError::new(&format!(
+                "Can't compile operator '{}'.",
+                unary.operator.lexeme
+            )));
+        }
+        other => {
+            return Err(BytecodeError::new(&format!(
+                "Can't compile {} expressions.",
+                kind(other)
+            )));
+        }
+    }
+    Ok(())
+}
+
+// Name of an unsupported expression kind, for error messages
+fn kind(expr: &Expr) -> &'static str {
+    match expr {
+        Expr::Assign(_) => "assignment",
+        Expr::Block(_) => "block",
+        Expr::Call(_) => "call",
+        Expr::Function(_) => "function",
+        Expr::Get(_) | Expr::Set(_) => "property",
+        Expr::Logical(_) => "logical",
+        Expr::Postfix(_) => "increment",
+        Expr::Super(_) => "super",
+        Expr::This(_) => "this",
+        Expr::Variable(_) => "variable",
+        Expr::Binary(_) | Expr::Grouping(_) | Expr::Literal(_) | Expr::Unary(_) => {
+            unreachable!("`lower` handles these itself")
+        }
+    }
+}
+
+/// Execute `ops` on an empty stack and return the value left on top
+///
+/// Operators behave like the tree-walking interpreter's with its default
+/// settings, so division by zero is an error.
+///
+/// # Example
+/// ```ignore
+/// let ops = compile(&Parser::new(tokens).parse()?)?;
+/// assert_eq!(run(&ops)?, LoxValue::Number(7.0));
+/// ```
+pub fn run(ops: &[Op]) -> Result<LoxValue, BytecodeError> {
+    let mut stack = Vec::new();
+    for op in ops {
+        let value = match op {
+            Op::Const(value) => value.clone(),
+            Op::Negate => match pop(&mut stack)? {
+                LoxValue::Number(n) => LoxValue::Number(-n),
+                _ => return Err(BytecodeError::new("Operand must be a number.")),
+            },
+            Op::Add => match pop_pair(&mut stack)? {
+                (LoxValue::Number(l), LoxValue::Number(r)) => LoxValue::Number(l + r),
+                (LoxValue::String(l), LoxValue::String(r)) => LoxValue::String(l + &r),
+                _ => {
+                    return Err(BytecodeError::new(
+                        "Operands must be two numbers or two strings.",
+                    ));
+                }
+            },
+            Op::Sub => LoxValue::Number(number_operands(&mut stack, |l, r| l - r)?),
+            Op::Mul => LoxValue::Number(number_operands(&mut stack, |l, r| l * r)?),
+            Op::Div => {
+                let (l, r) = numbers(pop_pair(&mut stack)?)?;
+                if r == 0.0 {
+                    return Err(BytecodeError::new("Division by zero."));
+                }
+                LoxValue::Number(l / r)
+            }
+            Op::Equal => {
+                let (l, r) = pop_pair(&mut stack)?;
+                LoxValue::Bool(l == r)
+            }
+            Op::Greater => LoxValue::Bool(number_operands(&mut stack, |l, r| l > r)?),
+            Op::Less => LoxValue::Bool(number_operands(&mut stack, |l, r| l < r)?),
+        };
+        stack.push(value);
+    }
+    pop(&mut stack)
+}
+
+fn pop(stack: &mut Vec<LoxValue>) -> Result<LoxValue, BytecodeError> {
+    stack
+        .pop()
+        .ok_or_else(|| BytecodeError::new("Stack underflow."))
+}
+
+// Right operand on top, so it comes off first
+fn pop_pair(stack: &mut Vec<LoxValue>) -> Result<(LoxValue, LoxValue), BytecodeError> {
+    let right = pop(stack)?;
+    let left = pop(stack)?;
+    Ok((left, right))
+}
+
+fn numbers(operands: (LoxValue, LoxValue)) -> Result<(f64, f64), BytecodeError> {
+    match operands {
+        (LoxValue::Number(l), LoxValue::Number(r)) => Ok((l, r)),
+        _ => Err(BytecodeError::new("Operands must be numbers.")),
+    }
+}
+
+fn number_operands<T>(
+    stack: &mut Vec<LoxValue>,
+    operator: impl FnOnce(f64, f64) -> T,
+) -> Result<T, BytecodeError> {
+    let (l, r) = numbers(pop_pair(stack)?)?;
+    Ok(operator(l, r))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::interpreter::Interpreter;
+    use crate::parser::Parser;
+    use crate::scanner::Scanner;
+
+    fn parse(source: &str) -> Expr {
+        let tokens = Scanner::new(source.to_string()).scan_tokens();
+        Parser::new(tokens).parse().unwrap()
+    }
+
+    fn run_source(source: &str) -> Result<LoxValue, BytecodeError> {
+        run(&compile(&parse(source))?)
+    }
+
+    #[test]
+    fn test_compile_emits_operands_before_operators() {
+        let ops = compile(&parse("1 + 2 * 3")).unwrap();
+
+        assert_eq!(
+            ops,
+            vec![
+                Op::Const(LoxValue::Number(1.0)),
+                Op::Const(LoxValue::Number(2.0)),
+                Op::Const(LoxValue::Number(3.0)),
+                Op::Mul,
+                Op::Add,
+            ]
+        );
+    }
+
+    #[test]
+    fn test_results_match_tree_walking_interpreter() {
+        for source in [
+            "1 + 2 * 3",
+            "(1 + 2) * 3",
+            "10 - 4 - 3",
+            "-(8 / 2)",
+            "\"ab\" + \"cd\"",
+            "1 + 1 == 2",
+            "nil == false",
+            "3 > 2",
+            "-1 < -2",
+            "true == (2 < 3)",
+        ] {
+            // Given
+            let expected = Interpreter::new().eval(source).unwrap();
+
+            // When
+            let actual = run_source(source).unwrap();
+
+            // Then
+            assert_eq!(actual, expected, "{}", source);
+        }
+    }
+
+    #[test]
+    fn test_run_errors_like_interpreter() {
+        assert_eq!(
+            run_source("1 + nil").unwrap_err().message,
+            "Operands must be two numbers or two strings."
+        );
+        assert_eq!(
+            run_source("-\"a\"").unwrap_err().message,
+            "Operand must be a number."
+        );
+        assert_eq!(
+            run_source("1 / 0").unwrap_err().message,
+            "Division by zero."
+        );
+    }
+
+    #[test]
+    fn test_compile_rejects_unsupported_expressions() {
+        assert_eq!(
+            compile(&parse("a + 1")).unwrap_err().message,
+            "Can't compile variable expressions."
+        );
+        assert_eq!(
+            compile(&parse("1 >= 2")).unwrap_err().message,
+            "Can't compile operator '>='."
+        );
+        assert_eq!(
+            compile(&parse("!true")).unwrap_err().message,
+            "Can't compile operator '!'."
+        );
+    }
+
+    #[test]
+    fn test_run_reports_stack_underflow() {
+        assert_eq!(run(&[Op::Add]).unwrap_err().message, "Stack underflow.");
+        assert_eq!(run(&[]).unwrap_err().message, "Stack underflow.");
+    }
+}
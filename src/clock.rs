@@ -0,0 +1,56 @@
+use std::fmt;
+use std::thread;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// Time source behind `clock`, `clock_millis` and `sleep`
+///
+/// The interpreter uses `SystemClock` unless given another with
+/// `Interpreter::with_clock`, so tests can check timing code without
+/// waiting.
+pub trait Clock: fmt::Debug {
+    /// Time elapsed since the Unix epoch
+    fn now(&self) -> Duration;
+
+    /// Block for `duration`
+    fn sleep(&mut self, duration: Duration);
+}
+
+/// The real wall clock
+#[derive(Debug, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> Duration {
+        // A clock set before 1970 reads as the epoch
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+    }
+
+    fn sleep(&mut self, duration: Duration) {
+        thread::sleep(duration);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Instant;
+
+    #[test]
+    fn test_system_clock_is_after_2020() {
+        // 2020-01-01T00:00:00Z
+        assert!(SystemClock.now() > Duration::from_secs(1_577_836_800));
+    }
+
+    #[test]
+    fn test_system_clock_sleep_waits() {
+        // Instant is monotonic, so a wall clock adjustment cannot fail this
+        let mut clock = SystemClock;
+        let before = Instant::now();
+
+        clock.sleep(Duration::from_millis(5));
+
+        assert!(before.elapsed() >= Duration::from_millis(5));
+    }
+}
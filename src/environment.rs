@@ -0,0 +1,432 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::rc::Rc;
+
+use crate::lox_value::LoxValue;
+use crate::runtime_error::RuntimeError;
+use crate::token::Token;
+
+/// Bindings of one scope saved by `Environment::snapshot`
+///
+/// Values are saved as they are, so an instance in one is shared with the
+/// scope: fields set on it later survive `restore`.
+#[derive(Debug, Clone)]
+pub struct EnvSnapshot {
+    values: HashMap<String, LoxValue>,
+}
+
+/// Variable bindings of one scope
+///
+/// Lookups that miss in this scope continue in the enclosing one,
+/// up to the global scope which has no enclosing environment.
+#[derive(Debug, Default)]
+pub struct Environment {
+    values: HashMap<String, LoxValue>,
+    enclosing: Option<Rc<RefCell<Environment>>>,
+}
+
+impl Environment {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_enclosing(enclosing: Rc<RefCell<Environment>>) -> Self {
+        Self {
+            values: HashMap::new(),
+            enclosing: Some(enclosing),
+        }
+    }
+
+    // Bind a name in this scope, shadowing any previous binding
+    pub fn define(&mut self, name: String, value: LoxValue) {
+        self.values.insert(name, value);
+    }
+
+    pub fn get(&self, name: &Token) -> Result<LoxValue, RuntimeError> {
+        if let Some(value) = self.values.get(&name.lexeme) {
+            return Ok(value.clone());
+        }
+
+        match &self.enclosing {
+            Some(enclosing) => enclosing.borrow().get(name),
+            None => Err(undefined(name)),
+        }
+    }
+
+    // Update an existing binding; assigning never creates a variable
+    pub fn assign(&mut self, name: &Token, value: LoxValue) -> Result<(), RuntimeError> {
+        if let Some(slot) = self.values.get_mut(&name.lexeme) {
+            *slot = value;
+            return Ok(());
+        }
+
+        match &self.enclosing {
+            Some(enclosing) => enclosing.borrow_mut().assign(name, value),
+            None => Err(undefined(name)),
+        }
+    }
+
+    /// Value of `name` in the scope exactly `distance` levels out
+    ///
+    /// The resolver has already found the binding there, so no other scope
+    /// is searched. A missing binding still errors rather than panicking.
+    ///
+    /// # Examples
+    /// - distance 0: this scope
+    /// - distance 2: the enclosing scope of the enclosing scope
+    pub fn get_at(&self, distance: usize, name: &Token) -> Result<LoxValue, RuntimeError> {
+        if distance == 0 {
+            return self
+                .values
+                .get(&name.lexeme)
+                .cloned()
+                .ok_or_else(|| undefined(name));
+        }
+
+        match &self.enclosing {
+            Some(enclosing) => enclosing.borrow().get_at(distance - 1, name),
+            None => Err(undefined(name)),
+        }
+    }
+
+    /// Update `name` in the scope exactly `distance` levels out, see `get_at`
+    pub fn assign_at(
+        &mut self,
+        distance: usize,
+        name: &Token,
+        value: LoxValue,
+    ) -> Result<(), RuntimeError> {
+        if distance == 0 {
+            return match self.values.get_mut(&name.lexeme) {
+                Some(slot) => {
+                    *slot = value;
+                    Ok(())
+                }
+                None => Err(undefined(name)),
+            };
+        }
+
+        match &self.enclosing {
+            Some(enclosing) => enclosing.borrow_mut().assign_at(distance - 1, name, value),
+            None => Err(undefined(name)),
+        }
+    }
+
+    /// Save this scope's bindings, to roll back to with `restore`
+    ///
+    /// Enclosing scopes are not saved.
+    ///
+    /// # Example
+    /// ```ignore
+    /// let snapshot = globals.borrow().snapshot();
+    /// if interpreter.run(source).is_err() {
+    ///     globals.borrow_mut().restore(snapshot);
+    /// }
+    /// ```
+    pub fn snapshot(&self) -> EnvSnapshot {
+        EnvSnapshot {
+            values: self.values.clone(),
+        }
+    }
+
+    /// Put back the bindings saved by `snapshot`
+    ///
+    /// Variables defined since are removed, and reassigned ones get their
+    /// saved values back.
+    pub fn restore(&mut self, snapshot: EnvSnapshot) {
+        self.values = snapshot.values;
+    }
+
+    /// Bindings of this scope only, sorted by name
+    pub fn dump(&self) -> Vec<(String, LoxValue)> {
+        let mut bindings: Vec<(String, LoxValue)> = self
+            .values
+            .iter()
+            .map(|(name, value)| (name.clone(), value.clone()))
+            .collect();
+        bindings.sort_by(|a, b| a.0.cmp(&b.0));
+        bindings
+    }
+
+    /// Every binding visible from this scope, sorted by name
+    ///
+    /// A name shadowed by an inner scope is listed once, with the inner value.
+    pub fn dump_all(&self) -> Vec<(String, LoxValue)> {
+        let mut bindings = match &self.enclosing {
+            Some(enclosing) => enclosing.borrow().dump_all(),
+            None => Vec::new(),
+        };
+        bindings.retain(|(name, _)| !self.values.contains_key(name));
+        bindings.extend(self.dump());
+        bindings.sort_by(|a, b| a.0.cmp(&b.0));
+        bindings
+    }
+}
+
+fn undefined(name: &Token) -> RuntimeError {
+    RuntimeError::new(name, &format!("Undefined variable '{}'.", name.lexeme))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::token::TokenType;
+
+    fn identifier(name: &str) -> Token {
+        Token::new(TokenType::Identifier, name.to_string(), None, 1)
+    }
+
+    #[test]
+    fn test_define_and_get() {
+        let mut env = Environment::new();
+        env.define("a".to_string(), LoxValue::Number(1.0));
+
+        assert_eq!(env.get(&identifier("a")).unwrap(), LoxValue::Number(1.0));
+    }
+
+    #[test]
+    fn test_get_undefined_variable() {
+        let env = Environment::new();
+
+        let error = env.get(&identifier("missing")).unwrap_err();
+        assert_eq!(error.message, "Undefined variable 'missing'.");
+    }
+
+    #[test]
+    fn test_assign_existing_variable() {
+        let mut env = Environment::new();
+        env.define("a".to_string(), LoxValue::Number(1.0));
+
+        env.assign(&identifier("a"), LoxValue::Number(2.0)).unwrap();
+        assert_eq!(env.get(&identifier("a")).unwrap(), LoxValue::Number(2.0));
+    }
+
+    #[test]
+    fn test_assign_undefined_variable() {
+        let mut env = Environment::new();
+
+        assert!(env.assign(&identifier("a"), LoxValue::Nil).is_err());
+    }
+
+    #[test]
+    fn test_enclosing_lookup_and_assign() {
+        let globals = Rc::new(RefCell::new(Environment::new()));
+        globals
+            .borrow_mut()
+            .define("a".to_string(), LoxValue::Number(1.0));
+        let mut local = Environment::with_enclosing(Rc::clone(&globals));
+
+        assert_eq!(local.get(&identifier("a")).unwrap(), LoxValue::Number(1.0));
+
+        local
+            .assign(&identifier("a"), LoxValue::Number(3.0))
+            .unwrap();
+        assert_eq!(
+            globals.borrow().get(&identifier("a")).unwrap(),
+            LoxValue::Number(3.0)
+        );
+    }
+
+    #[test]
+    fn test_shadowing_does_not_touch_enclosing() {
+        let globals = Rc::new(RefCell::new(Environment::new()));
+        globals
+            .borrow_mut()
+            .define("a".to_string(), LoxValue::Number(1.0));
+        let mut local = Environment::with_enclosing(Rc::clone(&globals));
+        local.define("a".to_string(), LoxValue::Number(2.0));
+
+        assert_eq!(local.get(&identifier("a")).unwrap(), LoxValue::Number(2.0));
+        assert_eq!(
+            globals.borrow().get(&identifier("a")).unwrap(),
+            LoxValue::Number(1.0)
+        );
+    }
+
+    #[test]
+    fn test_dump_sorted_by_name() {
+        // Given
+        let mut env = Environment::new();
+        env.define("b".to_string(), LoxValue::Number(2.0));
+        env.define("c".to_string(), LoxValue::Nil);
+        env.define("a".to_string(), LoxValue::String("one".to_string()));
+
+        // When
+        let dump = env.dump();
+
+        // Then
+        assert_eq!(
+            dump,
+            vec![
+                ("a".to_string(), LoxValue::String("one".to_string())),
+                ("b".to_string(), LoxValue::Number(2.0)),
+                ("c".to_string(), LoxValue::Nil),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_dump_only_current_scope() {
+        let globals = Rc::new(RefCell::new(Environment::new()));
+        globals
+            .borrow_mut()
+            .define("a".to_string(), LoxValue::Number(1.0));
+        let mut local = Environment::with_enclosing(Rc::clone(&globals));
+        local.define("b".to_string(), LoxValue::Number(2.0));
+
+        assert_eq!(local.dump(), vec![("b".to_string(), LoxValue::Number(2.0))]);
+    }
+
+    #[test]
+    fn test_dump_all_walks_enclosing_scopes() {
+        let globals = Rc::new(RefCell::new(Environment::new()));
+        globals
+            .borrow_mut()
+            .define("a".to_string(), LoxValue::Number(1.0));
+        globals
+            .borrow_mut()
+            .define("z".to_string(), LoxValue::Number(26.0));
+        let mut local = Environment::with_enclosing(Rc::clone(&globals));
+        local.define("a".to_string(), LoxValue::Number(2.0));
+        local.define("m".to_string(), LoxValue::Bool(true));
+
+        assert_eq!(
+            local.dump_all(),
+            vec![
+                ("a".to_string(), LoxValue::Number(2.0)),
+                ("m".to_string(), LoxValue::Bool(true)),
+                ("z".to_string(), LoxValue::Number(26.0)),
+            ]
+        );
+    }
+
+    // Three scopes, each defining `a` and one own name:
+    // global (a = 0, g), middle (a = 1, m), local (a = 2, l)
+    fn three_scopes() -> (
+        Rc<RefCell<Environment>>,
+        Rc<RefCell<Environment>>,
+        Environment,
+    ) {
+        let globals = Rc::new(RefCell::new(Environment::new()));
+        globals
+            .borrow_mut()
+            .define("a".to_string(), LoxValue::Number(0.0));
+        globals.borrow_mut().define("g".to_string(), LoxValue::Nil);
+        let middle = Rc::new(RefCell::new(Environment::with_enclosing(Rc::clone(
+            &globals,
+        ))));
+        middle
+            .borrow_mut()
+            .define("a".to_string(), LoxValue::Number(1.0));
+        middle.borrow_mut().define("m".to_string(), LoxValue::Nil);
+        let mut local = Environment::with_enclosing(Rc::clone(&middle));
+        local.define("a".to_string(), LoxValue::Number(2.0));
+        local.define("l".to_string(), LoxValue::Nil);
+        (globals, middle, local)
+    }
+
+    #[test]
+    fn test_get_at_distances() {
+        let (_globals, _middle, local) = three_scopes();
+
+        assert_eq!(
+            local.get_at(0, &identifier("a")).unwrap(),
+            LoxValue::Number(2.0)
+        );
+        assert_eq!(
+            local.get_at(1, &identifier("a")).unwrap(),
+            LoxValue::Number(1.0)
+        );
+        assert_eq!(
+            local.get_at(2, &identifier("a")).unwrap(),
+            LoxValue::Number(0.0)
+        );
+    }
+
+    #[test]
+    fn test_assign_at_distances() {
+        // Given
+        let (globals, middle, mut local) = three_scopes();
+
+        // When
+        local
+            .assign_at(0, &identifier("a"), LoxValue::Number(20.0))
+            .unwrap();
+        local
+            .assign_at(1, &identifier("a"), LoxValue::Number(10.0))
+            .unwrap();
+        local
+            .assign_at(2, &identifier("a"), LoxValue::Number(5.0))
+            .unwrap();
+
+        // Then
+        assert_eq!(
+            local.get_at(0, &identifier("a")).unwrap(),
+            LoxValue::Number(20.0)
+        );
+        assert_eq!(
+            middle.borrow().dump()[0],
+            ("a".to_string(), LoxValue::Number(10.0))
+        );
+        assert_eq!(
+            globals.borrow().dump()[0],
+            ("a".to_string(), LoxValue::Number(5.0))
+        );
+    }
+
+    #[test]
+    fn test_get_at_does_not_search_other_scopes() {
+        let (_globals, _middle, local) = three_scopes();
+
+        // `g` exists, but only two levels out
+        let error = local.get_at(1, &identifier("g")).unwrap_err();
+        assert_eq!(error.message, "Undefined variable 'g'.");
+        assert!(local.get_at(0, &identifier("m")).is_err());
+    }
+
+    #[test]
+    fn test_at_distance_past_global_scope_errors() {
+        let (_globals, _middle, mut local) = three_scopes();
+
+        assert!(local.get_at(3, &identifier("a")).is_err());
+        assert!(local.assign_at(3, &identifier("a"), LoxValue::Nil).is_err());
+        assert!(local.assign_at(1, &identifier("l"), LoxValue::Nil).is_err());
+    }
+
+    #[test]
+    fn test_restore_rolls_back_to_snapshot() {
+        // Given
+        let mut env = Environment::new();
+        env.define("a".to_string(), LoxValue::Number(1.0));
+        env.define("b".to_string(), LoxValue::String("x".to_string()));
+        let snapshot = env.snapshot();
+
+        // When
+        env.assign(&identifier("a"), LoxValue::Number(2.0)).unwrap();
+        env.define("c".to_string(), LoxValue::Nil);
+        env.restore(snapshot);
+
+        // Then
+        assert_eq!(
+            env.dump(),
+            vec![
+                ("a".to_string(), LoxValue::Number(1.0)),
+                ("b".to_string(), LoxValue::String("x".to_string())),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_restore_after_failed_run() {
+        let mut interpreter = crate::interpreter::Interpreter::new();
+        interpreter.run("var total = 10;").unwrap();
+        let globals = interpreter.globals();
+        let snapshot = globals.borrow().snapshot();
+
+        assert!(interpreter.run("total = 0; var x = nil + 1;").is_err());
+        globals.borrow_mut().restore(snapshot);
+
+        assert_eq!(interpreter.eval("total").unwrap(), LoxValue::Number(10.0));
+        assert!(interpreter.eval("x").is_err());
+    }
+}
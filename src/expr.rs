@@ -1,14 +1,33 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use crate::stmt::Stmt;
 use crate::token::Token;
 
+/// Fresh id for a node that names a variable: `Assign`, `Postfix`, `This`
+/// and `Variable`
+///
+/// The parser gives each such node its own id, which keys the resolver's
+/// table of scope depths. Ids come from one counter shared by every parser,
+/// so trees from separate parses, such as successive REPL lines run by one
+/// interpreter, never share one. A cloned node keeps its id; the parser
+/// only clones nodes into the same scope, where they resolve alike.
+pub fn next_id() -> usize {
+    static NEXT_ID: AtomicUsize = AtomicUsize::new(0);
+    NEXT_ID.fetch_add(1, Ordering::Relaxed)
+}
+
 #[derive(Debug, Clone, PartialEq)]
 pub enum Expr {
     Assign(Assign),
     Binary(Binary),
+    Block(Block),
     Call(Call),
+    Function(Function),
     Get(Get),
     Grouping(Grouping),
     Literal(Literal),
     Logical(Logical),
+    Postfix(Postfix),
     Set(Set),
     Super(Super),
     This(This),
@@ -16,6 +35,84 @@ pub enum Expr {
     Variable(Variable),
 }
 
+impl Expr {
+    /// Whether two trees have the same shape, operators and values
+    ///
+    /// Unlike `==`, token lines, source names and node ids are ignored, so
+    /// the same source parsed at different places compares equal.
+    ///
+    /// # Example
+    /// ```ignore
+    /// // `1+2` scanned on line 1 and on line 5
+    /// assert!(first.structurally_eq(&later));
+    /// assert_ne!(first, later);
+    /// ```
+    pub fn structurally_eq(&self, other: &Expr) -> bool {
+        match (self, other) {
+            (Expr::Assign(a), Expr::Assign(b)) => {
+                a.name.same_kind(&b.name) && a.value.structurally_eq(&b.value)
+            }
+            (Expr::Binary(a), Expr::Binary(b)) => {
+                a.operator.same_kind(&b.operator)
+                    && a.left.structurally_eq(&b.left)
+                    && a.right.structurally_eq(&b.right)
+            }
+            (Expr::Block(a), Expr::Block(b)) => {
+                same_each(&a.statements, &b.statements, Stmt::structurally_eq)
+                    && same_option(&a.value, &b.value, |a, b| a.structurally_eq(b))
+            }
+            (Expr::Call(a), Expr::Call(b)) => {
+                a.callee.structurally_eq(&b.callee)
+                    && same_each(&a.arguments, &b.arguments, Expr::structurally_eq)
+            }
+            (Expr::Function(a), Expr::Function(b)) => {
+                same_each(&a.params, &b.params, Token::same_kind)
+                    && same_each(&a.body, &b.body, Stmt::structurally_eq)
+            }
+            (Expr::Get(a), Expr::Get(b)) => {
+                a.name.same_kind(&b.name) && a.object.structurally_eq(&b.object)
+            }
+            (Expr::Grouping(a), Expr::Grouping(b)) => a.expression.structurally_eq(&b.expression),
+            (Expr::Literal(a), Expr::Literal(b)) => a.value == b.value,
+            (Expr::Logical(a), Expr::Logical(b)) => {
+                a.operator.same_kind(&b.operator)
+                    && a.left.structurally_eq(&b.left)
+                    && a.right.structurally_eq(&b.right)
+            }
+            (Expr::Postfix(a), Expr::Postfix(b)) => {
+                a.name.same_kind(&b.name) && a.operator.same_kind(&b.operator)
+            }
+            (Expr::Set(a), Expr::Set(b)) => {
+                a.name.same_kind(&b.name)
+                    && same_option(&a.operator, &b.operator, Token::same_kind)
+                    && a.object.structurally_eq(&b.object)
+                    && a.value.structurally_eq(&b.value)
+            }
+            (Expr::Super(a), Expr::Super(b)) => a.method.same_kind(&b.method),
+            (Expr::This(_), Expr::This(_)) => true,
+            (Expr::Unary(a), Expr::Unary(b)) => {
+                a.operator.same_kind(&b.operator) && a.right.structurally_eq(&b.right)
+            }
+            (Expr::Variable(a), Expr::Variable(b)) => a.name.same_kind(&b.name),
+            _ => false,
+        }
+    }
+}
+
+// Same length and pairwise equal under `eq`
+pub(crate) fn same_each<T>(a: &[T], b: &[T], eq: impl Fn(&T, &T) -> bool) -> bool {
+    a.len() == b.len() && a.iter().zip(b).all(|(a, b)| eq(a, b))
+}
+
+// Both absent, or both present and equal under `eq`
+pub(crate) fn same_option<T>(a: &Option<T>, b: &Option<T>, eq: impl Fn(&T, &T) -> bool) -> bool {
+    match (a, b) {
+        (Some(a), Some(b)) => eq(a, b),
+        (None, None) => true,
+        _ => false,
+    }
+}
+
 /// Assignment expression
 ///
 /// # Examples
@@ -24,6 +121,7 @@ pub enum Expr {
 /// - `name = "Bob"`
 #[derive(Debug, Clone, PartialEq)]
 pub struct Assign {
+    pub id: usize,
     pub name: Token,
     pub value: Box<Expr>,
 }
@@ -43,6 +141,20 @@ pub struct Binary {
     pub right: Box<Expr>,
 }
 
+/// Block expression, whose value is its trailing expression
+///
+/// The statements run in a new scope, like a block statement. Without a
+/// trailing expression the block evaluates to nil.
+///
+/// # Examples
+/// - `{ var t = 1; t + 1 }` -> `2`
+/// - `{ print "side effect"; }` -> `nil`
+#[derive(Debug, Clone, PartialEq)]
+pub struct Block {
+    pub statements: Vec<Stmt>,
+    pub value: Option<Box<Expr>>,
+}
+
 /// Function call expression
 ///
 /// # Examples
@@ -56,6 +168,20 @@ pub struct Call {
     pub arguments: Vec<Expr>,
 }
 
+/// Anonymous function (lambda) expression
+///
+/// Evaluates to a function closing over the scope it was evaluated in.
+///
+/// # Examples
+/// - `fun (a, b) { return a + b; }`
+/// - `fun () { print "tick"; }`
+#[derive(Debug, Clone, PartialEq)]
+pub struct Function {
+    pub keyword: Token,
+    pub params: Vec<Token>,
+    pub body: Vec<Stmt>,
+}
+
 /// Property access expression
 ///
 /// # Examples
@@ -102,15 +228,36 @@ pub struct Logical {
     pub right: Box<Expr>,
 }
 
+/// Postfix increment/decrement expression
+///
+/// Updates the variable and evaluates to its value before the update.
+/// Prefix `++x` has no node of its own; it desugars to `x = x + 1`.
+///
+/// # Examples
+/// - `i++`
+/// - `count--`
+#[derive(Debug, Clone, PartialEq)]
+pub struct Postfix {
+    pub id: usize,
+    pub name: Token,
+    pub operator: Token,
+}
+
 /// Property assignment expression
 ///
+/// A compound assignment keeps its operator, so the object is evaluated
+/// once: `o.x += 1` reads `o.x`, adds `value` and stores the sum.
+///
 /// # Examples
 /// - `person.age = 25`
 /// - `car.color = "red"`
+/// - `counter.hits += 1`
 #[derive(Debug, Clone, PartialEq)]
 pub struct Set {
     pub object: Box<Expr>,
     pub name: Token,
+    /// `+`, `-`, `*` or `/` of a compound assignment, None for plain `=`
+    pub operator: Option<Token>,
     pub value: Box<Expr>,
 }
 
@@ -132,6 +279,7 @@ pub struct Super {
 /// - `this.age`
 #[derive(Debug, Clone, PartialEq)]
 pub struct This {
+    pub id: usize,
     pub keyword: Token,
 }
 
@@ -155,6 +303,7 @@ pub struct Unary {
 /// - `userName`
 #[derive(Debug, Clone, PartialEq)]
 pub struct Variable {
+    pub id: usize,
     pub name: Token,
 }
 
@@ -169,17 +318,26 @@ pub enum LiteralValue {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::parser::Parser;
+    use crate::scanner::Scanner;
     use crate::token::TokenType;
 
+    fn parse_at(source: &str, line: usize) -> Expr {
+        let tokens = Scanner::new_at(source.to_string(), line).scan_tokens();
+        Parser::new(tokens).parse().unwrap()
+    }
+
     #[test]
     fn test_assign_creation() {
         // num = 1
         let assign = Assign {
+            id: 0,
             name: Token {
                 token_type: TokenType::Identifier,
                 lexeme: "num".to_string(),
                 literal: None,
                 line: 1,
+                source_name: None,
             },
             value: Box::new(Expr::Literal(Literal {
                 value: LiteralValue::Number(1.0),
@@ -206,6 +364,7 @@ mod tests {
                 lexeme: "+".to_string(),
                 literal: None,
                 line: 1,
+                source_name: None,
             },
             right: Box::new(Expr::Literal(Literal {
                 value: LiteralValue::Number(4.0),
@@ -232,11 +391,13 @@ mod tests {
         // x + y
         let binary = Binary {
             left: Box::new(Expr::Variable(Variable {
+                id: 0,
                 name: Token {
                     token_type: TokenType::Identifier,
                     lexeme: "x".to_string(),
                     literal: None,
                     line: 1,
+                    source_name: None,
                 },
             })),
             operator: Token {
@@ -244,13 +405,16 @@ mod tests {
                 lexeme: "+".to_string(),
                 literal: None,
                 line: 1,
+                source_name: None,
             },
             right: Box::new(Expr::Variable(Variable {
+                id: 0,
                 name: Token {
                     token_type: TokenType::Identifier,
                     lexeme: "y".to_string(),
                     literal: None,
                     line: 1,
+                    source_name: None,
                 },
             })),
         };
@@ -274,6 +438,7 @@ mod tests {
                 lexeme: "-".to_string(),
                 literal: None,
                 line: 1,
+                source_name: None,
             },
             right: Box::new(Expr::Literal(Literal {
                 value: LiteralValue::Number(5.0),
@@ -302,6 +467,7 @@ mod tests {
                     lexeme: "+".to_string(),
                     literal: None,
                     line: 1,
+                    source_name: None,
                 },
                 right: Box::new(Expr::Literal(Literal {
                     value: LiteralValue::Number(2.0),
@@ -340,11 +506,13 @@ mod tests {
     fn test_variable() {
         // x
         let var = Variable {
+            id: 0,
             name: Token {
                 token_type: TokenType::Identifier,
                 lexeme: "count".to_string(),
                 literal: None,
                 line: 1,
+                source_name: None,
             },
         };
 
@@ -356,11 +524,13 @@ mod tests {
         // print("hello")
         let call = Call {
             callee: Box::new(Expr::Variable(Variable {
+                id: 0,
                 name: Token {
                     token_type: TokenType::Identifier,
                     lexeme: "print".to_string(),
                     literal: None,
                     line: 1,
+                    source_name: None,
                 },
             })),
             paren: Token {
@@ -368,6 +538,7 @@ mod tests {
                 lexeme: ")".to_string(),
                 literal: None,
                 line: 1,
+                source_name: None,
             },
             arguments: vec![Expr::Literal(Literal {
                 value: LiteralValue::String("hello".to_string()),
@@ -376,4 +547,36 @@ mod tests {
 
         assert_eq!(call.arguments.len(), 1);
     }
+
+    #[test]
+    fn test_structurally_eq_ignores_lines() {
+        // Given
+        let first = parse_at("1+2", 1);
+        let later = parse_at("1+2", 5);
+
+        // When
+        let same = first.structurally_eq(&later);
+
+        // Then
+        assert!(same);
+        assert_ne!(first, later);
+    }
+
+    #[test]
+    fn test_structurally_eq_ignores_node_ids() {
+        let first = parse_at("{ var a = 1; a = a + 1; fun () { return a; } }", 1);
+        let later = parse_at("{ var a = 1; a = a + 1; fun () { return a; } }", 3);
+
+        assert!(first.structurally_eq(&later));
+    }
+
+    #[test]
+    fn test_structurally_eq_compares_operators_and_values() {
+        let sum = parse_at("1+2", 1);
+
+        assert!(!sum.structurally_eq(&parse_at("1-2", 1)));
+        assert!(!sum.structurally_eq(&parse_at("1+3", 1)));
+        assert!(!sum.structurally_eq(&parse_at("(1+2)", 1)));
+        assert!(!parse_at("a", 1).structurally_eq(&parse_at("b", 1)));
+    }
 }
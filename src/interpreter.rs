@@ -1,35 +1,687 @@
-use crate::expr::{Binary, Expr, Grouping, Literal, Unary};
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::io::{self, BufRead, Write};
+use std::rc::Rc;
+use std::thread;
+
+use crate::clock::{Clock, SystemClock};
+use crate::environment::Environment;
+use crate::expr::{
+    self, Assign, Binary, Call, Expr, Function, Get, Grouping, Literal, Logical, Postfix, Set,
+    This, Unary, Variable,
+};
+use crate::lox_callable::LoxCallable;
+use crate::lox_class::LoxClass;
+use crate::lox_error::LoxError;
+use crate::lox_function::LoxFunction;
+use crate::lox_instance::LoxInstance;
 use crate::lox_value::LoxValue;
-use crate::runtime_error::RuntimeError;
-use crate::token::TokenType;
+use crate::natives;
+use crate::parser::Parser;
+use crate::resolver::Resolver;
+use crate::rng::Rng;
+use crate::runtime_error::{CallFrame, RuntimeError};
+use crate::scanner::Scanner;
+use crate::stmt::{self, Stmt};
+use crate::token::{Token, TokenType};
+
+/// Why execution of a statement stopped early
+///
+/// `return` unwinds through the enclosing blocks the same way an error
+/// does, until the function call that started them catches it. `break` and
+/// `continue` unwind to their loop, named by its label or the innermost one
+/// for `None`. `fallthrough` ends a case body and tells the switch to run
+/// the next one.
+#[derive(Debug)]
+pub(crate) enum Unwind {
+    Error(RuntimeError),
+    Return(LoxValue),
+    Break(Option<String>),
+    Continue(Option<String>),
+    Fallthrough,
+}
+
+impl From<RuntimeError> for Unwind {
+    fn from(error: RuntimeError) -> Self {
+        Unwind::Error(error)
+    }
+}
+
+/// Deepest nesting of Lox calls before "Stack overflow."
+///
+/// Each call recurses through the Rust stack, so Lox code needs a thread
+/// started by `with_lox_stack` to reach this depth.
+pub const MAX_CALL_DEPTH: usize = 10_000;
+
+/// Stack size of a thread running Lox code. A call takes up to tens of
+/// kilobytes in a debug build, so this holds `MAX_CALL_DEPTH` nested calls
+/// with room to spare; the memory is only committed as it is used.
+pub const LOX_STACK_SIZE: usize = 512 * 1024 * 1024;
+
+/// Run `f` on a new thread with a `LOX_STACK_SIZE` stack and wait for it
+///
+/// The default stacks, 8 MiB for the main thread and 2 MiB for others,
+/// overflow long before `MAX_CALL_DEPTH`. A panic in `f` is passed on.
+///
+/// # Example
+/// ```ignore
+/// with_lox_stack(|| Interpreter::new().run("fun f(n) { if (n > 0) f(n - 1); } f(5000);"))
+/// ```
+pub fn with_lox_stack<T: Send>(f: impl FnOnce() -> T + Send) -> T {
+    thread::scope(|scope| {
+        thread::Builder::new()
+            .stack_size(LOX_STACK_SIZE)
+            .spawn_scoped(scope, f)
+            .expect("failed to spawn the interpreter thread")
+            .join()
+            .unwrap_or_else(|panic| std::panic::resume_unwind(panic))
+    })
+}
+
+// Global scope holding only the natives
+fn natives_only() -> Rc<RefCell<Environment>> {
+    let mut globals = Environment::new();
+    natives::define_natives(&mut globals);
+    Rc::new(RefCell::new(globals))
+}
+
+// Token a step limit error in `stmt` points at, None for a block
+fn stmt_token(stmt: &Stmt) -> Option<&Token> {
+    match stmt {
+        Stmt::Block(_) => None,
+        Stmt::Break(break_stmt) => Some(&break_stmt.keyword),
+        Stmt::Class(class) => Some(&class.name),
+        Stmt::Continue(continue_stmt) => Some(&continue_stmt.keyword),
+        Stmt::Expression(expression) => expr_token(&expression.expression),
+        Stmt::Fallthrough(fallthrough) => Some(&fallthrough.keyword),
+        Stmt::Function(function) => Some(&function.name),
+        Stmt::If(if_stmt) => expr_token(&if_stmt.condition),
+        Stmt::Print(print) => print.expressions.first().and_then(expr_token),
+        Stmt::Return(return_stmt) => Some(&return_stmt.keyword),
+        Stmt::Switch(switch) => expr_token(&switch.discriminant),
+        Stmt::Var(var) => Some(&var.name),
+        Stmt::While(while_stmt) => Some(&while_stmt.keyword),
+    }
+}
+
+// Token a step limit error in `expr` points at, None for a literal or block
+fn expr_token(expr: &Expr) -> Option<&Token> {
+    match expr {
+        Expr::Assign(assign) => Some(&assign.name),
+        Expr::Binary(binary) => Some(&binary.operator),
+        Expr::Block(_) | Expr::Literal(_) => None,
+        Expr::Call(call) => Some(&call.paren),
+        Expr::Function(function) => Some(&function.keyword),
+        Expr::Get(get) => Some(&get.name),
+        Expr::Grouping(grouping) => expr_token(&grouping.expression),
+        Expr::Logical(logical) => Some(&logical.operator),
+        Expr::Postfix(postfix) => Some(&postfix.operator),
+        Expr::Set(set) => Some(&set.name),
+        Expr::Super(super_expr) => Some(&super_expr.keyword),
+        Expr::This(this) => Some(&this.keyword),
+        Expr::Unary(unary) => Some(&unary.operator),
+        Expr::Variable(variable) => Some(&variable.name),
+    }
+}
+
+// Name of a loop label, as carried by `Unwind::Break` and `Unwind::Continue`
+fn label(label: &Option<Token>) -> Option<String> {
+    label.as_ref().map(|label| label.lexeme.clone())
+}
+
+/// What `/` and `%` do when the right operand is zero
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DivByZero {
+    /// Raise a "Division by zero." runtime error
+    #[default]
+    Error,
+    /// Follow IEEE 754: `1/0` is `inf`, `-1/0` is `-inf`, `0/0` is `NaN`
+    Infinity,
+}
 
 /// Lox Interpreter
-/// 
-pub struct Interpreter;
+///
+/// Programs read from `input` and write to `output`, stdin and stdout
+/// unless replaced with `with_input` / `with_output`.
+/// In `repl` mode top-level expression statements also print their value.
+pub struct Interpreter {
+    globals: Rc<RefCell<Environment>>,
+    environment: Rc<RefCell<Environment>>,
+    // Scope depth of each resolved local variable use, by node id; uses
+    // missing here are globals
+    locals: HashMap<usize, usize>,
+    // None reads stdin, which the REPL shares, so it is not buffered here
+    input: Option<Box<dyn BufRead>>,
+    output: Box<dyn Write>,
+    div_by_zero: DivByZero,
+    // Lox function calls in progress, outermost first
+    call_stack: Vec<CallFrame>,
+    // Source of `random()`, reseeded by `seed(n)`
+    pub(crate) rng: Rng,
+    // Time source of `clock()` and `sleep(ms)`
+    pub(crate) clock: Box<dyn Clock>,
+    // Statements and expressions run so far, checked against `max_steps`
+    steps: u64,
+    max_steps: Option<u64>,
+    pub repl: bool,
+}
 
 impl Interpreter {
     pub fn new() -> Self {
-        Self
+        let globals = natives_only();
+        Self {
+            environment: Rc::clone(&globals),
+            globals,
+            locals: HashMap::new(),
+            input: None,
+            output: Box::new(io::stdout()),
+            div_by_zero: DivByZero::default(),
+            call_stack: Vec::new(),
+            rng: Rng::from_time(),
+            clock: Box::new(SystemClock),
+            steps: 0,
+            max_steps: None,
+            repl: false,
+        }
+    }
+
+    /// Read program input (`input()`) from the given reader
+    ///
+    /// # Example
+    /// ```ignore
+    /// let interpreter = Interpreter::new().with_input(Box::new(Cursor::new("42\n")));
+    /// ```
+    pub fn with_input(mut self, input: Box<dyn BufRead>) -> Self {
+        self.input = Some(input);
+        self
+    }
+
+    /// Write program output (`print`) to the given writer
+    ///
+    /// # Example
+    /// ```ignore
+    /// let interpreter = Interpreter::new().with_output(Box::new(Vec::new()));
+    /// ```
+    pub fn with_output(mut self, output: Box<dyn Write>) -> Self {
+        self.output = output;
+        self
+    }
+
+    /// Choose what division by zero does, an error unless changed
+    ///
+    /// # Example
+    /// ```ignore
+    /// let mut interpreter = Interpreter::new().with_div_by_zero(DivByZero::Infinity);
+    /// assert_eq!(interpreter.eval("1 / 0").unwrap(), LoxValue::Number(f64::INFINITY));
+    /// ```
+    pub fn with_div_by_zero(mut self, policy: DivByZero) -> Self {
+        self.div_by_zero = policy;
+        self
+    }
+
+    /// Read the time and sleep through `clock` instead of the system clock
+    ///
+    /// # Example
+    /// ```ignore
+    /// let interpreter = Interpreter::new().with_clock(Box::new(FakeClock::default()));
+    /// ```
+    pub fn with_clock(mut self, clock: Box<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// Stop with "Execution step limit exceeded." after `max_steps`
+    /// statements and expressions, so an untrusted script cannot loop
+    /// forever; `None`, the default, sets no limit
+    ///
+    /// The count covers the interpreter's whole life, not each `run`.
+    ///
+    /// # Example
+    /// ```ignore
+    /// let mut interpreter = Interpreter::new().with_max_steps(Some(10_000));
+    /// assert!(interpreter.run("while (true) {}").is_err());
+    /// ```
+    pub fn with_max_steps(mut self, max_steps: Option<u64>) -> Self {
+        self.max_steps = max_steps;
+        self
+    }
+
+    /// Define each `(name, value)` as a global before any script runs, so a
+    /// host can hand configuration to scripts
+    ///
+    /// The bindings sit beside the natives and replace any of the same
+    /// name. Like `define_global`, they do not survive `reset`.
+    ///
+    /// # Example
+    /// ```ignore
+    /// let mut interpreter = Interpreter::new()
+    ///     .with_environment([("version".to_string(), LoxValue::String("1.2".to_string()))]);
+    /// interpreter.run("print version;")?;
+    /// ```
+    pub fn with_environment(self, bindings: impl IntoIterator<Item = (String, LoxValue)>) -> Self {
+        for (name, value) in bindings {
+            self.globals.borrow_mut().define(name, value);
+        }
+        self
+    }
+
+    // Count one statement or expression against the step budget. Nodes
+    // without a token of their own cannot carry the error, so the budget is
+    // enforced at the next one that has a token, or by a loop on each pass.
+    fn step(&mut self, token: Option<&Token>) -> Result<(), RuntimeError> {
+        self.steps += 1;
+        match token {
+            Some(token) => self.check_steps(token),
+            None => Ok(()),
+        }
+    }
+
+    // Fail at `token` once more steps ran than `max_steps` allows
+    fn check_steps(&self, token: &Token) -> Result<(), RuntimeError> {
+        match self.max_steps {
+            Some(max_steps) if self.steps > max_steps => {
+                Err(RuntimeError::new(token, "Execution step limit exceeded."))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Forget everything previous programs did, keeping the configuration
+    ///
+    /// Globals go back to just the natives, so functions added with
+    /// `define_global` are dropped too. The step count restarts at zero;
+    /// input, output, clock and the other `with_*` settings stay.
+    ///
+    /// # Example
+    /// ```ignore
+    /// interpreter.run("var a = 1;")?;
+    /// interpreter.reset();
+    /// assert!(interpreter.eval("a").is_err());
+    /// ```
+    pub fn reset(&mut self) {
+        self.globals = natives_only();
+        self.environment = Rc::clone(&self.globals);
+        self.locals.clear();
+        self.call_stack.clear();
+        self.steps = 0;
+    }
+
+    /// Next line of input without its line ending, None at end of input
+    pub(crate) fn read_line(&mut self) -> io::Result<Option<String>> {
+        let mut line = String::new();
+        let read = match &mut self.input {
+            Some(input) => input.read_line(&mut line)?,
+            None => io::stdin().read_line(&mut line)?,
+        };
+        if read == 0 {
+            return Ok(None);
+        }
+
+        if line.ends_with('\n') {
+            line.pop();
+            if line.ends_with('\r') {
+                line.pop();
+            }
+        }
+        Ok(Some(line))
+    }
+
+    /// Write text to the output without a newline, e.g. an input prompt
+    pub(crate) fn write_prompt(&mut self, text: &str) {
+        let _ = write!(self.output, "{}", text);
+        let _ = self.output.flush();
+    }
+
+    /// Scope the interpreter is currently executing in
+    pub fn environment(&self) -> Rc<RefCell<Environment>> {
+        Rc::clone(&self.environment)
+    }
+
+    /// Global scope, holding the natives and top-level declarations
+    pub fn globals(&self) -> Rc<RefCell<Environment>> {
+        Rc::clone(&self.globals)
+    }
+
+    /// Expose a host function to Lox scripts as the global `name`
+    ///
+    /// It is called and arity-checked like any other callable, and replaces
+    /// an existing global of the same name.
+    ///
+    /// # Example
+    /// ```ignore
+    /// let mut interpreter = Interpreter::new();
+    /// interpreter.define_global("http_get", Rc::new(HttpGet::new()));
+    /// interpreter.run("print http_get(\"https://example.com\");")?;
+    /// ```
+    pub fn define_global(&mut self, name: &str, callable: Rc<dyn LoxCallable>) {
+        self.globals
+            .borrow_mut()
+            .define(name.to_string(), LoxValue::Callable(callable));
+    }
+
+    /// Scan, parse and evaluate a single expression
+    ///
+    /// Tokens left over after the expression are a parse error.
+    ///
+    /// # Example
+    /// ```ignore
+    /// let mut interpreter = Interpreter::new();
+    /// assert_eq!(interpreter.eval("2 + 2")?, LoxValue::Number(4.0));
+    /// ```
+    pub fn eval(&mut self, source: &str) -> Result<LoxValue, LoxError> {
+        let mut scanner = Scanner::new(source.to_string());
+        let tokens = scanner.scan_tokens();
+        if let Some(error) = scanner.errors().first() {
+            return Err(error.clone().into());
+        }
+
+        let expr = Parser::new(tokens).parse_expression()?;
+        self.locals
+            .extend(Resolver::new().resolve_expression(&expr));
+        Ok(self.evaluate(&expr)?)
+    }
+
+    /// Scan, parse, resolve and execute a whole program
+    ///
+    /// Only the first scan, parse or resolve error is returned.
+    ///
+    /// # Example
+    /// ```ignore
+    /// let mut interpreter = Interpreter::new();
+    /// interpreter.run("var a = 1; print a + 1;")?;
+    /// ```
+    pub fn run(&mut self, source: &str) -> Result<(), LoxError> {
+        let mut scanner = Scanner::new(source.to_string());
+        let tokens = scanner.scan_tokens();
+        if let Some(error) = scanner.errors().first() {
+            return Err(error.clone().into());
+        }
+
+        let statements = Parser::new(tokens)
+            .parse_program()
+            .map_err(|errors| errors.into_iter().next().unwrap())?;
+        let mut resolver = Resolver::new();
+        resolver.resolve(&statements);
+        if let Some(error) = resolver.errors().first() {
+            return Err(error.clone().into());
+        }
+        Ok(self.interpret(&statements)?)
+    }
+
+    /// Execute statements in order, stopping at the first runtime error
+    ///
+    /// The statements are resolved first, so each variable use reads the
+    /// binding in scope where it was written. A resolve error, such as a
+    /// `break` to an undefined label, is returned before anything runs.
+    pub fn interpret(&mut self, statements: &[Stmt]) -> Result<(), RuntimeError> {
+        let mut resolver = Resolver::new();
+        let locals = resolver.resolve(statements);
+        if let Some(error) = resolver.errors().first() {
+            return Err(RuntimeError::new(&error.token, &error.message));
+        }
+        self.locals.extend(locals);
+        for statement in statements {
+            let result = match statement {
+                Stmt::Expression(expression) if self.repl => self.echo(&expression.expression),
+                _ => self.execute(statement),
+            };
+            match result {
+                Ok(()) => {}
+                // A top-level `return` ends the program
+                Err(Unwind::Return(_)) => return Ok(()),
+                Err(Unwind::Error(error)) => return Err(error),
+                Err(Unwind::Break(_) | Unwind::Continue(_)) => {
+                    unreachable!(
+                        "the parser and resolver reject `break` and `continue` outside a loop"
+                    )
+                }
+                Err(Unwind::Fallthrough) => {
+                    unreachable!("the parser accepts `fallthrough` only at the end of a case")
+                }
+            }
+        }
+        Ok(())
+    }
+
+    // REPL: evaluate a top-level expression statement and show its value
+    // Strings are echoed quoted, unlike `print`
+    fn echo(&mut self, expr: &Expr) -> Result<(), Unwind> {
+        let value = self.evaluate(expr)?;
+        let _ = writeln!(self.output, "{}", value.to_display_repl());
+        Ok(())
+    }
+
+    // Values separated by spaces, then a newline
+    pub(crate) fn print(&mut self, values: &[LoxValue]) {
+        // Like println!, but a closed output only loses the text
+        for (i, value) in values.iter().enumerate() {
+            let separator = if i == 0 { "" } else { " " };
+            let _ = write!(self.output, "{}{}", separator, value);
+        }
+        let _ = writeln!(self.output);
+    }
+
+    fn execute(&mut self, stmt: &Stmt) -> Result<(), Unwind> {
+        self.step(stmt_token(stmt))?;
+        match stmt {
+            Stmt::Block(block) => {
+                let environment = Environment::with_enclosing(Rc::clone(&self.environment));
+                self.execute_block(&block.statements, environment)
+            }
+            Stmt::Break(break_stmt) => Err(Unwind::Break(label(&break_stmt.label))),
+            Stmt::Class(class) => {
+                self.visit_class(class);
+                Ok(())
+            }
+            Stmt::Continue(continue_stmt) => Err(Unwind::Continue(label(&continue_stmt.label))),
+            Stmt::Expression(expression) => {
+                self.evaluate(&expression.expression)?;
+                Ok(())
+            }
+            Stmt::Fallthrough(_) => Err(Unwind::Fallthrough),
+            Stmt::Function(function) => {
+                self.visit_function_stmt(function);
+                Ok(())
+            }
+            Stmt::If(if_stmt) => self.visit_if(if_stmt),
+            Stmt::Print(print) => {
+                let mut values = Vec::with_capacity(print.expressions.len());
+                for expression in &print.expressions {
+                    values.push(self.evaluate(expression)?);
+                }
+                self.print(&values);
+                Ok(())
+            }
+            Stmt::Return(return_stmt) => {
+                let value = match &return_stmt.value {
+                    Some(value) => self.evaluate(value)?,
+                    None => LoxValue::Nil,
+                };
+                Err(Unwind::Return(value))
+            }
+            Stmt::Switch(switch) => self.visit_switch(switch),
+            Stmt::Var(var) => {
+                let value = match &var.initializer {
+                    Some(initializer) => self.evaluate(initializer)?,
+                    None => LoxValue::Nil,
+                };
+                self.environment
+                    .borrow_mut()
+                    .define(var.name.lexeme.clone(), value);
+                Ok(())
+            }
+            Stmt::While(while_stmt) => self.visit_while(while_stmt),
+        }
+    }
+
+    /// Execute statements in the given scope, restoring the current one afterwards
+    ///
+    /// The previous scope is restored even when a statement errors or returns.
+    pub(crate) fn execute_block(
+        &mut self,
+        statements: &[Stmt],
+        environment: Environment,
+    ) -> Result<(), Unwind> {
+        let previous = std::mem::replace(&mut self.environment, Rc::new(RefCell::new(environment)));
+        let result = statements
+            .iter()
+            .try_for_each(|statement| self.execute(statement));
+        self.environment = previous;
+        result
+    }
+
+    /// Run a function body with `frame` on the call stack, or fail with
+    /// "Stack overflow." at `paren` when the stack is already full
+    ///
+    /// An error leaving the innermost frame records the whole stack as its
+    /// call trace; outer frames leave that trace alone.
+    pub(crate) fn in_frame<T>(
+        &mut self,
+        frame: CallFrame,
+        paren: &Token,
+        body: impl FnOnce(&mut Self) -> Result<T, RuntimeError>,
+    ) -> Result<T, RuntimeError> {
+        if self.call_stack.len() >= MAX_CALL_DEPTH {
+            return Err(RuntimeError::new(paren, "Stack overflow."));
+        }
+        self.call_stack.push(frame);
+        let result = body(self).map_err(|mut error| {
+            if error.call_trace.is_empty() {
+                error.call_trace = self.call_stack.iter().rev().cloned().collect();
+            }
+            error
+        });
+        self.call_stack.pop();
+        result
+    }
+
+    fn visit_function_stmt(&mut self, function: &stmt::Function) {
+        let value = LoxFunction {
+            name: Some(function.name.lexeme.clone()),
+            params: function.params.clone(),
+            body: Rc::new(function.body.clone()),
+            closure: Rc::clone(&self.environment),
+            is_initializer: false,
+        };
+        self.environment.borrow_mut().define(
+            function.name.lexeme.clone(),
+            LoxValue::Callable(Rc::new(value)),
+        );
+    }
+
+    fn visit_class(&mut self, class: &stmt::Class) {
+        let methods = self.methods(&class.methods);
+        let getters = self.methods(&class.getters);
+        let class_methods = self.methods(&class.class_methods);
+
+        let value = LoxClass::new(class.name.lexeme.clone(), methods, getters, class_methods);
+        self.environment
+            .borrow_mut()
+            .define(class.name.lexeme.clone(), LoxValue::Class(value));
+    }
+
+    // Methods by name, closing over the current scope
+    fn methods(&self, methods: &[stmt::Function]) -> HashMap<String, Rc<LoxFunction>> {
+        methods
+            .iter()
+            .map(|method| {
+                let function = LoxFunction {
+                    name: Some(method.name.lexeme.clone()),
+                    params: method.params.clone(),
+                    body: Rc::new(method.body.clone()),
+                    closure: Rc::clone(&self.environment),
+                    is_initializer: method.name.lexeme == "init",
+                };
+                (method.name.lexeme.clone(), Rc::new(function))
+            })
+            .collect()
+    }
+
+    fn visit_if(&mut self, if_stmt: &stmt::If) -> Result<(), Unwind> {
+        if self.evaluate(&if_stmt.condition)?.is_truthy() {
+            self.execute(&if_stmt.then_branch)
+        } else if let Some(else_branch) = &if_stmt.else_branch {
+            self.execute(else_branch)
+        } else {
+            Ok(())
+        }
+    }
+
+    // Case values are evaluated in order, stopping at the first match.
+    // A body ending in `fallthrough` goes on to the next case's body.
+    fn visit_switch(&mut self, switch: &stmt::Switch) -> Result<(), Unwind> {
+        let discriminant = self.evaluate(&switch.discriminant)?;
+
+        let mut matched = None;
+        for (i, (value, _)) in switch.cases.iter().enumerate() {
+            let value = self.evaluate(value)?;
+            if self.is_equal(&discriminant, &value) {
+                matched = Some(i);
+                break;
+            }
+        }
+
+        let Some(first) = matched else {
+            return match &switch.default {
+                Some(statements) => self.execute_case(statements),
+                None => Ok(()),
+            };
+        };
+        for (_, statements) in &switch.cases[first..] {
+            match self.execute_case(statements) {
+                Err(Unwind::Fallthrough) => {}
+                result => return result,
+            }
+        }
+        Ok(())
+    }
+
+    // Each case body runs in a scope of its own
+    fn execute_case(&mut self, statements: &[Stmt]) -> Result<(), Unwind> {
+        let environment = Environment::with_enclosing(Rc::clone(&self.environment));
+        self.execute_block(statements, environment)
+    }
+
+    fn visit_while(&mut self, while_stmt: &stmt::While) -> Result<(), Unwind> {
+        let this_loop = label(&while_stmt.label);
+        let targets_this_loop = |target: &Option<String>| target.is_none() || *target == this_loop;
+
+        while self.evaluate(&while_stmt.condition)?.is_truthy() {
+            self.check_steps(&while_stmt.keyword)?;
+            match self.execute(&while_stmt.body) {
+                Ok(()) => {}
+                Err(Unwind::Break(target)) if targets_this_loop(&target) => break,
+                Err(Unwind::Continue(target)) if targets_this_loop(&target) => {}
+                Err(unwind) => return Err(unwind),
+            }
+            if let Some(increment) = &while_stmt.increment {
+                self.evaluate(increment)?;
+            }
+        }
+        Ok(())
     }
 
     pub fn evaluate(&mut self, expr: &Expr) -> Result<LoxValue, RuntimeError> {
+        self.step(expr_token(expr))?;
         match expr {
             Expr::Literal(lit) => Ok(self.visit_literal(lit)),
             Expr::Grouping(grp) => self.visit_grouping(grp),
             Expr::Unary(un) => self.visit_unary(un),
             Expr::Binary(bin) => self.visit_binary(bin),
-            _ => {
-                let dummy_token = crate::token::Token {
-                    token_type: crate::token::TokenType::Eof,
-                    lexeme: String::new(),
-                    literal: None,
-                    line: 0,
-                };
-                Err(RuntimeError::new(
-                    &dummy_token,
-                    "This expression type is not yet implemented",
-                ))
-            }
+            Expr::Variable(var) => self.visit_variable(var),
+            Expr::Assign(assign) => self.visit_assign(assign),
+            Expr::Postfix(postfix) => self.visit_postfix(postfix),
+            Expr::Call(call) => self.visit_call(call),
+            Expr::Logical(logical) => self.visit_logical(logical),
+            Expr::Function(function) => Ok(self.visit_function(function)),
+            Expr::Block(block) => self.visit_block_expr(block),
+            Expr::Get(get) => self.visit_get(get),
+            Expr::Set(set) => self.visit_set(set),
+            Expr::This(this) => self.visit_this(this),
+            _ => Err(RuntimeError::new(
+                &Token::eof(0),
+                "This expression type is not yet implemented",
+            )),
         }
     }
 
@@ -41,6 +693,190 @@ impl Interpreter {
         self.evaluate(&grouping.expression)
     }
 
+    fn visit_variable(&mut self, variable: &Variable) -> Result<LoxValue, RuntimeError> {
+        self.look_up(variable.id, &variable.name)
+    }
+
+    fn visit_assign(&mut self, assign: &Assign) -> Result<LoxValue, RuntimeError> {
+        let value = self.evaluate(&assign.value)?;
+        self.assign(assign.id, &assign.name, value.clone())?;
+        Ok(value)
+    }
+
+    // Value of the variable use `id`, in the scope the resolver found for it
+    fn look_up(&self, id: usize, name: &Token) -> Result<LoxValue, RuntimeError> {
+        match self.locals.get(&id) {
+            Some(&depth) => self.environment.borrow().get_at(depth, name),
+            None => self.globals.borrow().get(name),
+        }
+    }
+
+    fn assign(&mut self, id: usize, name: &Token, value: LoxValue) -> Result<(), RuntimeError> {
+        match self.locals.get(&id) {
+            Some(&depth) => self.environment.borrow_mut().assign_at(depth, name, value),
+            None => self.globals.borrow_mut().assign(name, value),
+        }
+    }
+
+    // Evaluates to the value held before the update
+    fn visit_postfix(&mut self, postfix: &Postfix) -> Result<LoxValue, RuntimeError> {
+        let old = self.look_up(postfix.id, &postfix.name)?;
+        let Some(n) = old.as_number() else {
+            return Err(RuntimeError::new(
+                &postfix.operator,
+                "Operand must be a number.",
+            ));
+        };
+
+        let new = match postfix.operator.token_type {
+            TokenType::PlusPlus => n + 1.0,
+            _ => n - 1.0,
+        };
+        self.assign(postfix.id, &postfix.name, LoxValue::Number(new))?;
+        Ok(old)
+    }
+
+    // Short-circuits: the right operand is evaluated only when needed.
+    // `??` falls back to the right operand only when the left is nil.
+    fn visit_logical(&mut self, logical: &Logical) -> Result<LoxValue, RuntimeError> {
+        let left = self.evaluate(&logical.left)?;
+
+        let short_circuit = match logical.operator.token_type {
+            TokenType::Or => left.is_truthy(),
+            TokenType::And => left.is_falsey(),
+            _ => !left.as_nil(),
+        };
+
+        if short_circuit {
+            Ok(left)
+        } else {
+            self.evaluate(&logical.right)
+        }
+    }
+
+    // A lambda captures the scope it is evaluated in
+    fn visit_function(&mut self, function: &Function) -> LoxValue {
+        LoxValue::Callable(Rc::new(LoxFunction {
+            name: None,
+            params: function.params.clone(),
+            body: Rc::new(function.body.clone()),
+            closure: Rc::clone(&self.environment),
+            is_initializer: false,
+        }))
+    }
+
+    // Runs in a new scope, like a block statement
+    fn visit_block_expr(&mut self, block: &expr::Block) -> Result<LoxValue, RuntimeError> {
+        let environment = Environment::with_enclosing(Rc::clone(&self.environment));
+        let previous = std::mem::replace(&mut self.environment, Rc::new(RefCell::new(environment)));
+        let result = self.block_value(block);
+        self.environment = previous;
+        result
+    }
+
+    fn block_value(&mut self, block: &expr::Block) -> Result<LoxValue, RuntimeError> {
+        for statement in &block.statements {
+            match self.execute(statement) {
+                Ok(()) => {}
+                Err(Unwind::Error(error)) => return Err(error),
+                Err(Unwind::Return(_)) => {
+                    unreachable!("the parser rejects `return` inside a block expression")
+                }
+                Err(Unwind::Break(_) | Unwind::Continue(_)) => {
+                    unreachable!(
+                        "the parser and resolver reject `break` and `continue` leaving a block \
+                         expression"
+                    )
+                }
+                Err(Unwind::Fallthrough) => {
+                    unreachable!("the parser accepts `fallthrough` only at the end of a case")
+                }
+            }
+        }
+        match &block.value {
+            Some(value) => self.evaluate(value),
+            None => Ok(LoxValue::Nil),
+        }
+    }
+
+    fn visit_get(&mut self, get: &Get) -> Result<LoxValue, RuntimeError> {
+        let object = self.evaluate(&get.object)?;
+        self.property(object, &get.name)
+    }
+
+    // Property `name` of an evaluated object, running a getter if it has one
+    fn property(&mut self, object: LoxValue, name: &Token) -> Result<LoxValue, RuntimeError> {
+        match object {
+            LoxValue::Instance(instance) => match LoxInstance::getter(&instance, name) {
+                Some(getter) => getter.call(self, name, Vec::new()),
+                None => LoxInstance::get(&instance, name),
+            },
+            LoxValue::Class(class) => class.get(name),
+            _ => Err(RuntimeError::new(name, "Only instances have properties.")),
+        }
+    }
+
+    // A compound assignment reads the property before evaluating `value`,
+    // as `o.x = o.x + value` would, but evaluates the object only once
+    fn visit_set(&mut self, set: &Set) -> Result<LoxValue, RuntimeError> {
+        let LoxValue::Instance(instance) = self.evaluate(&set.object)? else {
+            return Err(RuntimeError::new(&set.name, "Only instances have fields."));
+        };
+        let value = match &set.operator {
+            Some(operator) => {
+                let current = self.property(LoxValue::Instance(Rc::clone(&instance)), &set.name)?;
+                let right = self.evaluate(&set.value)?;
+                self.binary_operation(operator, current, right)?
+            }
+            None => self.evaluate(&set.value)?,
+        };
+        instance.borrow_mut().set(&set.name, value.clone());
+        Ok(value)
+    }
+
+    fn visit_this(&mut self, this: &This) -> Result<LoxValue, RuntimeError> {
+        self.look_up(this.id, &this.keyword)
+    }
+
+    fn visit_call(&mut self, call: &Call) -> Result<LoxValue, RuntimeError> {
+        let callee = self.evaluate(&call.callee)?;
+
+        let mut arguments = Vec::with_capacity(call.arguments.len());
+        for argument in &call.arguments {
+            arguments.push(self.evaluate(argument)?);
+        }
+
+        let function: Rc<dyn LoxCallable> = match callee {
+            LoxValue::Callable(function) => function,
+            LoxValue::Class(class) => class,
+            _ => {
+                return Err(RuntimeError::new(
+                    &call.paren,
+                    "Can only call functions and classes.",
+                ));
+            }
+        };
+
+        let (min, max) = (function.arity(), function.max_arity());
+        if arguments.len() < min || max.is_some_and(|max| arguments.len() > max) {
+            let expected = match max {
+                Some(max) if max == min => min.to_string(),
+                Some(max) => format!("{} to {}", min, max),
+                None => format!("at least {}", min),
+            };
+            return Err(RuntimeError::new(
+                &call.paren,
+                &format!(
+                    "Expected {} arguments but got {}.",
+                    expected,
+                    arguments.len()
+                ),
+            ));
+        }
+
+        function.call(self, &call.paren, arguments)
+    }
+
     fn visit_unary(&mut self, unary: &Unary) -> Result<LoxValue, RuntimeError> {
         let right = self.evaluate(&unary.right)?;
 
@@ -55,7 +891,7 @@ impl Interpreter {
                 }
             }
             TokenType::Bang => {
-                Ok(LoxValue::Bool(!right.is_truthy()))
+                Ok(LoxValue::Bool(right.is_falsey()))
             }
             _ => Err(RuntimeError::new(
                 &unary.operator,
@@ -67,64 +903,54 @@ impl Interpreter {
     fn visit_binary(&mut self, binary: &Binary) -> Result<LoxValue, RuntimeError> {
         let left = self.evaluate(&binary.left)?;
         let right = self.evaluate(&binary.right)?;
+        self.binary_operation(&binary.operator, left, right)
+    }
 
-        match binary.operator.token_type {
-            TokenType::Minus => {
-                match (left.as_number(), right.as_number()) {
-                    (Some(l), Some(r)) => Ok(LoxValue::Number(l - r)),
-                    _ => Err(RuntimeError::new(
-                        &binary.operator,
-                        "Operands must be numbers.",
-                    )),
-                }
-            }
-            TokenType::Star => {
-                match (left.as_number(), right.as_number()) {
-                    (Some(l), Some(r)) => Ok(LoxValue::Number(l * r)),
-                    _ => Err(RuntimeError::new(
-                        &binary.operator,
-                        "Operands must be numbers.",
-                    )),
+    // Apply a binary operator to evaluated operands
+    fn binary_operation(
+        &self,
+        operator: &Token,
+        left: LoxValue,
+        right: LoxValue,
+    ) -> Result<LoxValue, RuntimeError> {
+        // Arithmetic errors come without a token; point them at the operator
+        let at_operator = |error: RuntimeError| RuntimeError::new(operator, &error.message);
+
+        match operator.token_type {
+            TokenType::Minus => (&left - &right).map_err(at_operator),
+            TokenType::Star => (&left * &right).map_err(at_operator),
+            // `Div` rejects a zero divisor; only the policy can let it through
+            TokenType::Slash => match (left.as_number(), right.as_number()) {
+                (Some(l), Some(r)) if r == 0.0 && self.div_by_zero == DivByZero::Infinity => {
+                    Ok(LoxValue::Number(l / r))
                 }
-            }
-            TokenType::Slash => {
+                _ => (&left / &right).map_err(at_operator),
+            },
+            TokenType::Percent => {
                 match (left.as_number(), right.as_number()) {
                     (Some(l), Some(r)) => {
-                        if r == 0.0 {
+                        if r == 0.0 && self.div_by_zero == DivByZero::Error {
                             Err(RuntimeError::new(
-                                &binary.operator,
+                                operator,
                                 "Division by zero.",
                             ))
                         } else {
-                            Ok(LoxValue::Number(l / r))
+                            Ok(LoxValue::Number(l % r))
                         }
                     }
                     _ => Err(RuntimeError::new(
-                        &binary.operator,
+                        operator,
                         "Operands must be numbers.",
                     )),
                 }
             }
-            TokenType::Plus => {
-                match (left.as_number(), right.as_number()) {
-                    (Some(l), Some(r)) => Ok(LoxValue::Number(l + r)),
-                    _ => match (left.as_string(), right.as_string()) {
-                        (Some(l), Some(r)) => {
-                            Ok(LoxValue::String(format!("{}{}", l, r)))
-                        }
-                        _ => Err(RuntimeError::new(
-                            &binary.operator,
-                            "Operands must be two numbers or two strings.",
-                        )),
-                    },
-                }
-            }
+            TokenType::Plus => (&left + &right).map_err(at_operator),
 
             TokenType::Greater => {
                 match (left.as_number(), right.as_number()) {
                     (Some(l), Some(r)) => Ok(LoxValue::Bool(l > r)),
                     _ => Err(RuntimeError::new(
-                        &binary.operator,
+                        operator,
                         "Operands must be numbers.",
                     )),
                 }
@@ -133,7 +959,7 @@ impl Interpreter {
                 match (left.as_number(), right.as_number()) {
                     (Some(l), Some(r)) => Ok(LoxValue::Bool(l >= r)),
                     _ => Err(RuntimeError::new(
-                        &binary.operator,
+                        operator,
                         "Operands must be numbers.",
                     )),
                 }
@@ -142,7 +968,7 @@ impl Interpreter {
                 match (left.as_number(), right.as_number()) {
                     (Some(l), Some(r)) => Ok(LoxValue::Bool(l < r)),
                     _ => Err(RuntimeError::new(
-                        &binary.operator,
+                        operator,
                         "Operands must be numbers.",
                     )),
                 }
@@ -151,7 +977,7 @@ impl Interpreter {
                 match (left.as_number(), right.as_number()) {
                     (Some(l), Some(r)) => Ok(LoxValue::Bool(l <= r)),
                     _ => Err(RuntimeError::new(
-                        &binary.operator,
+                        operator,
                         "Operands must be numbers.",
                     )),
                 }
@@ -165,21 +991,29 @@ impl Interpreter {
             }
 
             _ => Err(RuntimeError::new(
-                &binary.operator,
+                operator,
                 "Invalid binary operator.",
             )),
         }
     }
 
-    fn is_equal(&self, left: &LoxValue, right: &LoxValue) -> bool {
+    pub(crate) fn is_equal(&self, left: &LoxValue, right: &LoxValue) -> bool {
         left == right
     }
 }
 
+impl Default for Interpreter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::expr::LiteralValue;
+    use crate::lox_callable::NativeFunction;
+    use crate::test_support::SharedBuffer;
 
     #[test]
     fn test_evaluate_literal_number() {
@@ -199,6 +1033,7 @@ mod tests {
             lexeme: "-".to_string(),
             literal: None,
             line: 1,
+            source_name: None,
         };
         let expr = Expr::Unary(Unary {
             operator: token,
@@ -218,6 +1053,7 @@ mod tests {
             lexeme: "+".to_string(),
             literal: None,
             line: 1,
+            source_name: None,
         };
         let expr = Expr::Binary(Binary {
             left: Box::new(Expr::Literal(Literal {
@@ -231,4 +1067,1082 @@ mod tests {
         let result = interpreter.evaluate(&expr).unwrap();
         assert_eq!(result, LoxValue::Number(3.0));
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_default_interpreter_has_natives() {
+        let mut interpreter: Interpreter = Default::default();
+
+        assert_eq!(interpreter.eval("assert(true)").unwrap(), LoxValue::Nil);
+        assert!(!interpreter.repl);
+    }
+
+    #[test]
+    fn test_eval_expression_source() {
+        let mut interpreter = Interpreter::new();
+        let result = interpreter.eval("2+2").unwrap();
+        assert_eq!(result, LoxValue::Number(4.0));
+    }
+
+    #[test]
+    fn test_eval_rejects_trailing_tokens() {
+        let mut interpreter = Interpreter::new();
+        match interpreter.eval("1 1") {
+            Err(LoxError::Parse(error)) => {
+                assert_eq!(error.message, "Expect end of expression.");
+                assert_eq!(error.token.lexeme, "1");
+            }
+            other => panic!("Expected parse error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_prefix_increment_returns_new_value() {
+        let mut interpreter = Interpreter::new();
+        interpreter
+            .environment
+            .borrow_mut()
+            .define("x".to_string(), LoxValue::Number(1.0));
+
+        assert_eq!(interpreter.eval("++x").unwrap(), LoxValue::Number(2.0));
+        assert_eq!(interpreter.eval("x").unwrap(), LoxValue::Number(2.0));
+    }
+
+    #[test]
+    fn test_postfix_increment_returns_old_value() {
+        let mut interpreter = Interpreter::new();
+        interpreter
+            .environment
+            .borrow_mut()
+            .define("x".to_string(), LoxValue::Number(1.0));
+
+        assert_eq!(interpreter.eval("x++").unwrap(), LoxValue::Number(1.0));
+        assert_eq!(interpreter.eval("x").unwrap(), LoxValue::Number(2.0));
+    }
+
+    #[test]
+    fn test_prefix_and_postfix_decrement() {
+        let mut interpreter = Interpreter::new();
+        interpreter
+            .environment
+            .borrow_mut()
+            .define("x".to_string(), LoxValue::Number(5.0));
+
+        assert_eq!(interpreter.eval("--x").unwrap(), LoxValue::Number(4.0));
+        assert_eq!(interpreter.eval("x--").unwrap(), LoxValue::Number(4.0));
+        assert_eq!(interpreter.eval("x").unwrap(), LoxValue::Number(3.0));
+    }
+
+    #[test]
+    fn test_increment_non_number_errors() {
+        let mut interpreter = Interpreter::new();
+        interpreter
+            .environment
+            .borrow_mut()
+            .define("s".to_string(), LoxValue::String("a".to_string()));
+
+        assert!(matches!(interpreter.eval("s++"), Err(LoxError::Runtime(_))));
+    }
+
+    #[test]
+    fn test_eval_modulo() {
+        let mut interpreter = Interpreter::new();
+        assert_eq!(interpreter.eval("7 % 3").unwrap(), LoxValue::Number(1.0));
+        assert_eq!(interpreter.eval("-7 % 3").unwrap(), LoxValue::Number(-1.0));
+        assert!(matches!(
+            interpreter.eval("1 % 0"),
+            Err(LoxError::Runtime(_))
+        ));
+    }
+
+    #[test]
+    fn test_logical_operators_return_operand() {
+        let mut interpreter = Interpreter::new();
+        assert_eq!(interpreter.eval("nil or 2").unwrap(), LoxValue::Number(2.0));
+        assert_eq!(interpreter.eval("1 or 2").unwrap(), LoxValue::Number(1.0));
+        assert_eq!(interpreter.eval("nil and 2").unwrap(), LoxValue::Nil);
+        assert_eq!(interpreter.eval("1 and 2").unwrap(), LoxValue::Number(2.0));
+    }
+
+    #[test]
+    fn test_coalesce_nil_falls_back() {
+        let mut interpreter = Interpreter::new();
+        assert_eq!(interpreter.eval("nil ?? 5").unwrap(), LoxValue::Number(5.0));
+    }
+
+    #[test]
+    fn test_coalesce_false_is_kept() {
+        let mut interpreter = Interpreter::new();
+        assert_eq!(
+            interpreter.eval("false ?? 5").unwrap(),
+            LoxValue::Bool(false)
+        );
+    }
+
+    #[test]
+    fn test_coalesce_short_circuits() {
+        let mut interpreter = Interpreter::new();
+        // `missing` is undefined, so evaluating it would be a runtime error
+        assert_eq!(
+            interpreter.eval("1 ?? missing").unwrap(),
+            LoxValue::Number(1.0)
+        );
+        assert!(interpreter.eval("nil ?? missing").is_err());
+    }
+
+    #[test]
+    fn test_subtracting_non_numbers_errors() {
+        match Interpreter::new().eval("\"a\" - \"b\"") {
+            Err(LoxError::Runtime(error)) => assert_eq!(error.message, "Operands must be numbers."),
+            other => panic!("Expected runtime error, got {:?}", other),
+        }
+    }
+
+    // ===== Division by zero =====
+
+    #[test]
+    fn test_div_by_zero_errors_by_default() {
+        let mut interpreter = Interpreter::new();
+        for (source, operator) in [
+            ("1 / 0", "/"),
+            ("-1 / 0", "/"),
+            ("0 / 0", "/"),
+            ("1 % 0", "%"),
+        ] {
+            match interpreter.eval(source) {
+                Err(LoxError::Runtime(error)) => {
+                    assert_eq!(error.message, "Division by zero.");
+                    assert_eq!(error.token.lexeme, operator);
+                }
+                other => panic!("Expected runtime error for {}, got {:?}", source, other),
+            }
+        }
+    }
+
+    #[test]
+    fn test_div_by_zero_infinity_policy() {
+        // Given
+        let mut interpreter = Interpreter::new().with_div_by_zero(DivByZero::Infinity);
+
+        // When / Then
+        assert_eq!(
+            interpreter.eval("1 / 0").unwrap(),
+            LoxValue::Number(f64::INFINITY)
+        );
+        assert_eq!(
+            interpreter.eval("-1 / 0").unwrap(),
+            LoxValue::Number(f64::NEG_INFINITY)
+        );
+        assert!(matches!(interpreter.eval("0 / 0").unwrap(), LoxValue::Number(n) if n.is_nan()));
+        assert!(matches!(interpreter.eval("1 % 0").unwrap(), LoxValue::Number(n) if n.is_nan()));
+    }
+
+    // ===== Statements and functions =====
+
+    #[test]
+    fn test_run_var_and_block_scope() {
+        let mut interpreter = Interpreter::new();
+        interpreter
+            .run("var a = 1; { var a = 2; a = a + 1; } var b = a;")
+            .unwrap();
+
+        assert_eq!(interpreter.eval("b").unwrap(), LoxValue::Number(1.0));
+    }
+
+    #[test]
+    fn test_run_control_flow() {
+        let mut interpreter = Interpreter::new();
+        interpreter
+            .run("var sum = 0; for (var i = 1; i <= 4; i++) { if (i % 2 == 0) sum += i; }")
+            .unwrap();
+
+        assert_eq!(interpreter.eval("sum").unwrap(), LoxValue::Number(6.0));
+    }
+
+    #[test]
+    fn test_function_declaration_returns_value() {
+        let mut interpreter = Interpreter::new();
+        interpreter
+            .run("fun add(a, b) { return a + b; } var result = add(1, 2);")
+            .unwrap();
+
+        assert_eq!(interpreter.eval("result").unwrap(), LoxValue::Number(3.0));
+    }
+
+    #[test]
+    fn test_lambda_assigned_to_variable() {
+        // Given
+        let mut interpreter = Interpreter::new();
+
+        // When
+        interpreter
+            .run("var square = fun (x) { return x * x; }; var result = square(4);")
+            .unwrap();
+
+        // Then
+        assert_eq!(interpreter.eval("result").unwrap(), LoxValue::Number(16.0));
+        assert_eq!(interpreter.eval("square").unwrap().to_string(), "<fn>");
+    }
+
+    #[test]
+    fn test_lambda_passed_as_argument() {
+        let mut interpreter = Interpreter::new();
+        interpreter
+            .run(
+                "fun twice(f, x) { return f(f(x)); }
+                 var result = twice(fun (n) { return n + 3; }, 1);",
+            )
+            .unwrap();
+
+        assert_eq!(interpreter.eval("result").unwrap(), LoxValue::Number(7.0));
+    }
+
+    #[test]
+    fn test_lambda_closes_over_scope() {
+        let mut interpreter = Interpreter::new();
+        interpreter
+            .run(
+                "fun counter() { var n = 0; return fun () { n++; return n; }; }
+                 var next = counter();
+                 next();
+                 var result = next();",
+            )
+            .unwrap();
+
+        assert_eq!(interpreter.eval("result").unwrap(), LoxValue::Number(2.0));
+    }
+
+    #[test]
+    fn test_lambda_expression_statement() {
+        let mut interpreter = Interpreter::new();
+        interpreter
+            .run("var called = false; fun () { called = true; }();")
+            .unwrap();
+
+        assert_eq!(interpreter.eval("called").unwrap(), LoxValue::Bool(true));
+    }
+
+    #[test]
+    fn test_error_in_block_restores_scope() {
+        let mut interpreter = Interpreter::new();
+        interpreter.run("var a = \"outer\";").unwrap();
+
+        assert!(interpreter.run("{ var a = \"inner\"; missing; }").is_err());
+        assert_eq!(
+            interpreter.eval("a").unwrap(),
+            LoxValue::String("outer".to_string())
+        );
+    }
+
+    // ===== Output and REPL mode =====
+
+    #[test]
+    fn test_print_writes_to_output() {
+        let buffer = SharedBuffer::default();
+        let mut interpreter = Interpreter::new().with_output(Box::new(buffer.clone()));
+
+        interpreter.run("print 1; print \"two\";").unwrap();
+
+        assert_eq!(buffer.contents(), "1\ntwo\n");
+    }
+
+    #[test]
+    fn test_script_mode_discards_expression_values() {
+        // Given
+        let buffer = SharedBuffer::default();
+        let mut interpreter = Interpreter::new().with_output(Box::new(buffer.clone()));
+
+        // When
+        interpreter.run("2 * 3;").unwrap();
+
+        // Then
+        assert_eq!(buffer.contents(), "");
+    }
+
+    #[test]
+    fn test_repl_mode_echoes_expression_values() {
+        // Given
+        let buffer = SharedBuffer::default();
+        let mut interpreter = Interpreter::new().with_output(Box::new(buffer.clone()));
+        interpreter.repl = true;
+
+        // When
+        interpreter.run("2 * 3;").unwrap();
+
+        // Then
+        assert_eq!(buffer.contents(), "6\n");
+    }
+
+    #[test]
+    fn test_repl_mode_echoes_strings_quoted() {
+        let buffer = SharedBuffer::default();
+        let mut interpreter = Interpreter::new().with_output(Box::new(buffer.clone()));
+        interpreter.repl = true;
+
+        interpreter.run("\"a\" + \"b\"; print \"c\";").unwrap();
+
+        assert_eq!(buffer.contents(), "\"ab\"\nc\n");
+    }
+
+    #[test]
+    fn test_repl_mode_echoes_only_top_level_expressions() {
+        let buffer = SharedBuffer::default();
+        let mut interpreter = Interpreter::new().with_output(Box::new(buffer.clone()));
+        interpreter.repl = true;
+
+        interpreter.run("var a = 1; { a + 1; } print a;").unwrap();
+
+        assert_eq!(buffer.contents(), "1\n");
+    }
+
+    // ===== Call dispatch =====
+
+    fn runtime_error(interpreter: &mut Interpreter, source: &str) -> RuntimeError {
+        match interpreter.eval(source) {
+            Err(LoxError::Runtime(error)) => error,
+            other => panic!("Expected runtime error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_call_native_with_correct_arity() {
+        let mut interpreter = Interpreter::new();
+
+        assert_eq!(
+            interpreter.eval("repeat(\"-\", 2)").unwrap(),
+            LoxValue::String("--".to_string())
+        );
+    }
+
+    #[test]
+    fn test_call_native_with_wrong_arity() {
+        // Given
+        let mut interpreter = Interpreter::new();
+
+        // When
+        let error = runtime_error(&mut interpreter, "repeat(\"-\"\n)");
+
+        // Then: reported at the closing parenthesis
+        assert_eq!(error.message, "Expected 2 arguments but got 1.");
+        assert_eq!(error.token.token_type, TokenType::RightParen);
+        assert_eq!(error.token.line, 2);
+    }
+
+    #[test]
+    fn test_call_variadic_native_with_too_few_arguments() {
+        let mut interpreter = Interpreter::new();
+
+        let error = runtime_error(&mut interpreter, "format()");
+        assert_eq!(error.message, "Expected at least 1 arguments but got 0.");
+    }
+
+    #[test]
+    fn test_call_non_callable() {
+        let mut interpreter = Interpreter::new();
+
+        let error = runtime_error(&mut interpreter, "\"not a function\"()");
+        assert_eq!(error.message, "Can only call functions and classes.");
+        assert_eq!(error.token.token_type, TokenType::RightParen);
+    }
+
+    #[test]
+    fn test_call_evaluates_arguments_before_checking_callee() {
+        let mut interpreter = Interpreter::new();
+
+        // The undefined argument fails first, so the message names it
+        let error = runtime_error(&mut interpreter, "nil(missing)");
+        assert_eq!(error.message, "Undefined variable 'missing'.");
+    }
+
+    // ===== Call trace =====
+
+    fn run_error(interpreter: &mut Interpreter, source: &str) -> RuntimeError {
+        match interpreter.run(source) {
+            Err(LoxError::Runtime(error)) => error,
+            other => panic!("Expected runtime error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_top_level_error_has_no_call_trace() {
+        let mut interpreter = Interpreter::new();
+
+        let error = run_error(&mut interpreter, "1 + nil;");
+
+        assert!(error.call_trace.is_empty());
+        assert!(!error.to_string().contains('\n'));
+    }
+
+    #[test]
+    fn test_call_trace_follows_call_chain() {
+        // Given: `fail` is called from `outer` and from `other`
+        let mut interpreter = Interpreter::new();
+        interpreter
+            .run(
+                "fun fail() { return 1 + nil; }\n\
+                 fun outer() { fail(); }\n\
+                 fun other() { fail(); }",
+            )
+            .unwrap();
+
+        // When
+        let through_outer = run_error(&mut interpreter, "\n\n\nouter();");
+        let through_other = run_error(&mut interpreter, "\n\nother();");
+
+        // Then
+        assert_eq!(
+            through_outer.call_trace,
+            vec![
+                CallFrame {
+                    function: "<fn fail>".to_string(),
+                    line: 2
+                },
+                CallFrame {
+                    function: "<fn outer>".to_string(),
+                    line: 4
+                },
+            ]
+        );
+        assert_eq!(
+            through_other.call_trace,
+            vec![
+                CallFrame {
+                    function: "<fn fail>".to_string(),
+                    line: 3
+                },
+                CallFrame {
+                    function: "<fn other>".to_string(),
+                    line: 3
+                },
+            ]
+        );
+        assert!(through_outer.to_string().ends_with(
+            "\n    in <fn fail>, called at line 2\n    in <fn outer>, called at line 4"
+        ));
+    }
+
+    #[test]
+    fn test_call_stack_is_empty_after_error() {
+        let mut interpreter = Interpreter::new();
+        interpreter.run("fun f() { return nil + 1; }").unwrap();
+
+        run_error(&mut interpreter, "f();");
+
+        assert!(interpreter.call_stack.is_empty());
+        assert!(
+            run_error(&mut interpreter, "nil + 1;")
+                .call_trace
+                .is_empty()
+        );
+    }
+
+    // ===== Host functions =====
+
+    fn double(
+        _interpreter: &mut Interpreter,
+        paren: &Token,
+        arguments: &[LoxValue],
+    ) -> Result<LoxValue, RuntimeError> {
+        match arguments[0].as_number() {
+            Some(n) => Ok(LoxValue::Number(n * 2.0)),
+            None => Err(RuntimeError::new(paren, "Can only double numbers.")),
+        }
+    }
+
+    fn doubling_interpreter(buffer: &SharedBuffer) -> Interpreter {
+        let mut interpreter = Interpreter::new().with_output(Box::new(buffer.clone()));
+        let native = NativeFunction {
+            name: "double",
+            arity: 1,
+            max_arity: Some(1),
+            function: double,
+        };
+        interpreter.define_global("double", Rc::new(native));
+        interpreter
+    }
+
+    #[test]
+    fn test_define_global_is_callable_from_script() {
+        // Given
+        let buffer = SharedBuffer::default();
+        let mut interpreter = doubling_interpreter(&buffer);
+
+        // When
+        interpreter
+            .run("fun quadruple(n) { return double(double(n)); } print quadruple(5);")
+            .unwrap();
+
+        // Then
+        assert_eq!(buffer.contents(), "20\n");
+        assert!(
+            interpreter
+                .globals()
+                .borrow()
+                .dump()
+                .iter()
+                .any(|(name, _)| name == "double")
+        );
+    }
+
+    #[test]
+    fn test_define_global_is_arity_checked() {
+        let buffer = SharedBuffer::default();
+        let mut interpreter = doubling_interpreter(&buffer);
+
+        let error = run_error(&mut interpreter, "double(1, 2);");
+
+        assert_eq!(error.message, "Expected 1 arguments but got 2.");
+    }
+
+    #[test]
+    fn test_with_environment_preseeds_globals() {
+        // Given
+        let buffer = SharedBuffer::default();
+        let mut interpreter = Interpreter::new()
+            .with_output(Box::new(buffer.clone()))
+            .with_environment([("answer".to_string(), LoxValue::Number(42.0))]);
+
+        // When
+        interpreter.run("print answer + 1;").unwrap();
+
+        // Then
+        assert_eq!(buffer.contents(), "43\n");
+    }
+
+    #[test]
+    fn test_with_environment_keeps_natives() {
+        let mut interpreter =
+            Interpreter::new().with_environment([("x".to_string(), LoxValue::Number(-2.0))]);
+
+        assert_eq!(interpreter.eval("abs(x)").unwrap(), LoxValue::Number(2.0));
+    }
+
+    // ===== Print =====
+
+    fn output_of(source: &str) -> String {
+        let buffer = SharedBuffer::default();
+        let mut interpreter = Interpreter::new().with_output(Box::new(buffer.clone()));
+        interpreter.run(source).unwrap();
+        buffer.contents()
+    }
+
+    #[test]
+    fn test_print_multiple_values_separated_by_spaces() {
+        assert_eq!(output_of("print 1, 2, 3;"), "1 2 3\n");
+        assert_eq!(
+            output_of("var x = 5; print \"x =\", x, x > 1;"),
+            "x = 5 true\n"
+        );
+    }
+
+    #[test]
+    fn test_print_single_value() {
+        assert_eq!(output_of("print \"one\";"), "one\n");
+    }
+
+    #[test]
+    fn test_print_evaluates_all_values_before_printing() {
+        let buffer = SharedBuffer::default();
+        let mut interpreter = Interpreter::new().with_output(Box::new(buffer.clone()));
+
+        assert!(interpreter.run("print 1, nil + 1;").is_err());
+        assert_eq!(buffer.contents(), "");
+    }
+
+    // ===== Switch =====
+
+    #[test]
+    fn test_switch_runs_only_matching_case() {
+        let source = "var x = 2;
+            switch (x) {
+                case 1: print \"one\";
+                case 1 + 1: print \"two\"; print \"still two\";
+                case 3: print \"three\";
+                default: print \"other\";
+            }";
+
+        assert_eq!(output_of(source), "two\nstill two\n");
+    }
+
+    #[test]
+    fn test_switch_runs_default_without_match() {
+        let source = "switch (\"b\") { case \"a\": print 1; default: print 0; }";
+
+        assert_eq!(output_of(source), "0\n");
+    }
+
+    #[test]
+    fn test_switch_without_match_or_default_does_nothing() {
+        let source = "var x = 5; switch (x) { case 1: x = 0; } print x;";
+
+        assert_eq!(output_of(source), "5\n");
+    }
+
+    #[test]
+    fn test_switch_stops_evaluating_cases_after_match() {
+        // `missing` is undefined, so evaluating the second case would error
+        let source = "switch (1) { case 1: print \"hit\"; case missing: print \"no\"; }";
+
+        assert_eq!(output_of(source), "hit\n");
+    }
+
+    #[test]
+    fn test_switch_case_has_own_scope() {
+        let source = "var a = \"outer\"; switch (1) { case 1: var a = \"inner\"; } print a;";
+
+        assert_eq!(output_of(source), "outer\n");
+    }
+
+    #[test]
+    fn test_switch_case_does_not_fall_through_by_default() {
+        let source = "switch (1) { case 1: print \"one\"; case 2: print \"two\"; }";
+
+        assert_eq!(output_of(source), "one\n");
+    }
+
+    #[test]
+    fn test_fallthrough_runs_next_case_without_testing_it() {
+        // Given
+        let source = "
+            switch (0) {
+                case 0: print \"zero\"; fallthrough;
+                case missing: print \"small\"; fallthrough;
+                case 2: print \"two\";
+                case 3: print \"three\";
+                default: print \"other\";
+            }";
+
+        // When
+        let output = output_of(source);
+
+        // Then
+        assert_eq!(output, "zero\nsmall\ntwo\n");
+    }
+
+    #[test]
+    fn test_break_in_switch_leaves_enclosing_loop() {
+        let source = "
+            for (var i = 0; i < 3; i++) {
+                switch (i) { case 1: break; default: print i; }
+            }";
+
+        assert_eq!(output_of(source), "0\n");
+    }
+
+    #[test]
+    fn test_unbounded_recursion_is_stack_overflow() {
+        // When
+        let (message, line, depth, trace) = with_lox_stack(|| {
+            let error = run_error(&mut Interpreter::new(), "fun f() { f(); }\nf();");
+            let trace = error.format_trace();
+            (
+                error.message,
+                error.token.line,
+                error.call_trace.len(),
+                trace,
+            )
+        });
+
+        // Then
+        assert_eq!(message, "Stack overflow.");
+        assert_eq!(line, 1);
+        assert_eq!(depth, MAX_CALL_DEPTH);
+        assert_eq!(
+            trace,
+            format!(
+                "\n    in <fn f>, called at line 1\n    ... repeated {} more times\
+                 \n    in <fn f>, called at line 2",
+                MAX_CALL_DEPTH - 2
+            )
+        );
+    }
+
+    #[test]
+    fn test_deep_recursion_within_call_depth_limit() {
+        let source = "fun count(n) { if (n == 0) return 0; return 1 + count(n - 1); }
+            print count(5000);";
+
+        assert_eq!(with_lox_stack(|| output_of(source)), "5000\n");
+    }
+
+    #[test]
+    fn test_deep_method_recursion_fits_the_stack() {
+        // Nested expressions in every frame take more stack per call
+        let source = "class Walker {
+                walk(n) {
+                    if (n == 0) return 0;
+                    var step = (1 + (2 * (3 - 2))) - 2;
+                    return step + this.walk(n - step) * 1;
+                }
+            }
+            print Walker().walk(9990);";
+
+        assert_eq!(with_lox_stack(|| output_of(source)), "9990\n");
+    }
+
+    // ===== Classes =====
+
+    #[test]
+    fn test_class_fields_and_methods() {
+        let source = "class Counter {
+                init(start) { this.count = start; }
+                add(n) { this.count += n; return this; }
+            }
+            var c = Counter(1);
+            c.add(2).add(3);
+            print c.count, c, Counter;";
+
+        assert_eq!(output_of(source), "6 <Counter instance> <class Counter>\n");
+    }
+
+    #[test]
+    fn test_compound_property_assignment_evaluates_object_once() {
+        let source = "class Box {}
+            var box = Box(); box.x = 1;
+            var calls = 0;
+            fun get() { calls = calls + 1; return box; }
+            get().x += 2;
+            print box.x, calls;";
+
+        assert_eq!(output_of(source), "3 1\n");
+    }
+
+    #[test]
+    fn test_bound_method_keeps_this() {
+        let source = "class A { name() { return this.n; } }
+            var a = A(); a.n = \"first\";
+            var f = a.name;
+            a.n = \"second\";
+            print f();";
+
+        assert_eq!(output_of(source), "second\n");
+    }
+
+    #[test]
+    fn test_initializer_returns_this() {
+        let source = "class A { init() { this.x = 1; return; } }
+            var a = A();
+            print a.init() == nil, a.x;";
+
+        assert_eq!(output_of(source), "false 1\n");
+    }
+
+    #[test]
+    fn test_getter_is_run_on_access() {
+        let source = "class Rect {
+                init(w, h) { this.w = w; this.h = h; }
+                area { return this.w * this.h; }
+            }
+            var r = Rect(3, 4);
+            print r.area;
+            r.w = 5;
+            print r.area;";
+
+        assert_eq!(output_of(source), "12\n20\n");
+    }
+
+    #[test]
+    fn test_calling_getter_is_an_error() {
+        let mut interpreter = Interpreter::new();
+
+        let error = run_error(&mut interpreter, "class A { one { return 1; } } A().one();");
+
+        assert_eq!(error.message, "Can only call functions and classes.");
+    }
+
+    #[test]
+    fn test_class_method_is_called_on_class() {
+        let source = "class Math { class square(n) { return n * n; } }
+            print Math.square(3);";
+
+        assert_eq!(output_of(source), "9\n");
+    }
+
+    #[test]
+    fn test_class_method_has_no_this() {
+        let mut interpreter = Interpreter::new();
+
+        let error = run_error(
+            &mut interpreter,
+            "class A { class f() { return this; } } A.f();",
+        );
+
+        assert_eq!(error.message, "Undefined variable 'this'.");
+    }
+
+    #[test]
+    fn test_class_method_is_not_on_instances() {
+        let mut interpreter = Interpreter::new();
+
+        let error = run_error(&mut interpreter, "class A { class f() {} } A().f();");
+
+        assert_eq!(error.message, "Undefined property 'f'.");
+    }
+
+    #[test]
+    fn test_instances_compare_by_identity() {
+        let source = "class P { init(x) { this.x = x; } }
+            var a = P(1);
+            var b = P(1);
+            var alias = a;
+            print a == b, a != b, a == alias, alias != a;";
+
+        assert_eq!(output_of(source), "false true true false\n");
+    }
+
+    #[test]
+    fn test_instance_is_not_equal_to_its_class() {
+        assert_eq!(
+            output_of("class A {} print A() == A, A == A;"),
+            "false true\n"
+        );
+    }
+
+    #[test]
+    fn test_class_arity_is_init_arity() {
+        let mut interpreter = Interpreter::new();
+
+        let error = run_error(&mut interpreter, "class P { init(x, y) {} } P(1);");
+
+        assert_eq!(error.message, "Expected 2 arguments but got 1.");
+    }
+
+    #[test]
+    fn test_undefined_property() {
+        let mut interpreter = Interpreter::new();
+
+        let error = run_error(&mut interpreter, "class A {} A().missing;");
+
+        assert_eq!(error.message, "Undefined property 'missing'.");
+    }
+
+    #[test]
+    fn test_property_on_non_instance() {
+        let mut interpreter = Interpreter::new();
+
+        let error = run_error(&mut interpreter, "var s = \"str\"; s.x = 1;");
+
+        assert_eq!(error.message, "Only instances have fields.");
+    }
+
+    // ===== Block expressions =====
+
+    #[test]
+    fn test_block_expression_yields_last_expression() {
+        let source = "var x = { var t = 1; t + 1 }; print x;";
+
+        assert_eq!(output_of(source), "2\n");
+    }
+
+    #[test]
+    fn test_block_expression_without_tail_is_nil() {
+        assert_eq!(output_of("print { }, { var t = 1; };"), "nil nil\n");
+    }
+
+    #[test]
+    fn test_block_expression_has_own_scope() {
+        let source = "var t = \"outer\"; var x = { var t = \"inner\"; t }; print x, t;";
+
+        assert_eq!(output_of(source), "inner outer\n");
+    }
+
+    #[test]
+    fn test_error_in_block_expression_restores_scope() {
+        let mut interpreter = Interpreter::new();
+
+        run_error(&mut interpreter, "var x = { var t = 1; t + nil };");
+
+        assert!(interpreter.eval("t").is_err());
+    }
+
+    // ===== Break and continue =====
+
+    #[test]
+    fn test_break_leaves_innermost_loop() {
+        let source = "var i = 0; while (true) { i = i + 1; if (i == 3) break; } print i;";
+
+        assert_eq!(output_of(source), "3\n");
+    }
+
+    #[test]
+    fn test_labeled_break_leaves_both_loops() {
+        // Given
+        let source = "
+            outer: for (var i = 0; i < 3; i++) {
+                for (var j = 0; j < 3; j++) {
+                    if (j == 1) break outer;
+                    print i, j;
+                }
+            }
+            print \"done\";";
+
+        // When
+        let output = output_of(source);
+
+        // Then
+        assert_eq!(output, "0 0\ndone\n");
+    }
+
+    #[test]
+    fn test_continue_in_for_loop_runs_increment() {
+        let source = "for (var i = 0; i < 4; i++) { if (i == 1) continue; print i; }";
+
+        assert_eq!(output_of(source), "0\n2\n3\n");
+    }
+
+    #[test]
+    fn test_labeled_continue_resumes_outer_loop() {
+        let source = "
+            rows: for (var i = 0; i < 2; i++) {
+                for (var j = 0; j < 3; j++) {
+                    if (j == 1) continue rows;
+                    print i, j;
+                }
+            }";
+
+        assert_eq!(output_of(source), "0 0\n1 0\n");
+    }
+
+    #[test]
+    fn test_run_rejects_undefined_label_before_running() {
+        let buffer = SharedBuffer::default();
+        let mut interpreter = Interpreter::new().with_output(Box::new(buffer.clone()));
+
+        let result = interpreter.run("print 1;\nouter: while (true) { break inner; }");
+
+        match result {
+            Err(LoxError::Parse(error)) => {
+                assert_eq!(error.kind, crate::lox_error::ErrorKind::UndefinedLabel);
+                assert_eq!(error.message, "Undefined loop label 'inner'.");
+                assert_eq!(error.token.line, 2);
+            }
+            other => panic!("Expected resolve error, got {:?}", other),
+        }
+        assert_eq!(buffer.contents(), "");
+    }
+
+    // ===== Step budget =====
+
+    #[test]
+    fn test_step_budget_stops_infinite_loop() {
+        // Given
+        let mut interpreter = Interpreter::new().with_max_steps(Some(1000));
+
+        // When
+        let error = run_error(&mut interpreter, "var x = 0;\nwhile (true) {}");
+
+        // Then: the error points at the loop, not at the end of the source
+        assert_eq!(error.message, "Execution step limit exceeded.");
+        assert_eq!(error.token.lexeme, "while");
+        assert_eq!(error.token.line, 2);
+    }
+
+
+    #[test]
+    fn test_program_within_budget_runs() {
+        let mut interpreter = Interpreter::new().with_max_steps(Some(1000));
+
+        interpreter
+            .run("var x = 0; while (x < 10) x = x + 1;")
+            .unwrap();
+
+        assert_eq!(interpreter.eval("x").unwrap(), LoxValue::Number(10.0));
+    }
+
+    #[test]
+    fn test_no_step_limit_by_default() {
+        let mut interpreter = Interpreter::new();
+
+        interpreter
+            .run("var x = 0; while (x < 100000) x = x + 1;")
+            .unwrap();
+
+        assert_eq!(interpreter.eval("x").unwrap(), LoxValue::Number(100000.0));
+    }
+
+    // ===== Resolution =====
+
+    #[test]
+    fn test_closure_keeps_binding_it_was_resolved_to() {
+        // Given
+        let source = "
+            var a = \"global\";
+            {
+                fun show() { print a; }
+                show();
+                var a = \"block\";
+                show();
+            }";
+
+        // When
+        let output = output_of(source);
+
+        // Then
+        assert_eq!(output, "global\nglobal\n");
+    }
+
+    #[test]
+    fn test_resolved_locals_survive_across_runs() {
+        let mut interpreter = Interpreter::new();
+        interpreter
+            .run("fun counter() { var n = 0; fun next() { n++; return n; } return next; }")
+            .unwrap();
+        interpreter.run("var c = counter(); c();").unwrap();
+
+        assert_eq!(interpreter.eval("c()").unwrap(), LoxValue::Number(2.0));
+    }
+
+    #[test]
+    fn test_methods_resolve_this_and_locals() {
+        let source = "
+            class Box {
+                init(v) { this.v = v; }
+                get { var w = this.v; return { var x = w; x * 2 }; }
+            }
+            print Box(3).get;";
+
+        assert_eq!(output_of(source), "6\n");
+    }
+
+    // ===== Reset =====
+
+    #[test]
+    fn test_reset_clears_globals_but_keeps_natives() {
+        // Given
+        let mut interpreter = Interpreter::new();
+        interpreter.run("var a = 1; fun f() {}").unwrap();
+
+        // When
+        interpreter.reset();
+
+        // Then
+        assert!(interpreter.eval("a").is_err());
+        assert!(interpreter.eval("f").is_err());
+        assert!(matches!(
+            interpreter.eval("clock").unwrap(),
+            LoxValue::Callable(_)
+        ));
+        interpreter.run("var a = 2;").unwrap();
+        assert_eq!(interpreter.eval("a").unwrap(), LoxValue::Number(2.0));
+    }
+
+    #[test]
+    fn test_reset_restarts_step_count() {
+        let mut interpreter = Interpreter::new().with_max_steps(Some(50));
+        interpreter
+            .run("var i = 0; while (i < 5) i = i + 1;")
+            .unwrap();
+        assert!(
+            interpreter
+                .run("var i = 0; while (i < 5) i = i + 1;")
+                .is_err()
+        );
+
+        interpreter.reset();
+
+        assert!(
+            interpreter
+                .run("var i = 0; while (i < 5) i = i + 1;")
+                .is_ok()
+        );
+    }
+}
@@ -1,9 +1,25 @@
 pub mod ast_printer;
+pub mod bytecode;
+pub mod clock;
+pub mod environment;
 pub mod expr;
 pub mod interpreter;
 pub mod lox;
+pub mod lox_callable;
+pub mod lox_class;
+pub mod lox_error;
+pub mod lox_function;
+pub mod lox_instance;
 pub mod lox_value;
+pub mod natives;
+pub mod optimize;
 pub mod parser;
+pub mod repl;
+pub mod resolver;
+pub mod rng;
 pub mod runtime_error;
 pub mod scanner;
+pub mod stmt;
+#[cfg(test)]
+mod test_support;
 pub mod token;
@@ -1,3 +1,80 @@
+const RED: &str = "\x1b[31m";
+const RESET: &str = "\x1b[0m";
+
+/// Driver state shared by every run
+///
+/// `color` turns on ANSI colors in formatted diagnostics. The driver only
+/// enables it when stderr is a terminal and `--no-color` was not given.
 pub struct Lox {
-    hadError: bool,
+    pub had_error: bool,
+    pub color: bool,
+}
+
+impl Lox {
+    pub fn new(color: bool) -> Self {
+        Self {
+            had_error: false,
+            color,
+        }
+    }
+
+    /// Format a diagnostic as `[line N] <kind><location>: <message>`
+    ///
+    /// With colors on, the line marker and the kind are red.
+    ///
+    /// # Examples
+    /// - `(3, "Error", " at ';'", "Expect expression.")`
+    ///   -> `[line 3] Error at ';': Expect expression.`
+    /// - `(1, "Warning", "", "Unused value.")` -> `[line 1] Warning: Unused value.`
+    pub fn format_error(&self, line: usize, kind: &str, location: &str, message: &str) -> String {
+        format!(
+            "{}{}: {}",
+            self.paint(&format!("[line {}] {}", line, kind)),
+            location,
+            message
+        )
+    }
+
+    fn paint(&self, text: &str) -> String {
+        if self.color {
+            format!("{}{}{}", RED, text, RESET)
+        } else {
+            text.to_string()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn strip_ansi(text: &str) -> String {
+        text.replace(RED, "").replace(RESET, "")
+    }
+
+    #[test]
+    fn test_plain_format_has_no_escape_codes() {
+        let lox = Lox::new(false);
+
+        let message = lox.format_error(3, "Error", " at ';'", "Expect expression.");
+
+        assert_eq!(message, "[line 3] Error at ';': Expect expression.");
+        assert!(!message.contains('\x1b'));
+    }
+
+    #[test]
+    fn test_colored_format_differs_only_in_escape_codes() {
+        // Given
+        let plain = Lox::new(false);
+        let colored = Lox::new(true);
+
+        // When
+        let plain_message = plain.format_error(7, "Runtime Error", " at '+'", "Bad operands.");
+        let colored_message = colored.format_error(7, "Runtime Error", " at '+'", "Bad operands.");
+
+        // Then
+        assert_ne!(plain_message, colored_message);
+        assert!(colored_message.starts_with(RED));
+        assert_eq!(strip_ansi(&colored_message), plain_message);
+    }
 }
@@ -0,0 +1,67 @@
+use core::fmt;
+
+use crate::interpreter::Interpreter;
+use crate::lox_value::LoxValue;
+use crate::runtime_error::RuntimeError;
+use crate::token::Token;
+
+/// Anything that can be called from Lox with `callee(args...)`
+///
+/// The interpreter checks the argument count against `arity` and
+/// `max_arity` before `call`, so implementations can rely on it.
+pub trait LoxCallable: fmt::Debug + fmt::Display {
+    /// Fewest arguments accepted
+    fn arity(&self) -> usize;
+
+    /// Most arguments accepted, None for no upper limit
+    fn max_arity(&self) -> Option<usize> {
+        Some(self.arity())
+    }
+
+    /// `paren` is the call's closing parenthesis, used to locate errors
+    fn call(
+        &self,
+        interpreter: &mut Interpreter,
+        paren: &Token,
+        arguments: Vec<LoxValue>,
+    ) -> Result<LoxValue, RuntimeError>;
+}
+
+/// Signature of a function implemented in Rust
+pub type NativeFn = fn(&mut Interpreter, &Token, &[LoxValue]) -> Result<LoxValue, RuntimeError>;
+
+/// Function implemented in Rust and exposed to Lox as a global
+///
+/// Optional and variadic arguments are expressed with `max_arity`.
+#[derive(Debug)]
+pub struct NativeFunction {
+    pub name: &'static str,
+    pub arity: usize,
+    pub max_arity: Option<usize>,
+    pub function: NativeFn,
+}
+
+impl LoxCallable for NativeFunction {
+    fn arity(&self) -> usize {
+        self.arity
+    }
+
+    fn max_arity(&self) -> Option<usize> {
+        self.max_arity
+    }
+
+    fn call(
+        &self,
+        interpreter: &mut Interpreter,
+        paren: &Token,
+        arguments: Vec<LoxValue>,
+    ) -> Result<LoxValue, RuntimeError> {
+        (self.function)(interpreter, paren, &arguments)
+    }
+}
+
+impl fmt::Display for NativeFunction {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "<native fn>")
+    }
+}
@@ -0,0 +1,95 @@
+use core::fmt;
+use std::collections::HashMap;
+use std::rc::{Rc, Weak};
+
+use crate::interpreter::Interpreter;
+use crate::lox_callable::LoxCallable;
+use crate::lox_function::LoxFunction;
+use crate::lox_instance::LoxInstance;
+use crate::lox_value::LoxValue;
+use crate::runtime_error::RuntimeError;
+use crate::token::Token;
+
+/// Class declared in Lox
+///
+/// Calling the class creates an instance and runs its `init` method, if
+/// any, with the call's arguments. Getters are kept apart from methods
+/// because reading them calls them. Class methods are read from the class
+/// itself and have no `this`.
+#[derive(Debug)]
+pub struct LoxClass {
+    pub name: String,
+    pub methods: HashMap<String, Rc<LoxFunction>>,
+    pub getters: HashMap<String, Rc<LoxFunction>>,
+    pub class_methods: HashMap<String, Rc<LoxFunction>>,
+    // Handle on the Rc holding this class, so `call` can give new
+    // instances a reference to their class
+    this: Weak<LoxClass>,
+}
+
+impl LoxClass {
+    pub fn new(
+        name: String,
+        methods: HashMap<String, Rc<LoxFunction>>,
+        getters: HashMap<String, Rc<LoxFunction>>,
+        class_methods: HashMap<String, Rc<LoxFunction>>,
+    ) -> Rc<Self> {
+        Rc::new_cyclic(|this| Self {
+            name,
+            methods,
+            getters,
+            class_methods,
+            this: this.clone(),
+        })
+    }
+
+    pub fn find_method(&self, name: &str) -> Option<Rc<LoxFunction>> {
+        self.methods.get(name).cloned()
+    }
+
+    pub fn find_getter(&self, name: &str) -> Option<Rc<LoxFunction>> {
+        self.getters.get(name).cloned()
+    }
+
+    /// Class method `name`, the value of `Class.name`
+    pub fn get(&self, name: &Token) -> Result<LoxValue, RuntimeError> {
+        match self.class_methods.get(&name.lexeme) {
+            Some(method) => Ok(LoxValue::Callable(Rc::clone(method) as Rc<dyn LoxCallable>)),
+            None => Err(RuntimeError::new(
+                name,
+                &format!("Undefined class method '{}'.", name.lexeme),
+            )),
+        }
+    }
+}
+
+impl LoxCallable for LoxClass {
+    fn arity(&self) -> usize {
+        self.find_method("init").map_or(0, |init| init.arity())
+    }
+
+    fn call(
+        &self,
+        interpreter: &mut Interpreter,
+        paren: &Token,
+        arguments: Vec<LoxValue>,
+    ) -> Result<LoxValue, RuntimeError> {
+        let class = self
+            .this
+            .upgrade()
+            .expect("a class is only reachable through its Rc");
+        let instance = LoxValue::Instance(LoxInstance::new(class));
+
+        if let Some(init) = self.find_method("init") {
+            init.bind(instance.clone())
+                .call(interpreter, paren, arguments)?;
+        }
+        Ok(instance)
+    }
+}
+
+impl fmt::Display for LoxClass {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "<class {}>", self.name)
+    }
+}
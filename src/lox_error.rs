@@ -0,0 +1,186 @@
+use std::collections::HashSet;
+
+use crate::parser::ParseError;
+use crate::runtime_error::RuntimeError;
+use crate::scanner::ScanError;
+
+/// What went wrong, independent of the human-readable message
+///
+/// Lets callers and tests react to an error without matching on its text.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ErrorKind {
+    // ===== Scanning =====
+    /// A character that starts no token, e.g. `@`
+    UnexpectedChar,
+    /// Input ended inside a string literal
+    UnterminatedString,
+    /// Malformed or unknown escape sequence in a string, e.g. `\u{110000}`
+    InvalidEscape,
+    /// Number literal that is malformed or too large, e.g. `5.`
+    InvalidNumber,
+
+    // ===== Parsing =====
+    /// A required token is missing, e.g. `)` or `;`
+    ExpectedToken,
+    /// An expression was required but none starts here
+    ExpectedExpression,
+    /// Left side of `=`, `++` or `--` is not a variable
+    InvalidAssignTarget,
+    /// More parameters or arguments than `Parser::with_max_args` allows,
+    /// 255 by default
+    TooManyArguments,
+    /// `a < b < c`
+    ChainedComparison,
+    /// Warning for `if (x = 1)`
+    AssignmentInCondition,
+    /// `return` directly inside a block expression, or returning a value
+    /// from an initializer
+    InvalidReturn,
+    /// `break` or `continue` outside a loop
+    InvalidLoopControl,
+    /// `fallthrough` anywhere but at the end of a case followed by another
+    InvalidFallthrough,
+
+    // ===== Resolving =====
+    /// Warning for statements after a `return` in the same block
+    UnreachableCode,
+    /// `break` or `continue` naming no enclosing loop, e.g. `break outer;`
+    UndefinedLabel,
+}
+
+/// Any error produced while running Lox source
+///
+/// Wraps the error of each phase so a single entry point
+/// (e.g. `Interpreter::eval`) can return one error type.
+#[derive(Debug)]
+pub enum LoxError {
+    Scan(ScanError),
+    Parse(ParseError),
+    Runtime(RuntimeError),
+}
+
+impl LoxError {
+    /// Line of the source the error was found on
+    pub fn line(&self) -> usize {
+        match self {
+            LoxError::Scan(error) => error.line,
+            LoxError::Parse(error) => error.token.line,
+            LoxError::Runtime(error) => error.token.line,
+        }
+    }
+
+    /// Where the error is, `foo.lox:12` for a named source or `line 12`
+    pub fn location(&self) -> String {
+        match self {
+            LoxError::Scan(error) => error.location(),
+            LoxError::Parse(error) => error.token.location(),
+            LoxError::Runtime(error) => error.token.location(),
+        }
+    }
+
+    pub fn message(&self) -> &str {
+        match self {
+            LoxError::Scan(error) => &error.message,
+            LoxError::Parse(error) => &error.message,
+            LoxError::Runtime(error) => &error.message,
+        }
+    }
+}
+
+/// Order errors by line and drop repeats of a message on the same line
+///
+/// The sort is stable, so errors on one line keep the order they were
+/// found in. Tokens carry no column, so the line is the finest position.
+///
+/// # Example
+/// - lines `[3 "b", 1 "a", 3 "b"]` -> `[1 "a", 3 "b"]`
+pub fn sort_and_dedup(errors: &mut Vec<LoxError>) {
+    errors.sort_by_key(LoxError::line);
+    let mut seen = HashSet::new();
+    errors.retain(|error| seen.insert((error.line(), error.message().to_string())));
+}
+
+impl From<ScanError> for LoxError {
+    fn from(error: ScanError) -> Self {
+        LoxError::Scan(error)
+    }
+}
+
+impl From<ParseError> for LoxError {
+    fn from(error: ParseError) -> Self {
+        LoxError::Parse(error)
+    }
+}
+
+impl From<RuntimeError> for LoxError {
+    fn from(error: RuntimeError) -> Self {
+        LoxError::Runtime(error)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lox_error::ErrorKind;
+    use crate::token::{Token, TokenType};
+
+    fn scan_error(line: usize, message: &str) -> LoxError {
+        LoxError::Scan(ScanError {
+            line,
+            kind: ErrorKind::UnexpectedChar,
+            message: message.to_string(),
+            source_name: None,
+        })
+    }
+
+    fn parse_error(line: usize, message: &str) -> LoxError {
+        LoxError::Parse(ParseError {
+            kind: ErrorKind::ExpectedExpression,
+            message: message.to_string(),
+            token: Token::new(TokenType::Semicolon, ";".to_string(), None, line),
+        })
+    }
+
+    #[test]
+    fn test_sort_and_dedup() {
+        // Given: out of order, with a repeat on line 3
+        let mut errors = vec![
+            parse_error(3, "Expect expression."),
+            scan_error(1, "Unexpected character."),
+            scan_error(3, "Unexpected character."),
+            parse_error(3, "Expect expression."),
+            parse_error(2, "Expect ';' after value."),
+        ];
+
+        // When
+        sort_and_dedup(&mut errors);
+
+        // Then
+        let summary: Vec<(usize, &str)> = errors
+            .iter()
+            .map(|error| (error.line(), error.message()))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                (1, "Unexpected character."),
+                (2, "Expect ';' after value."),
+                (3, "Expect expression."),
+                (3, "Unexpected character."),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_sort_and_dedup_keeps_same_message_on_other_lines() {
+        let mut errors = vec![
+            scan_error(2, "Unexpected character."),
+            scan_error(1, "Unexpected character."),
+        ];
+
+        sort_and_dedup(&mut errors);
+
+        assert_eq!(errors.len(), 2);
+        assert_eq!(errors[0].line(), 1);
+    }
+}
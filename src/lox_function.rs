@@ -0,0 +1,95 @@
+use core::fmt;
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use crate::environment::Environment;
+use crate::interpreter::{Interpreter, Unwind};
+use crate::lox_callable::LoxCallable;
+use crate::lox_value::LoxValue;
+use crate::runtime_error::{CallFrame, RuntimeError};
+use crate::stmt::Stmt;
+use crate::token::{Token, TokenType};
+
+/// Function defined in Lox, by a declaration or a lambda expression
+///
+/// `closure` is the environment the function was created in, so the body
+/// can keep using variables of enclosing scopes after they have returned.
+#[derive(Debug)]
+pub struct LoxFunction {
+    /// None for lambdas
+    pub name: Option<String>,
+    pub params: Vec<Token>,
+    pub body: Rc<Vec<Stmt>>,
+    pub closure: Rc<RefCell<Environment>>,
+    /// A class's `init` method, which always returns `this`
+    pub is_initializer: bool,
+}
+
+impl LoxFunction {
+    /// Copy of this method whose `this` is `instance`
+    pub fn bind(&self, instance: LoxValue) -> LoxFunction {
+        let mut environment = Environment::with_enclosing(Rc::clone(&self.closure));
+        environment.define("this".to_string(), instance);
+        LoxFunction {
+            name: self.name.clone(),
+            params: self.params.clone(),
+            body: Rc::clone(&self.body),
+            closure: Rc::new(RefCell::new(environment)),
+            is_initializer: self.is_initializer,
+        }
+    }
+
+    // `this` of a bound initializer
+    fn this(&self) -> Result<LoxValue, RuntimeError> {
+        let this = Token::new(TokenType::This, "this".to_string(), None, 0);
+        self.closure.borrow().get(&this)
+    }
+}
+
+impl LoxCallable for LoxFunction {
+    fn arity(&self) -> usize {
+        self.params.len()
+    }
+
+    fn call(
+        &self,
+        interpreter: &mut Interpreter,
+        paren: &Token,
+        arguments: Vec<LoxValue>,
+    ) -> Result<LoxValue, RuntimeError> {
+        let mut environment = Environment::with_enclosing(Rc::clone(&self.closure));
+        for (param, argument) in self.params.iter().zip(arguments) {
+            environment.define(param.lexeme.clone(), argument);
+        }
+
+        let frame = CallFrame {
+            function: self.to_string(),
+            line: paren.line,
+        };
+        interpreter.in_frame(frame, paren, |interpreter| {
+            match interpreter.execute_block(&self.body, environment) {
+                Ok(()) | Err(Unwind::Return(_)) if self.is_initializer => self.this(),
+                Ok(()) => Ok(LoxValue::Nil),
+                Err(Unwind::Return(value)) => Ok(value),
+                Err(Unwind::Error(error)) => Err(error),
+                Err(Unwind::Break(_) | Unwind::Continue(_)) => {
+                    unreachable!(
+                        "the parser and resolver reject `break` and `continue` leaving a function"
+                    )
+                }
+                Err(Unwind::Fallthrough) => {
+                    unreachable!("the parser accepts `fallthrough` only at the end of a case")
+                }
+            }
+        })
+    }
+}
+
+impl fmt::Display for LoxFunction {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match &self.name {
+            Some(name) => write!(f, "<fn {}>", name),
+            None => write!(f, "<fn>"),
+        }
+    }
+}
@@ -0,0 +1,149 @@
+use core::fmt;
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use crate::lox_class::LoxClass;
+use crate::lox_function::LoxFunction;
+use crate::lox_value::LoxValue;
+use crate::runtime_error::RuntimeError;
+use crate::token::Token;
+
+/// Instance of a Lox class
+///
+/// Fields are kept in the order they were first set, so dumps and debug
+/// output are deterministic. Instances are small, so a linear search is
+/// cheaper than a hash map here.
+#[derive(Debug)]
+pub struct LoxInstance {
+    pub class: Rc<LoxClass>,
+    fields: Vec<(String, LoxValue)>,
+}
+
+impl LoxInstance {
+    /// New instance without fields, shared like every Lox object
+    pub fn new(class: Rc<LoxClass>) -> Rc<RefCell<Self>> {
+        Rc::new(RefCell::new(Self {
+            class,
+            fields: Vec::new(),
+        }))
+    }
+
+    /// Field `name`, or else method `name` bound to `instance`
+    pub fn get(
+        instance: &Rc<RefCell<LoxInstance>>,
+        name: &Token,
+    ) -> Result<LoxValue, RuntimeError> {
+        let this = instance.borrow();
+        if let Some((_, value)) = this.fields.iter().find(|(field, _)| *field == name.lexeme) {
+            return Ok(value.clone());
+        }
+
+        match this.class.find_method(&name.lexeme) {
+            Some(method) => {
+                let bound = method.bind(LoxValue::Instance(Rc::clone(instance)));
+                Ok(LoxValue::Callable(Rc::new(bound)))
+            }
+            None => Err(RuntimeError::new(
+                name,
+                &format!("Undefined property '{}'.", name.lexeme),
+            )),
+        }
+    }
+
+    /// Getter `name` bound to `instance`, unless a field of that name hides it
+    pub fn getter(instance: &Rc<RefCell<LoxInstance>>, name: &Token) -> Option<LoxFunction> {
+        let this = instance.borrow();
+        if this.fields.iter().any(|(field, _)| *field == name.lexeme) {
+            return None;
+        }
+        let getter = this.class.find_getter(&name.lexeme)?;
+        Some(getter.bind(LoxValue::Instance(Rc::clone(instance))))
+    }
+
+    /// Set a field, adding it after the existing ones if it is new
+    pub fn set(&mut self, name: &Token, value: LoxValue) {
+        match self
+            .fields
+            .iter_mut()
+            .find(|(field, _)| *field == name.lexeme)
+        {
+            Some((_, slot)) => *slot = value,
+            None => self.fields.push((name.lexeme.clone(), value)),
+        }
+    }
+
+    /// Fields in the order they were first set
+    pub fn dump(&self) -> Vec<(String, LoxValue)> {
+        self.fields.clone()
+    }
+}
+
+impl fmt::Display for LoxInstance {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "<{} instance>", self.class.name)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::token::TokenType;
+    use std::collections::HashMap;
+
+    fn identifier(name: &str) -> Token {
+        Token::new(TokenType::Identifier, name.to_string(), None, 1)
+    }
+
+    #[test]
+    fn test_dump_lists_fields_in_insertion_order() {
+        // Given
+        let class = LoxClass::new(
+            "Point".to_string(),
+            HashMap::new(),
+            HashMap::new(),
+            HashMap::new(),
+        );
+        let instance = LoxInstance::new(class);
+
+        // When
+        for (name, value) in [("zeta", 1.0), ("alpha", 2.0), ("mid", 3.0)] {
+            instance
+                .borrow_mut()
+                .set(&identifier(name), LoxValue::Number(value));
+        }
+        instance
+            .borrow_mut()
+            .set(&identifier("zeta"), LoxValue::Number(4.0));
+
+        // Then
+        assert_eq!(
+            instance.borrow().dump(),
+            vec![
+                ("zeta".to_string(), LoxValue::Number(4.0)),
+                ("alpha".to_string(), LoxValue::Number(2.0)),
+                ("mid".to_string(), LoxValue::Number(3.0)),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_get_prefers_field_over_method() {
+        let class = LoxClass::new(
+            "Empty".to_string(),
+            HashMap::new(),
+            HashMap::new(),
+            HashMap::new(),
+        );
+        let instance = LoxInstance::new(class);
+
+        instance
+            .borrow_mut()
+            .set(&identifier("x"), LoxValue::Bool(true));
+
+        assert_eq!(
+            LoxInstance::get(&instance, &identifier("x")).unwrap(),
+            LoxValue::Bool(true)
+        );
+        assert!(LoxInstance::get(&instance, &identifier("y")).is_err());
+    }
+}
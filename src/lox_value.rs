@@ -1,27 +1,42 @@
 use core::fmt;
+use std::cell::RefCell;
+use std::convert::Infallible;
 use std::fmt::Formatter;
+use std::ops::{Add, Div, Mul, Sub};
+use std::rc::Rc;
+use std::str::FromStr;
 
 use crate::expr::LiteralValue;
+use crate::lox_callable::LoxCallable;
+use crate::lox_class::LoxClass;
+use crate::lox_instance::LoxInstance;
+use crate::runtime_error::RuntimeError;
+use crate::token::Token;
 /// runtime value of Lox
 ///
 /// Lox is dynamic type language
 /// value for interpreter.rs
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone)]
 pub enum LoxValue {
     Number(f64),
     String(String),
     Bool(bool),
     Nil,
+    Callable(Rc<dyn LoxCallable>),
+    Class(Rc<LoxClass>),
+    Instance(Rc<RefCell<LoxInstance>>),
 }
 
 impl LoxValue {
     // check whether LoxValue is truthy or not
     // bool and nil are false
     pub fn is_truthy(&self) -> bool {
-        match self {
-            LoxValue::Bool(false) | LoxValue::Nil => false,
-            _ => true,
-        }
+        !matches!(self, LoxValue::Bool(false) | LoxValue::Nil)
+    }
+
+    // exact complement of is_truthy: only false and nil are falsey
+    pub fn is_falsey(&self) -> bool {
+        !self.is_truthy()
     }
 
     // check type is Number and return value
@@ -32,10 +47,16 @@ impl LoxValue {
         }
     }
 
+    // check value is a finite number with no fractional part, like 2 but
+    // not 2.5, inf or NaN
+    pub fn is_integer(&self) -> bool {
+        matches!(self, LoxValue::Number(n) if n.is_finite() && n.fract() == 0.0)
+    }
+
     // check type is string and return value
     pub fn as_string(&self) -> Option<&str> {
         match self {
-            LoxValue::String(s) => Some(&s),
+            LoxValue::String(s) => Some(s),
             _ => None,
         }
     }
@@ -52,6 +73,136 @@ impl LoxValue {
     pub fn as_nil(&self) -> bool {
         matches!(self, LoxValue::Nil)
     }
+
+    // check value can be called (a function or a class) and return it
+    pub fn as_callable(&self) -> Option<&dyn LoxCallable> {
+        match self {
+            LoxValue::Callable(c) => Some(c.as_ref()),
+            LoxValue::Class(c) => Some(c.as_ref()),
+            _ => None,
+        }
+    }
+
+    /// Text the REPL echoes for this value
+    ///
+    /// Strings are quoted, with quotes, backslashes, newlines, tabs and NUL
+    /// escaped as in Lox source, so `"1"` and `1` look different. Every
+    /// other value is shown as `Display` shows it.
+    ///
+    /// # Examples
+    /// - `String("say \"hi\"\n")` -> `"say \"hi\"\n"`
+    /// - `Number(1.0)` -> `1`
+    pub fn to_display_repl(&self) -> String {
+        let LoxValue::String(s) = self else {
+            return self.to_string();
+        };
+
+        let mut quoted = String::with_capacity(s.len() + 2);
+        quoted.push('"');
+        for c in s.chars() {
+            match c {
+                '"' => quoted.push_str("\\\""),
+                '\\' => quoted.push_str("\\\\"),
+                '\n' => quoted.push_str("\\n"),
+                '\t' => quoted.push_str("\\t"),
+                '\r' => quoted.push_str("\\r"),
+                '\0' => quoted.push_str("\\0"),
+                _ => quoted.push(c),
+            }
+        }
+        quoted.push('"');
+        quoted
+    }
+
+    // name of the value's type, for error messages
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            LoxValue::Number(_) => "number",
+            LoxValue::String(_) => "string",
+            LoxValue::Bool(_) => "bool",
+            LoxValue::Nil => "nil",
+            LoxValue::Callable(_) => "function",
+            LoxValue::Class(_) => "class",
+            LoxValue::Instance(_) => "instance",
+        }
+    }
+}
+
+/// Failed `TryFrom<LoxValue>` conversion: the value had another type
+///
+/// # Example
+/// - `f64::try_from(LoxValue::Nil)` -> `Expected a number but got nil.`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConversionError {
+    pub expected: &'static str,
+    pub found: &'static str,
+}
+
+impl fmt::Display for ConversionError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "Expected a {} but got {}.", self.expected, self.found)
+    }
+}
+
+impl std::error::Error for ConversionError {}
+
+impl TryFrom<LoxValue> for f64 {
+    type Error = ConversionError;
+
+    fn try_from(value: LoxValue) -> Result<Self, Self::Error> {
+        match value {
+            LoxValue::Number(n) => Ok(n),
+            other => Err(ConversionError {
+                expected: "number",
+                found: other.type_name(),
+            }),
+        }
+    }
+}
+
+impl TryFrom<LoxValue> for String {
+    type Error = ConversionError;
+
+    fn try_from(value: LoxValue) -> Result<Self, Self::Error> {
+        match value {
+            LoxValue::String(s) => Ok(s),
+            other => Err(ConversionError {
+                expected: "string",
+                found: other.type_name(),
+            }),
+        }
+    }
+}
+
+impl TryFrom<LoxValue> for bool {
+    type Error = ConversionError;
+
+    fn try_from(value: LoxValue) -> Result<Self, Self::Error> {
+        match value {
+            LoxValue::Bool(b) => Ok(b),
+            other => Err(ConversionError {
+                expected: "bool",
+                found: other.type_name(),
+            }),
+        }
+    }
+}
+
+// callables, classes and instances are equal only when they are the same
+// object; two instances with equal fields are still different values
+impl PartialEq for LoxValue {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (LoxValue::Number(a), LoxValue::Number(b)) => a == b,
+            (LoxValue::String(a), LoxValue::String(b)) => a == b,
+            (LoxValue::Bool(a), LoxValue::Bool(b)) => a == b,
+            (LoxValue::Nil, LoxValue::Nil) => true,
+            (LoxValue::Callable(a), LoxValue::Callable(b)) => Rc::ptr_eq(a, b),
+            (LoxValue::Class(a), LoxValue::Class(b)) => Rc::ptr_eq(a, b),
+            (LoxValue::Instance(a), LoxValue::Instance(b)) => Rc::ptr_eq(a, b),
+            _ => false,
+        }
+    }
 }
 
 // change from LiteralValue to LoxValue
@@ -66,11 +217,43 @@ impl From<LiteralValue> for LoxValue {
     }
 }
 
+/// Parse text such as a config value or a test fixture into a value
+///
+/// `nil`, `true` and `false` become the keyword values and finite numbers
+/// become numbers; anything else is kept as a string. Text that could be
+/// either is resolved in favor of the keyword or number, so the string
+/// `"true"` cannot be produced this way. Parsing never fails.
+///
+/// # Examples
+/// - `"nil"` -> `Nil`
+/// - `"2.5"` -> `Number(2.5)`
+/// - `"hello"` -> `String("hello")`
+impl FromStr for LoxValue {
+    type Err = Infallible;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(match s {
+            "nil" => LoxValue::Nil,
+            "true" => LoxValue::Bool(true),
+            "false" => LoxValue::Bool(false),
+            _ => match s.parse::<f64>() {
+                Ok(n) if n.is_finite() => LoxValue::Number(n),
+                _ => LoxValue::String(s.to_string()),
+            },
+        })
+    }
+}
+
+// `{}` prints integral numbers without a decimal part, `{:#}` keeps it (`42.0`)
 impl fmt::Display for LoxValue {
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
         match self {
             LoxValue::Number(n) => {
-                if n.fract() == 0.0 && n.is_finite() {
+                // i64::MAX as f64 rounds up to 2^63, which is already out of range
+                let fits_i64 = *n >= i64::MIN as f64 && *n < i64::MAX as f64;
+                if n.fract() == 0.0 && n.is_finite() && f.alternate() {
+                    write!(f, "{:.1}", n)
+                } else if n.fract() == 0.0 && n.is_finite() && fits_i64 {
                     write!(f, "{}", *n as i64)
                 } else {
                     write!(f, "{}", n)
@@ -79,282 +262,579 @@ impl fmt::Display for LoxValue {
             LoxValue::String(s) => write!(f, "{}", s),
             LoxValue::Bool(b) => write!(f, "{}", b),
             LoxValue::Nil => write!(f, "nil"),
+            LoxValue::Callable(c) => write!(f, "{}", c),
+            LoxValue::Class(c) => write!(f, "{}", c),
+            LoxValue::Instance(i) => write!(f, "{}", i.borrow()),
         }
     }
 }
 
-#[cfg(test)]
-mod test {
-    use crate::lox_value::LoxValue;
+/// Lox arithmetic, shared by host code and the interpreter
+///
+/// `+` adds two numbers or concatenates two strings; `-`, `*` and `/` take
+/// two numbers. A type error carries a placeholder EOF token on line 0,
+/// since no source token is at hand; the interpreter swaps in the operator.
+///
+/// # Example
+/// ```ignore
+/// let sum = (&LoxValue::Number(1.0) + &LoxValue::Number(2.0))?;
+/// assert_eq!(sum, LoxValue::Number(3.0));
+/// ```
+impl Add for &LoxValue {
+    type Output = Result<LoxValue, RuntimeError>;
+
+    fn add(self, other: &LoxValue) -> Self::Output {
+        match (self, other) {
+            (LoxValue::Number(l), LoxValue::Number(r)) => Ok(LoxValue::Number(l + r)),
+            (LoxValue::String(l), LoxValue::String(r)) => Ok(LoxValue::String(format!("{}{}", l, r))),
+            _ => Err(operand_error("Operands must be two numbers or two strings.")),
+        }
+    }
+}
 
-    #[cfg(test)]
-    mod test {
-        use crate::expr::LiteralValue;
-        use crate::lox_value::LoxValue;
+impl Sub for &LoxValue {
+    type Output = Result<LoxValue, RuntimeError>;
 
-        // ===== Truthiness Tests =====
+    fn sub(self, other: &LoxValue) -> Self::Output {
+        let (l, r) = number_operands(self, other)?;
+        Ok(LoxValue::Number(l - r))
+    }
+}
 
-        #[test]
-        fn test_is_truthy_bool_false() {
-            let bool_false = LoxValue::Bool(false);
-            assert_eq!(bool_false.is_truthy(), false);
-        }
+impl Mul for &LoxValue {
+    type Output = Result<LoxValue, RuntimeError>;
 
-        #[test]
-        fn test_is_truthy_bool_true() {
-            let bool_true = LoxValue::Bool(true);
-            assert_eq!(bool_true.is_truthy(), true);
-        }
+    fn mul(self, other: &LoxValue) -> Self::Output {
+        let (l, r) = number_operands(self, other)?;
+        Ok(LoxValue::Number(l * r))
+    }
+}
 
-        #[test]
-        fn test_is_truthy_nil() {
-            let nil_false = LoxValue::Nil;
-            assert_eq!(nil_false.is_truthy(), false);
-        }
+/// A zero divisor raises "Division by zero.", as in the interpreter under
+/// its default `DivByZero::Error`
+impl Div for &LoxValue {
+    type Output = Result<LoxValue, RuntimeError>;
 
-        #[test]
-        fn test_is_truthy_number_zero() {
-            let number_zero = LoxValue::Number(0.0);
-            assert_eq!(number_zero.is_truthy(), true);
+    fn div(self, other: &LoxValue) -> Self::Output {
+        let (l, r) = number_operands(self, other)?;
+        if r == 0.0 {
+            return Err(operand_error("Division by zero."));
         }
+        Ok(LoxValue::Number(l / r))
+    }
+}
 
-        #[test]
-        fn test_is_truthy_number_positive() {
-            let number_positive = LoxValue::Number(42.0);
-            assert_eq!(number_positive.is_truthy(), true);
-        }
+fn number_operands(left: &LoxValue, right: &LoxValue) -> Result<(f64, f64), RuntimeError> {
+    match (left, right) {
+        (LoxValue::Number(l), LoxValue::Number(r)) => Ok((*l, *r)),
+        _ => Err(operand_error("Operands must be numbers.")),
+    }
+}
 
-        #[test]
-        fn test_is_truthy_number_negative() {
-            let number_negative = LoxValue::Number(-5.0);
-            assert_eq!(number_negative.is_truthy(), true);
-        }
+fn operand_error(message: &str) -> RuntimeError {
+    RuntimeError::new(&Token::eof(0), message)
+}
 
-        #[test]
-        fn test_is_truthy_string_empty() {
-            let string_empty = LoxValue::String(String::from(""));
-            assert_eq!(string_empty.is_truthy(), true);
-        }
+#[cfg(test)]
+mod tests {
+    use crate::expr::LiteralValue;
+    use crate::interpreter::Interpreter;
+    use crate::lox_value::{ConversionError, LoxValue};
 
-        #[test]
-        fn test_is_truthy_string_non_empty() {
-            let string_non_empty = LoxValue::String(String::from("hello"));
-            assert_eq!(string_non_empty.is_truthy(), true);
-        }
+    // ===== Truthiness Tests =====
 
-        // ===== Type Checker Tests =====
+    #[test]
+    fn test_is_truthy_bool_false() {
+        let bool_false = LoxValue::Bool(false);
+        assert!(!bool_false.is_truthy());
+    }
 
-        #[test]
-        fn test_as_number_success() {
-            let lox_number = LoxValue::Number(42.0);
-            assert_eq!(lox_number.as_number(), Some(42.0));
-        }
+    #[test]
+    fn test_is_truthy_bool_true() {
+        let bool_true = LoxValue::Bool(true);
+        assert!(bool_true.is_truthy());
+    }
 
-        #[test]
-        fn test_as_number_failure() {
-            let lox_string = LoxValue::String(String::from("not a number"));
-            assert_eq!(lox_string.as_number(), None);
+    #[test]
+    fn test_is_truthy_nil() {
+        let nil_false = LoxValue::Nil;
+        assert!(!nil_false.is_truthy());
+    }
 
-            let lox_bool = LoxValue::Bool(true);
-            assert_eq!(lox_bool.as_number(), None);
+    #[test]
+    fn test_is_truthy_number_zero() {
+        let number_zero = LoxValue::Number(0.0);
+        assert!(number_zero.is_truthy());
+    }
 
-            let lox_nil = LoxValue::Nil;
-            assert_eq!(lox_nil.as_number(), None);
-        }
+    #[test]
+    fn test_is_truthy_number_positive() {
+        let number_positive = LoxValue::Number(42.0);
+        assert!(number_positive.is_truthy());
+    }
 
-        #[test]
-        fn test_as_string_success() {
-            let lox_string = LoxValue::String(String::from("Hello World"));
-            assert_eq!(lox_string.as_string(), Some("Hello World"));
-        }
+    #[test]
+    fn test_is_truthy_number_negative() {
+        let number_negative = LoxValue::Number(-5.0);
+        assert!(number_negative.is_truthy());
+    }
 
-        #[test]
-        fn test_as_string_failure() {
-            let lox_number = LoxValue::Number(42.0);
-            assert_eq!(lox_number.as_string(), None);
+    #[test]
+    fn test_is_truthy_string_empty() {
+        let string_empty = LoxValue::String(String::from(""));
+        assert!(string_empty.is_truthy());
+    }
 
-            let lox_bool = LoxValue::Bool(true);
-            assert_eq!(lox_bool.as_string(), None);
+    #[test]
+    fn test_is_truthy_string_non_empty() {
+        let string_non_empty = LoxValue::String(String::from("hello"));
+        assert!(string_non_empty.is_truthy());
+    }
 
-            let lox_nil = LoxValue::Nil;
-            assert_eq!(lox_nil.as_string(), None);
-        }
+    #[test]
+    fn test_is_falsey_is_complement_of_is_truthy() {
+        // Given: one value of every variant
+        let callable = crate::interpreter::Interpreter::new()
+            .eval("assert")
+            .unwrap();
+        let values = [
+            LoxValue::Number(0.0),
+            LoxValue::Number(f64::NAN),
+            LoxValue::String(String::new()),
+            LoxValue::Bool(true),
+            LoxValue::Bool(false),
+            LoxValue::Nil,
+            callable,
+        ];
+
+        // Then
+        for value in &values {
+            assert_eq!(value.is_falsey(), !value.is_truthy(), "{:?}", value);
+        }
+        assert!(LoxValue::Nil.is_falsey());
+        assert!(LoxValue::Bool(false).is_falsey());
+        assert!(!LoxValue::Number(0.0).is_falsey());
+    }
 
-        #[test]
-        fn test_as_bool_success() {
-            let lox_bool_true = LoxValue::Bool(true);
-            assert_eq!(lox_bool_true.as_bool(), Some(true));
+    // ===== Type Checker Tests =====
 
-            let lox_bool_false = LoxValue::Bool(false);
-            assert_eq!(lox_bool_false.as_bool(), Some(false));
-        }
+    #[test]
+    fn test_as_number_success() {
+        let lox_number = LoxValue::Number(42.0);
+        assert_eq!(lox_number.as_number(), Some(42.0));
+    }
 
-        #[test]
-        fn test_as_bool_failure() {
-            let lox_number = LoxValue::Number(42.0);
-            assert_eq!(lox_number.as_bool(), None);
+    #[test]
+    fn test_as_number_failure() {
+        let lox_string = LoxValue::String(String::from("not a number"));
+        assert_eq!(lox_string.as_number(), None);
 
-            let lox_string = LoxValue::String(String::from("true"));
-            assert_eq!(lox_string.as_bool(), None);
+        let lox_bool = LoxValue::Bool(true);
+        assert_eq!(lox_bool.as_number(), None);
 
-            let lox_nil = LoxValue::Nil;
-            assert_eq!(lox_nil.as_bool(), None);
-        }
+        let lox_nil = LoxValue::Nil;
+        assert_eq!(lox_nil.as_number(), None);
+    }
 
-        #[test]
-        fn test_as_nil_success() {
-            let lox_nil = LoxValue::Nil;
-            assert_eq!(lox_nil.as_nil(), true);
-        }
+    #[test]
+    fn test_as_string_success() {
+        let lox_string = LoxValue::String(String::from("Hello World"));
+        assert_eq!(lox_string.as_string(), Some("Hello World"));
+    }
 
-        #[test]
-        fn test_as_nil_failure() {
-            let lox_number = LoxValue::Number(42.0);
-            assert_eq!(lox_number.as_nil(), false);
+    #[test]
+    fn test_as_string_failure() {
+        let lox_number = LoxValue::Number(42.0);
+        assert_eq!(lox_number.as_string(), None);
 
-            let lox_string = LoxValue::String(String::from("nil"));
-            assert_eq!(lox_string.as_nil(), false);
+        let lox_bool = LoxValue::Bool(true);
+        assert_eq!(lox_bool.as_string(), None);
 
-            let lox_bool = LoxValue::Bool(false);
-            assert_eq!(lox_bool.as_nil(), false);
-        }
+        let lox_nil = LoxValue::Nil;
+        assert_eq!(lox_nil.as_string(), None);
+    }
 
-        // ===== Display Tests =====
+    #[test]
+    fn test_as_bool_success() {
+        let lox_bool_true = LoxValue::Bool(true);
+        assert_eq!(lox_bool_true.as_bool(), Some(true));
 
-        #[test]
-        fn test_display_number_integer() {
-            assert_eq!(LoxValue::Number(42.0).to_string(), "42");
-            assert_eq!(LoxValue::Number(0.0).to_string(), "0");
-            assert_eq!(LoxValue::Number(-5.0).to_string(), "-5");
-        }
+        let lox_bool_false = LoxValue::Bool(false);
+        assert_eq!(lox_bool_false.as_bool(), Some(false));
+    }
 
-        #[test]
-        fn test_display_number_float() {
-            assert_eq!(LoxValue::Number(3.14).to_string(), "3.14");
-            assert_eq!(LoxValue::Number(0.5).to_string(), "0.5");
-            assert_eq!(LoxValue::Number(-2.718).to_string(), "-2.718");
-        }
+    #[test]
+    fn test_as_bool_failure() {
+        let lox_number = LoxValue::Number(42.0);
+        assert_eq!(lox_number.as_bool(), None);
+
+        let lox_string = LoxValue::String(String::from("true"));
+        assert_eq!(lox_string.as_bool(), None);
+
+        let lox_nil = LoxValue::Nil;
+        assert_eq!(lox_nil.as_bool(), None);
+    }
+
+    #[test]
+    fn test_as_nil_success() {
+        let lox_nil = LoxValue::Nil;
+        assert!(lox_nil.as_nil());
+    }
+
+    #[test]
+    fn test_as_nil_failure() {
+        let lox_number = LoxValue::Number(42.0);
+        assert!(!lox_number.as_nil());
+
+        let lox_string = LoxValue::String(String::from("nil"));
+        assert!(!lox_string.as_nil());
 
-        #[test]
-        fn test_display_string() {
-            assert_eq!(LoxValue::String(String::from("hello")).to_string(), "hello");
-            assert_eq!(LoxValue::String(String::from("")).to_string(), "");
+        let lox_bool = LoxValue::Bool(false);
+        assert!(!lox_bool.as_nil());
+    }
+
+    #[test]
+    fn test_as_callable_success() {
+        let mut interpreter = Interpreter::new();
+        let lox_function = interpreter.eval("fun (a, b) {}").unwrap();
+        assert_eq!(lox_function.as_callable().map(|f| f.arity()), Some(2));
+    }
+
+    #[test]
+    fn test_as_callable_failure() {
+        assert!(LoxValue::Nil.as_callable().is_none());
+        assert!(LoxValue::String(String::from("f")).as_callable().is_none());
+    }
+
+    #[test]
+    fn test_is_integer() {
+        assert!(LoxValue::Number(2.0).is_integer());
+        assert!(LoxValue::Number(-0.0).is_integer());
+        assert!(!LoxValue::Number(2.5).is_integer());
+        assert!(!LoxValue::Number(f64::INFINITY).is_integer());
+        assert!(!LoxValue::Number(f64::NAN).is_integer());
+        assert!(!LoxValue::String(String::from("2")).is_integer());
+    }
+
+    // ===== Display Tests =====
+
+    #[test]
+    fn test_display_number_integer() {
+        assert_eq!(LoxValue::Number(42.0).to_string(), "42");
+        assert_eq!(LoxValue::Number(0.0).to_string(), "0");
+        assert_eq!(LoxValue::Number(-5.0).to_string(), "-5");
+    }
+
+    #[test]
+    fn test_display_number_float() {
+        assert_eq!(LoxValue::Number(3.25).to_string(), "3.25");
+        assert_eq!(LoxValue::Number(0.5).to_string(), "0.5");
+        assert_eq!(LoxValue::Number(-2.75).to_string(), "-2.75");
+    }
+
+    #[test]
+    fn test_display_number_beyond_i64() {
+        // `as i64` would saturate to 9223372036854775807
+        assert_eq!(LoxValue::Number(1e19).to_string(), "10000000000000000000");
+        assert_eq!(LoxValue::Number(-1e19).to_string(), "-10000000000000000000");
+    }
+
+    #[test]
+    fn test_display_number_alternate_keeps_decimal() {
+        assert_eq!(format!("{:#}", LoxValue::Number(42.0)), "42.0");
+        assert_eq!(format!("{:#}", LoxValue::Number(-3.0)), "-3.0");
+        assert_eq!(format!("{:#}", LoxValue::Number(0.5)), "0.5");
+        assert_eq!(format!("{:#}", LoxValue::Nil), "nil");
+    }
+
+    #[test]
+    fn test_display_string() {
+        assert_eq!(LoxValue::String(String::from("hello")).to_string(), "hello");
+        assert_eq!(LoxValue::String(String::from("")).to_string(), "");
+        assert_eq!(
+            LoxValue::String(String::from("Hello World!")).to_string(),
+            "Hello World!"
+        );
+    }
+
+    #[test]
+    fn test_display_repl_quotes_and_escapes_strings() {
+        let value = LoxValue::String(String::from("say \"hi\"\nbye\\"));
+
+        assert_eq!(value.to_display_repl(), r#""say \"hi\"\nbye\\""#);
+        assert_eq!(value.to_string(), "say \"hi\"\nbye\\");
+    }
+
+    #[test]
+    fn test_display_repl_leaves_other_values_bare() {
+        assert_eq!(LoxValue::Number(42.0).to_display_repl(), "42");
+        assert_eq!(LoxValue::Bool(true).to_display_repl(), "true");
+        assert_eq!(LoxValue::Nil.to_display_repl(), "nil");
+    }
+
+    #[test]
+    fn test_display_bool() {
+        assert_eq!(LoxValue::Bool(true).to_string(), "true");
+        assert_eq!(LoxValue::Bool(false).to_string(), "false");
+    }
+
+    #[test]
+    fn test_display_nil() {
+        assert_eq!(LoxValue::Nil.to_string(), "nil");
+    }
+
+    // ===== From<LiteralValue> Tests =====
+
+    #[test]
+    fn test_from_literal_number() {
+        let literal = LiteralValue::Number(42.0);
+        let lox_value: LoxValue = literal.into();
+        assert_eq!(lox_value, LoxValue::Number(42.0));
+    }
+
+    #[test]
+    fn test_from_literal_string() {
+        let literal = LiteralValue::String(String::from("test"));
+        let lox_value: LoxValue = literal.into();
+        assert_eq!(lox_value, LoxValue::String(String::from("test")));
+    }
+
+    #[test]
+    fn test_from_literal_bool() {
+        let literal_true = LiteralValue::Bool(true);
+        let lox_value_true: LoxValue = literal_true.into();
+        assert_eq!(lox_value_true, LoxValue::Bool(true));
+
+        let literal_false = LiteralValue::Bool(false);
+        let lox_value_false: LoxValue = literal_false.into();
+        assert_eq!(lox_value_false, LoxValue::Bool(false));
+    }
+
+    #[test]
+    fn test_from_literal_nil() {
+        let literal = LiteralValue::Nil;
+        let lox_value: LoxValue = literal.into();
+        assert_eq!(lox_value, LoxValue::Nil);
+    }
+
+    // ===== FromStr Tests =====
+
+    #[test]
+    fn test_from_str_keywords() {
+        assert_eq!("nil".parse::<LoxValue>().unwrap(), LoxValue::Nil);
+        assert_eq!("true".parse::<LoxValue>().unwrap(), LoxValue::Bool(true));
+        assert_eq!("false".parse::<LoxValue>().unwrap(), LoxValue::Bool(false));
+    }
+
+    #[test]
+    fn test_from_str_numbers() {
+        assert_eq!("42".parse::<LoxValue>().unwrap(), LoxValue::Number(42.0));
+        assert_eq!(
+            "-2.75".parse::<LoxValue>().unwrap(),
+            LoxValue::Number(-2.75)
+        );
+    }
+
+    #[test]
+    fn test_from_str_everything_else_is_a_string() {
+        for text in ["hello", "", "True", " 1", "inf", "NaN", "1e999"] {
             assert_eq!(
-                LoxValue::String(String::from("Hello World!")).to_string(),
-                "Hello World!"
+                text.parse::<LoxValue>().unwrap(),
+                LoxValue::String(text.to_string()),
+                "parsing {:?}",
+                text
             );
         }
+    }
 
-        #[test]
-        fn test_display_bool() {
-            assert_eq!(LoxValue::Bool(true).to_string(), "true");
-            assert_eq!(LoxValue::Bool(false).to_string(), "false");
-        }
+    #[test]
+    fn test_from_str_round_trips_display() {
+        let values = [
+            LoxValue::Nil,
+            LoxValue::Bool(true),
+            LoxValue::Number(42.0),
+            LoxValue::Number(0.5),
+            LoxValue::String("hello world".to_string()),
+        ];
 
-        #[test]
-        fn test_display_nil() {
-            assert_eq!(LoxValue::Nil.to_string(), "nil");
+        for value in values {
+            assert_eq!(value.to_string().parse::<LoxValue>().unwrap(), value);
         }
+    }
 
-        // ===== From<LiteralValue> Tests =====
+    // ===== TryFrom Tests =====
 
-        #[test]
-        fn test_from_literal_number() {
-            let literal = LiteralValue::Number(42.0);
-            let lox_value: LoxValue = literal.into();
-            assert_eq!(lox_value, LoxValue::Number(42.0));
-        }
+    #[test]
+    fn test_try_from_number() {
+        assert_eq!(f64::try_from(LoxValue::Number(2.5)), Ok(2.5));
 
-        #[test]
-        fn test_from_literal_string() {
-            let literal = LiteralValue::String(String::from("test"));
-            let lox_value: LoxValue = literal.into();
-            assert_eq!(lox_value, LoxValue::String(String::from("test")));
-        }
+        let error = f64::try_from(LoxValue::String("2.5".to_string())).unwrap_err();
+        assert_eq!(error.to_string(), "Expected a number but got string.");
+    }
 
-        #[test]
-        fn test_from_literal_bool() {
-            let literal_true = LiteralValue::Bool(true);
-            let lox_value_true: LoxValue = literal_true.into();
-            assert_eq!(lox_value_true, LoxValue::Bool(true));
+    #[test]
+    fn test_try_from_string() {
+        assert_eq!(
+            String::try_from(LoxValue::String("hi".to_string())),
+            Ok("hi".to_string())
+        );
 
-            let literal_false = LiteralValue::Bool(false);
-            let lox_value_false: LoxValue = literal_false.into();
-            assert_eq!(lox_value_false, LoxValue::Bool(false));
-        }
+        let error = String::try_from(LoxValue::Nil).unwrap_err();
+        assert_eq!(error.expected, "string");
+        assert_eq!(error.found, "nil");
+    }
 
-        #[test]
-        fn test_from_literal_nil() {
-            let literal = LiteralValue::Nil;
-            let lox_value: LoxValue = literal.into();
-            assert_eq!(lox_value, LoxValue::Nil);
-        }
+    #[test]
+    fn test_try_from_bool() {
+        assert_eq!(bool::try_from(LoxValue::Bool(false)), Ok(false));
 
-        // ===== Edge Cases =====
+        // Truthiness does not count: only real booleans convert
+        let error = bool::try_from(LoxValue::Number(1.0)).unwrap_err();
+        assert_eq!(error.to_string(), "Expected a bool but got number.");
+    }
 
-        #[test]
-        fn test_number_special_values() {
-            // Infinity
-            let infinity = LoxValue::Number(f64::INFINITY);
-            assert_eq!(infinity.to_string(), "inf");
+    #[test]
+    fn test_try_from_works_with_question_mark() {
+        fn add(a: LoxValue, b: LoxValue) -> Result<f64, ConversionError> {
+            Ok(f64::try_from(a)? + f64::try_from(b)?)
+        }
 
-            // Negative Infinity
-            let neg_infinity = LoxValue::Number(f64::NEG_INFINITY);
-            assert_eq!(neg_infinity.to_string(), "-inf");
+        assert_eq!(add(LoxValue::Number(1.0), LoxValue::Number(2.0)), Ok(3.0));
+        assert!(add(LoxValue::Number(1.0), LoxValue::Bool(true)).is_err());
+    }
 
-            // NaN - is_finite()이 false이므로 그대로 출력
-            let nan = LoxValue::Number(f64::NAN);
-            assert_eq!(nan.to_string(), "NaN");
-        }
+    // ===== Arithmetic Tests =====
 
-        #[test]
-        fn test_string_special_characters() {
-            let newline = LoxValue::String(String::from("hello\nworld"));
-            assert_eq!(newline.to_string(), "hello\nworld");
+    fn number(n: f64) -> LoxValue {
+        LoxValue::Number(n)
+    }
 
-            let tab = LoxValue::String(String::from("hello\tworld"));
-            assert_eq!(tab.to_string(), "hello\tworld");
+    fn string(s: &str) -> LoxValue {
+        LoxValue::String(s.to_string())
+    }
 
-            let quote = LoxValue::String(String::from("say \"hello\""));
-            assert_eq!(quote.to_string(), "say \"hello\"");
-        }
+    #[test]
+    fn test_add_numbers_and_strings() {
+        assert_eq!((&number(1.0) + &number(2.0)).unwrap(), number(3.0));
+        assert_eq!((&string("ab") + &string("cd")).unwrap(), string("abcd"));
+    }
 
-        // ===== Clone and PartialEq Tests =====
+    #[test]
+    fn test_sub_mul_div_numbers() {
+        assert_eq!((&number(5.0) - &number(3.0)).unwrap(), number(2.0));
+        assert_eq!((&number(4.0) * &number(2.5)).unwrap(), number(10.0));
+        assert_eq!((&number(9.0) / &number(2.0)).unwrap(), number(4.5));
+    }
 
-        #[test]
-        fn test_clone() {
-            let original = LoxValue::Number(42.0);
-            let cloned = original.clone();
-            assert_eq!(original, cloned);
+    #[test]
+    fn test_div_by_zero_is_error() {
+        for (left, right) in [(1.0, 0.0), (0.0, 0.0), (-1.0, -0.0)] {
+            let error = (&number(left) / &number(right)).unwrap_err();
 
-            let string_original = LoxValue::String(String::from("hello"));
-            let string_cloned = string_original.clone();
-            assert_eq!(string_original, string_cloned);
+            assert_eq!(error.message, "Division by zero.");
         }
+    }
 
-        #[test]
-        fn test_equality() {
-            // Same type, same value
-            assert_eq!(LoxValue::Number(42.0), LoxValue::Number(42.0));
+    #[test]
+    fn test_add_type_errors() {
+        for (left, right) in [
+            (number(1.0), string("a")),
+            (string("a"), LoxValue::Nil),
+            (LoxValue::Bool(true), LoxValue::Bool(false)),
+        ] {
+            // When
+            let error = (&left + &right).unwrap_err();
+
+            // Then
             assert_eq!(
-                LoxValue::String(String::from("hello")),
-                LoxValue::String(String::from("hello"))
-            );
-            assert_eq!(LoxValue::Bool(true), LoxValue::Bool(true));
-            assert_eq!(LoxValue::Nil, LoxValue::Nil);
-
-            // Different values
-            assert_ne!(LoxValue::Number(42.0), LoxValue::Number(43.0));
-            assert_ne!(
-                LoxValue::String(String::from("hello")),
-                LoxValue::String(String::from("world"))
+                error.message,
+                "Operands must be two numbers or two strings."
             );
-            assert_ne!(LoxValue::Bool(true), LoxValue::Bool(false));
-
-            // Different types
-            assert_ne!(LoxValue::Number(42.0), LoxValue::String(String::from("42")));
-            assert_ne!(LoxValue::Bool(true), LoxValue::Number(1.0));
-            assert_ne!(LoxValue::Nil, LoxValue::Bool(false));
         }
     }
+
+    #[test]
+    fn test_number_only_operators_reject_strings() {
+        let (left, right) = (string("a"), string("b"));
+
+        assert_eq!(
+            (&left - &right).unwrap_err().message,
+            "Operands must be numbers."
+        );
+        assert_eq!(
+            (&left * &right).unwrap_err().message,
+            "Operands must be numbers."
+        );
+        assert_eq!(
+            (&left / &number(1.0)).unwrap_err().message,
+            "Operands must be numbers."
+        );
+    }
+
+    // ===== Edge Cases =====
+
+    #[test]
+    fn test_number_special_values() {
+        // Infinity
+        let infinity = LoxValue::Number(f64::INFINITY);
+        assert_eq!(infinity.to_string(), "inf");
+
+        // Negative Infinity
+        let neg_infinity = LoxValue::Number(f64::NEG_INFINITY);
+        assert_eq!(neg_infinity.to_string(), "-inf");
+
+        // NaN - is_finite()이 false이므로 그대로 출력
+        let nan = LoxValue::Number(f64::NAN);
+        assert_eq!(nan.to_string(), "NaN");
+    }
+
+    #[test]
+    fn test_string_special_characters() {
+        let newline = LoxValue::String(String::from("hello\nworld"));
+        assert_eq!(newline.to_string(), "hello\nworld");
+
+        let tab = LoxValue::String(String::from("hello\tworld"));
+        assert_eq!(tab.to_string(), "hello\tworld");
+
+        let quote = LoxValue::String(String::from("say \"hello\""));
+        assert_eq!(quote.to_string(), "say \"hello\"");
+    }
+
+    // ===== Clone and PartialEq Tests =====
+
+    #[test]
+    fn test_clone() {
+        let original = LoxValue::Number(42.0);
+        let cloned = original.clone();
+        assert_eq!(original, cloned);
+
+        let string_original = LoxValue::String(String::from("hello"));
+        let string_cloned = string_original.clone();
+        assert_eq!(string_original, string_cloned);
+    }
+
+    #[test]
+    fn test_equality() {
+        // Same type, same value
+        assert_eq!(LoxValue::Number(42.0), LoxValue::Number(42.0));
+        assert_eq!(
+            LoxValue::String(String::from("hello")),
+            LoxValue::String(String::from("hello"))
+        );
+        assert_eq!(LoxValue::Bool(true), LoxValue::Bool(true));
+        assert_eq!(LoxValue::Nil, LoxValue::Nil);
+
+        // Different values
+        assert_ne!(LoxValue::Number(42.0), LoxValue::Number(43.0));
+        assert_ne!(
+            LoxValue::String(String::from("hello")),
+            LoxValue::String(String::from("world"))
+        );
+        assert_ne!(LoxValue::Bool(true), LoxValue::Bool(false));
+
+        // Different types
+        assert_ne!(LoxValue::Number(42.0), LoxValue::String(String::from("42")));
+        assert_ne!(LoxValue::Bool(true), LoxValue::Number(1.0));
+        assert_ne!(LoxValue::Nil, LoxValue::Bool(false));
+    }
 }
@@ -1,45 +1,352 @@
-use rlox::token::{Token, TokenType};
+use rlox::ast_printer::AstPrinter;
+use rlox::interpreter::{Interpreter, with_lox_stack};
+use rlox::lox::Lox;
+use rlox::lox_error::{self, LoxError};
+use rlox::parser::{ParseError, Parser, ReplUnit};
+use rlox::repl::LineBuffer;
+use rlox::resolver::Resolver;
+use rlox::scanner::Scanner;
+use rlox::stmt::{self, Stmt};
+use rlox::token::format_tokens;
 use std::env;
+use std::fs;
 use std::io;
-use std::io::BufRead;
-use std::process;
+use std::io::IsTerminal;
+use std::io::Write;
 use std::process::exit;
+use std::time::{Duration, Instant};
 
+// Scripts may recurse deeper than the default main thread stack allows
 fn main() {
-    let args: Vec<String> = env::args().collect();
+    with_lox_stack(start);
+}
+
+fn start() {
+    let args: Vec<String> = env::args().skip(1).collect();
+    let (flags, paths): (Vec<&String>, Vec<&String>) =
+        args.iter().partition(|arg| arg.starts_with("--"));
 
-    match args.len() {
-        n if n > 1 => {
-            println!("Usage: rlox [script]");
-            exit(64);
+    let mut no_color = false;
+    let mut check = false;
+    let mut ast = false;
+    let mut time = false;
+    let mut tokens = false;
+    for flag in flags {
+        match flag.as_str() {
+            "--no-color" => no_color = true,
+            "--check" => check = true,
+            "--ast" => ast = true,
+            "--time" => time = true,
+            "--tokens" => tokens = true,
+            _ => usage(),
         }
-        // Todo : runFile(args[1])
-        2 => println!("run_file"),
-        // Todo : runPrompt()
-        _ => run_prompt(),
     }
+    // Diagnostics go to stderr, so color depends on whether it is a terminal
+    let mut lox = Lox::new(!no_color && io::stderr().is_terminal());
+
+    match paths.as_slice() {
+        [] if !check && !ast && !time && !tokens => run_prompt(&mut lox),
+        [path] if check => check_file(&mut lox, path),
+        [path] if ast => print_ast(&mut lox, path),
+        [path] if tokens => print_tokens(&mut lox, path),
+        [path] => run_file(&mut lox, path, time),
+        _ => usage(),
+    }
+}
+
+fn usage() -> ! {
+    println!("Usage: rlox [--no-color] [--check] [--ast] [--tokens] [--time] [script]");
+    exit(64);
 }
 
-fn run_prompt() {
-    let stdin = io::stdin();
-    let reader = stdin.lock();
+fn run_prompt(lox: &mut Lox) {
+    let mut interpreter = Interpreter::new();
+    interpreter.repl = true;
+    let mut lines = LineBuffer::new();
 
-    for line in reader.lines() {
-        if let Ok(content) = line {
+    print!("> ");
+    io::stdout().flush().ok();
+    // stdin is locked per line only, so `input()` can read it while code runs
+    while let Some(content) = read_line() {
+        if lines.is_empty() && content.trim() == ":env" {
+            print_environment(&interpreter);
             print!("> ");
-            run(content);
+            io::stdout().flush().ok();
+            continue;
+        }
+        if lines.is_empty() && content.trim() == ":paste" {
+            println!("(paste mode; end with a blank line)");
+            lines.start_paste();
+            print!("... ");
+            io::stdout().flush().ok();
+            continue;
+        }
+
+        // Keep reading while the input is unfinished, e.g. inside `{ ... }`
+        match lines.push_line(&content) {
+            Some(source) => {
+                run(lox, &mut interpreter, source, &mut Timings::default());
+                // An error in one line must not end the session
+                lox.had_error = false;
+                print!("> ");
+            }
+            None => print!("... "),
+        }
+        io::stdout().flush().ok();
+    }
+
+    // A paste cut short by the end of input still runs
+    if let Some(source) = lines.finish() {
+        run(lox, &mut interpreter, source, &mut Timings::default());
+    }
+}
+
+// Next line of stdin without its line ending, None at end of input
+fn read_line() -> Option<String> {
+    let mut line = String::new();
+    match io::stdin().read_line(&mut line) {
+        Ok(0) | Err(_) => None,
+        Ok(_) => Some(line.trim_end_matches(['\n', '\r']).to_string()),
+    }
+}
+
+// `:env` meta-command, lists every variable visible at the top level
+fn print_environment(interpreter: &Interpreter) {
+    for (name, value) in interpreter.environment().borrow().dump_all() {
+        println!("{} = {}", name, value);
+    }
+}
+
+// `time`: report how long each phase took on stderr, away from the output
+fn run_file(lox: &mut Lox, path: &str, time: bool) {
+    let source = read_source(path);
+
+    let mut interpreter = Interpreter::new();
+    let mut timings = Timings::default();
+    run(lox, &mut interpreter, source, &mut timings);
+    if time {
+        timings.report();
+    }
+    if lox.had_error {
+        exit(65);
+    }
+}
+
+// `--check`: report every scan, parse and resolve error without running anything
+fn check_file(lox: &mut Lox, path: &str) {
+    let mut scanner = Scanner::new(read_source(path));
+    let tokens = scanner.scan_tokens();
+    let mut errors: Vec<LoxError> = scanner
+        .errors()
+        .iter()
+        .cloned()
+        .map(LoxError::from)
+        .collect();
+
+    // Parse even after scan errors, so both kinds surface in one pass
+    let mut parser = Parser::new(tokens);
+    let result = parser.parse_program();
+    print_warnings(lox, &parser);
+    match result {
+        Ok(statements) => {
+            let mut resolver = Resolver::new();
+            resolver.resolve(&statements);
+            errors.extend(resolver.errors().iter().cloned().map(LoxError::from));
+        }
+        Err(parse_errors) => errors.extend(parse_errors.into_iter().map(LoxError::from)),
+    }
+
+    report_errors(lox, errors);
+
+    if lox.had_error {
+        exit(65);
+    }
+}
+
+// `--ast`: print the syntax tree of each top-level statement, one per line
+fn print_ast(lox: &mut Lox, path: &str) {
+    let mut scanner = Scanner::new(read_source(path));
+    let tokens = scanner.scan_tokens();
+    for e in scanner.errors() {
+        error(lox, e.line, &e.message);
+    }
+    if scanner.had_error() {
+        exit(65);
+    }
+
+    match Parser::new(tokens).parse_program() {
+        Ok(statements) => {
+            let printer = AstPrinter::new();
+            for statement in &statements {
+                println!("{}", printer.print_stmt(statement));
+            }
+        }
+        Err(errors) => {
+            report_parse_errors(lox, errors);
+            exit(65);
+        }
+    }
+}
+
+// `--tokens`: print the scanner's output as a table
+fn print_tokens(lox: &mut Lox, path: &str) {
+    let mut scanner = Scanner::new(read_source(path));
+    let tokens = scanner.scan_tokens();
+    for e in scanner.errors() {
+        error(lox, e.line, &e.message);
+    }
+    if scanner.had_error() {
+        exit(65);
+    }
+
+    print!("{}", format_tokens(&tokens));
+}
+
+fn read_source(path: &str) -> String {
+    match fs::read_to_string(path) {
+        Ok(source) => source,
+        Err(e) => {
+            eprintln!("Could not read file '{}': {}", path, e);
+            exit(66);
+        }
+    }
+}
+
+// Wall-clock time spent in each phase of `run`; phases never reached stay zero
+#[derive(Default)]
+struct Timings {
+    scan: Duration,
+    parse: Duration,
+    interpret: Duration,
+}
+
+impl Timings {
+    fn report(&self) {
+        eprintln!("scan:      {:?}", self.scan);
+        eprintln!("parse:     {:?}", self.parse);
+        eprintln!("interpret: {:?}", self.interpret);
+    }
+}
+
+// Sets `lox.had_error` when the source could not be parsed or executed
+fn run(lox: &mut Lox, interpreter: &mut Interpreter, source: String, timings: &mut Timings) {
+    let start = Instant::now();
+    let mut scanner = Scanner::new(source);
+    let tokens = scanner.scan_tokens();
+    timings.scan = start.elapsed();
+    let mut errors: Vec<LoxError> = scanner
+        .errors()
+        .iter()
+        .cloned()
+        .map(LoxError::from)
+        .collect();
+
+    // The REPL also takes a single expression without a trailing `;`
+    if interpreter.repl && errors.is_empty() {
+        let start = Instant::now();
+        let mut parser = Parser::new(tokens.clone());
+        if let Ok(unit) = parser.parse_repl() {
+            timings.parse = start.elapsed();
+            print_warnings(lox, &parser);
+            let statement = match unit {
+                ReplUnit::Expr(expression) => Stmt::Expression(stmt::Expression { expression }),
+                ReplUnit::Stmt(statement) => statement,
+            };
+            let start = Instant::now();
+            execute(lox, interpreter, &[statement]);
+            timings.interpret = start.elapsed();
+            return;
+        }
+    }
+
+    // Parse even after scan errors, so both kinds are reported together
+    let start = Instant::now();
+    let mut parser = Parser::new(tokens);
+    let result = parser.parse_program();
+    timings.parse = start.elapsed();
+    print_warnings(lox, &parser);
+    let statements = match result {
+        Ok(statements) => statements,
+        Err(parse_errors) => {
+            errors.extend(parse_errors.into_iter().map(LoxError::from));
+            Vec::new()
         }
+    };
+    if !errors.is_empty() {
+        report_errors(lox, errors);
+        return;
+    }
+
+    let start = Instant::now();
+    execute(lox, interpreter, &statements);
+    timings.interpret = start.elapsed();
+}
+
+fn execute(lox: &mut Lox, interpreter: &mut Interpreter, statements: &[Stmt]) {
+    // Only for its warnings and errors; `interpret` resolves the statements itself
+    let mut resolver = Resolver::new();
+    resolver.resolve(statements);
+    for w in resolver.warnings() {
+        warning(lox, w.token.line, &w.message);
+    }
+    if !resolver.errors().is_empty() {
+        report_parse_errors(lox, resolver.errors().to_vec());
+        return;
+    }
+
+    if let Err(e) = interpreter.interpret(statements) {
+        let message = lox.format_error(
+            e.token.line,
+            "Runtime Error",
+            &at(&e.token.lexeme),
+            &e.message,
+        );
+        eprintln!("{}{}", message, e.format_trace());
+        lox.had_error = true;
+    }
+}
+
+// A bad character can also break the statement around it; list both in source order
+fn report_errors(lox: &mut Lox, mut errors: Vec<LoxError>) {
+    lox_error::sort_and_dedup(&mut errors);
+    for e in errors {
+        match e {
+            LoxError::Parse(e) => report(lox, e.token.line, &at(&e.token.lexeme), &e.message),
+            e => error(lox, e.line(), e.message()),
+        }
+    }
+}
+
+fn report_parse_errors(lox: &mut Lox, errors: Vec<ParseError>) {
+    for e in errors {
+        report(lox, e.token.line, &at(&e.token.lexeme), &e.message);
+    }
+}
+
+fn print_warnings(lox: &Lox, parser: &Parser) {
+    for w in parser.warnings() {
+        warning(lox, w.token.line, &w.message);
     }
 }
 
-fn fun_file(path: String) {}
+// Location of an error at the given lexeme, empty at end of input
+fn at(lexeme: &str) -> String {
+    if lexeme.is_empty() {
+        " at end".to_string()
+    } else {
+        format!(" at '{}'", lexeme)
+    }
+}
 
-fn run(source: String) {
-    let mut tokens: Vec<Token>;
+fn error(lox: &mut Lox, line: usize, message: &str) {
+    report(lox, line, "", message);
 }
 
-fn error(line: usize, message: String) {}
+fn warning(lox: &Lox, line: usize, message: &str) {
+    eprintln!("{}", lox.format_error(line, "Warning", "", message));
+}
 
-fn report(line: usize, location: String, message: String) {
-    eprintln!("[line {} ] Error {} : {} ", line, location, message);
+fn report(lox: &mut Lox, line: usize, location: &str, message: &str) {
+    eprintln!("{}", lox.format_error(line, "Error", location, message));
+    lox.had_error = true;
 }
@@ -0,0 +1,1297 @@
+use std::rc::Rc;
+use std::time::Duration;
+
+use crate::environment::Environment;
+use crate::interpreter::Interpreter;
+use crate::lox_callable::{NativeFn, NativeFunction};
+use crate::lox_value::LoxValue;
+use crate::rng::Rng;
+use crate::runtime_error::RuntimeError;
+use crate::token::Token;
+
+/// Register every native function in the given (global) environment
+pub fn define_natives(globals: &mut Environment) {
+    define(globals, "assert", 1, assert);
+    define(globals, "assert_eq", 2, assert_eq);
+    define(globals, "assert_throws", 1, assert_throws);
+    define(globals, "repeat", 2, repeat);
+    define_range(globals, "input", 0, Some(1), input);
+    define_range(globals, "format", 1, None, format);
+    define(globals, "contains", 2, contains);
+    define(globals, "index_of", 2, index_of);
+    define(globals, "ord", 1, ord);
+    define(globals, "chr", 1, chr);
+    define(globals, "parse_int", 2, parse_int);
+    define(globals, "parse_float", 1, parse_float);
+    define(globals, "floor", 1, floor);
+    define(globals, "ceil", 1, ceil);
+    define(globals, "round", 1, round);
+    define(globals, "abs", 1, abs);
+    define(globals, "sqrt", 1, sqrt);
+    define_range(globals, "min", 1, None, min);
+    define_range(globals, "max", 1, None, max);
+    define(globals, "pow", 2, pow);
+    define(globals, "is_int", 1, is_int);
+    define(globals, "seed", 1, seed);
+    define(globals, "random", 0, random);
+    define(globals, "random_int", 2, random_int);
+    define(globals, "arity", 1, arity);
+    define(globals, "clock", 0, clock);
+    define(globals, "clock_millis", 0, clock_millis);
+    define(globals, "sleep", 1, sleep);
+    define(globals, "println", 1, println);
+}
+
+fn define(globals: &mut Environment, name: &'static str, arity: usize, function: NativeFn) {
+    define_range(globals, name, arity, Some(arity), function);
+}
+
+// Native taking between `arity` and `max_arity` arguments, None for no limit
+fn define_range(
+    globals: &mut Environment,
+    name: &'static str,
+    arity: usize,
+    max_arity: Option<usize>,
+    function: NativeFn,
+) {
+    let native = NativeFunction {
+        name,
+        arity,
+        max_arity,
+        function,
+    };
+    globals.define(name.to_string(), LoxValue::Callable(Rc::new(native)));
+}
+
+/// `assert(cond)` - error unless `cond` is truthy
+fn assert(
+    _interpreter: &mut Interpreter,
+    paren: &Token,
+    arguments: &[LoxValue],
+) -> Result<LoxValue, RuntimeError> {
+    if arguments[0].is_truthy() {
+        Ok(LoxValue::Nil)
+    } else {
+        Err(RuntimeError::new(paren, "Assertion failed."))
+    }
+}
+
+/// `assert_eq(a, b)` - error unless `a == b`
+fn assert_eq(
+    interpreter: &mut Interpreter,
+    paren: &Token,
+    arguments: &[LoxValue],
+) -> Result<LoxValue, RuntimeError> {
+    let (left, right) = (&arguments[0], &arguments[1]);
+    if interpreter.is_equal(left, right) {
+        Ok(LoxValue::Nil)
+    } else {
+        Err(RuntimeError::new(
+            paren,
+            &format!("Assertion failed: {} != {}.", left, right),
+        ))
+    }
+}
+
+/// `assert_throws(f)` - call `f` with no arguments and error unless that
+/// raises a runtime error, which is caught and discarded
+fn assert_throws(
+    interpreter: &mut Interpreter,
+    paren: &Token,
+    arguments: &[LoxValue],
+) -> Result<LoxValue, RuntimeError> {
+    let Some(function) = arguments[0].as_callable().filter(|f| f.arity() == 0) else {
+        return Err(RuntimeError::new(
+            paren,
+            "Argument to assert_throws must be a function taking no arguments.",
+        ));
+    };
+
+    match function.call(interpreter, paren, Vec::new()) {
+        Err(_) => Ok(LoxValue::Nil),
+        Ok(_) => Err(RuntimeError::new(
+            paren,
+            "Expected a runtime error but none occurred.",
+        )),
+    }
+}
+
+// Longest string `repeat` builds, in bytes
+const MAX_REPEAT_LEN: usize = 1 << 28;
+
+/// `repeat(s, n)` - `s` concatenated `n` times, `n` a non-negative integer.
+/// Errors if the result would exceed 256 MiB.
+fn repeat(
+    _interpreter: &mut Interpreter,
+    paren: &Token,
+    arguments: &[LoxValue],
+) -> Result<LoxValue, RuntimeError> {
+    let Some(s) = arguments[0].as_string() else {
+        return Err(RuntimeError::new(
+            paren,
+            "First argument to repeat must be a string.",
+        ));
+    };
+
+    let count = match arguments[1].as_number() {
+        Some(n) if n >= 0.0 && n.fract() == 0.0 => n as usize,
+        _ => {
+            return Err(RuntimeError::new(
+                paren,
+                "Repeat count must be a non-negative integer.",
+            ));
+        }
+    };
+    match s.len().checked_mul(count) {
+        Some(len) if len <= MAX_REPEAT_LEN => Ok(LoxValue::String(s.repeat(count))),
+        _ => Err(RuntimeError::new(paren, "Repeated string is too long.")),
+    }
+}
+
+/// `input()` / `input(prompt)` - next line of input without its newline,
+/// nil at end of input. The prompt is written to the output first.
+fn input(
+    interpreter: &mut Interpreter,
+    paren: &Token,
+    arguments: &[LoxValue],
+) -> Result<LoxValue, RuntimeError> {
+    if let Some(prompt) = arguments.first() {
+        interpreter.write_prompt(&prompt.to_string());
+    }
+
+    match interpreter.read_line() {
+        Ok(Some(line)) => Ok(LoxValue::String(line)),
+        Ok(None) => Ok(LoxValue::Nil),
+        Err(error) => Err(RuntimeError::new(
+            paren,
+            &format!("Could not read input: {}.", error),
+        )),
+    }
+}
+
+/// `format(template, args...)` - `template` with each `{}` replaced by the
+/// next argument; `{{` and `}}` stand for literal braces
+fn format(
+    _interpreter: &mut Interpreter,
+    paren: &Token,
+    arguments: &[LoxValue],
+) -> Result<LoxValue, RuntimeError> {
+    let Some(template) = arguments[0].as_string() else {
+        return Err(RuntimeError::new(
+            paren,
+            "First argument to format must be a string.",
+        ));
+    };
+    let values = &arguments[1..];
+
+    let mut result = String::new();
+    let mut placeholders = 0;
+    let mut chars = template.chars().peekable();
+    while let Some(c) = chars.next() {
+        match (c, chars.peek()) {
+            ('{', Some('{')) | ('}', Some('}')) => {
+                chars.next();
+                result.push(c);
+            }
+            ('{', Some('}')) => {
+                chars.next();
+                if let Some(value) = values.get(placeholders) {
+                    result.push_str(&value.to_string());
+                }
+                placeholders += 1;
+            }
+            ('{', _) => {
+                return Err(RuntimeError::new(
+                    paren,
+                    "Unmatched '{' in format string; use '{{' for a literal brace.",
+                ));
+            }
+            _ => result.push(c),
+        }
+    }
+
+    if placeholders != values.len() {
+        return Err(RuntimeError::new(
+            paren,
+            &format!(
+                "Format string has {} placeholders but got {} arguments.",
+                placeholders,
+                values.len()
+            ),
+        ));
+    }
+    Ok(LoxValue::String(result))
+}
+
+/// `contains(haystack, needle)` - true if `needle` occurs in `haystack`
+fn contains(
+    _interpreter: &mut Interpreter,
+    paren: &Token,
+    arguments: &[LoxValue],
+) -> Result<LoxValue, RuntimeError> {
+    let (haystack, needle) = string_pair("contains", paren, arguments)?;
+    Ok(LoxValue::Bool(haystack.contains(needle)))
+}
+
+/// `index_of(haystack, needle)` - character index of the first `needle` in
+/// `haystack`, -1 if absent
+fn index_of(
+    _interpreter: &mut Interpreter,
+    paren: &Token,
+    arguments: &[LoxValue],
+) -> Result<LoxValue, RuntimeError> {
+    let (haystack, needle) = string_pair("index_of", paren, arguments)?;
+    let index = match haystack.find(needle) {
+        // `find` gives a byte offset; count the characters before it
+        Some(byte) => haystack[..byte].chars().count() as f64,
+        None => -1.0,
+    };
+    Ok(LoxValue::Number(index))
+}
+
+/// `ord(c)` - Unicode code point of the one-character string `c`
+fn ord(
+    _interpreter: &mut Interpreter,
+    paren: &Token,
+    arguments: &[LoxValue],
+) -> Result<LoxValue, RuntimeError> {
+    let mut chars = arguments[0].as_string().unwrap_or_default().chars();
+    match (chars.next(), chars.next()) {
+        (Some(c), None) => Ok(LoxValue::Number(c as u32 as f64)),
+        _ => Err(RuntimeError::new(
+            paren,
+            "Argument to ord must be a one-character string.",
+        )),
+    }
+}
+
+/// `chr(n)` - one-character string of the Unicode code point `n`
+fn chr(
+    _interpreter: &mut Interpreter,
+    paren: &Token,
+    arguments: &[LoxValue],
+) -> Result<LoxValue, RuntimeError> {
+    let n = number_argument("chr", paren, &arguments[0])?;
+    // `as u32` saturates, so only a whole number in range survives the round trip
+    match char::from_u32(n as u32) {
+        Some(c) if c as u32 as f64 == n => Ok(LoxValue::String(c.to_string())),
+        _ => Err(RuntimeError::new(
+            paren,
+            "Argument to chr must be a valid code point.",
+        )),
+    }
+}
+
+/// `parse_int(s, radix)` - integer written in `s` with digits of base
+/// `radix` (2 to 36), optionally signed: `parse_int("ff", 16)` is 255
+fn parse_int(
+    _interpreter: &mut Interpreter,
+    paren: &Token,
+    arguments: &[LoxValue],
+) -> Result<LoxValue, RuntimeError> {
+    let Some(s) = arguments[0].as_string() else {
+        return Err(RuntimeError::new(
+            paren,
+            "First argument to parse_int must be a string.",
+        ));
+    };
+    let radix = match arguments[1].as_number() {
+        Some(n) if (2.0..=36.0).contains(&n) && n.fract() == 0.0 => n as u32,
+        _ => {
+            return Err(RuntimeError::new(
+                paren,
+                "Radix must be an integer from 2 to 36.",
+            ));
+        }
+    };
+
+    let (sign, digits) = match s.strip_prefix('-') {
+        Some(rest) => (-1.0, rest),
+        None => (1.0, s.strip_prefix('+').unwrap_or(s)),
+    };
+    // Accumulate in f64 so long inputs lose precision rather than overflow
+    let value = digits.chars().try_fold(None, |value: Option<f64>, c| {
+        let digit = c.to_digit(radix)?;
+        Some(Some(value.unwrap_or(0.0) * radix as f64 + digit as f64))
+    });
+    match value {
+        Some(Some(value)) => Ok(LoxValue::Number(sign * value)),
+        _ => Err(RuntimeError::new(
+            paren,
+            &format!("Invalid digits for radix {}: '{}'.", radix, s),
+        )),
+    }
+}
+
+/// `parse_float(s)` - decimal number written in `s`, such as `"2.5"` or
+/// `"-1e3"`
+fn parse_float(
+    _interpreter: &mut Interpreter,
+    paren: &Token,
+    arguments: &[LoxValue],
+) -> Result<LoxValue, RuntimeError> {
+    let Some(s) = arguments[0].as_string() else {
+        return Err(RuntimeError::new(
+            paren,
+            "Argument to parse_float must be a string.",
+        ));
+    };
+    match s.parse::<f64>() {
+        Ok(n) => Ok(LoxValue::Number(n)),
+        Err(_) => Err(RuntimeError::new(
+            paren,
+            &format!("Invalid number: '{}'.", s),
+        )),
+    }
+}
+
+// The two arguments of a native taking (haystack, needle) strings
+fn string_pair<'a>(
+    name: &str,
+    paren: &Token,
+    arguments: &'a [LoxValue],
+) -> Result<(&'a str, &'a str), RuntimeError> {
+    match (arguments[0].as_string(), arguments[1].as_string()) {
+        (Some(haystack), Some(needle)) => Ok((haystack, needle)),
+        _ => Err(RuntimeError::new(
+            paren,
+            &format!("Arguments to {} must be strings.", name),
+        )),
+    }
+}
+
+/// `floor(n)` - largest integer not above `n`
+fn floor(
+    _interpreter: &mut Interpreter,
+    paren: &Token,
+    arguments: &[LoxValue],
+) -> Result<LoxValue, RuntimeError> {
+    let n = number_argument("floor", paren, &arguments[0])?;
+    Ok(LoxValue::Number(n.floor()))
+}
+
+/// `ceil(n)` - smallest integer not below `n`
+fn ceil(
+    _interpreter: &mut Interpreter,
+    paren: &Token,
+    arguments: &[LoxValue],
+) -> Result<LoxValue, RuntimeError> {
+    let n = number_argument("ceil", paren, &arguments[0])?;
+    Ok(LoxValue::Number(n.ceil()))
+}
+
+/// `round(n)` - nearest integer, halves go to the even neighbour
+/// (`round(2.5)` is 2, `round(3.5)` is 4)
+fn round(
+    _interpreter: &mut Interpreter,
+    paren: &Token,
+    arguments: &[LoxValue],
+) -> Result<LoxValue, RuntimeError> {
+    let n = number_argument("round", paren, &arguments[0])?;
+    Ok(LoxValue::Number(n.round_ties_even()))
+}
+
+/// `abs(n)` - absolute value
+fn abs(
+    _interpreter: &mut Interpreter,
+    paren: &Token,
+    arguments: &[LoxValue],
+) -> Result<LoxValue, RuntimeError> {
+    let n = number_argument("abs", paren, &arguments[0])?;
+    Ok(LoxValue::Number(n.abs()))
+}
+
+/// `sqrt(n)` - square root, `n` must not be negative
+fn sqrt(
+    _interpreter: &mut Interpreter,
+    paren: &Token,
+    arguments: &[LoxValue],
+) -> Result<LoxValue, RuntimeError> {
+    let n = number_argument("sqrt", paren, &arguments[0])?;
+    if n < 0.0 {
+        return Err(RuntimeError::new(
+            paren,
+            "Cannot take the square root of a negative number.",
+        ));
+    }
+    Ok(LoxValue::Number(n.sqrt()))
+}
+
+/// `min(a, b, ...)` - smallest of one or more numbers
+fn min(
+    _interpreter: &mut Interpreter,
+    paren: &Token,
+    arguments: &[LoxValue],
+) -> Result<LoxValue, RuntimeError> {
+    let numbers = number_arguments("min", paren, arguments)?;
+    Ok(LoxValue::Number(
+        numbers.into_iter().fold(f64::INFINITY, f64::min),
+    ))
+}
+
+/// `max(a, b, ...)` - largest of one or more numbers
+fn max(
+    _interpreter: &mut Interpreter,
+    paren: &Token,
+    arguments: &[LoxValue],
+) -> Result<LoxValue, RuntimeError> {
+    let numbers = number_arguments("max", paren, arguments)?;
+    Ok(LoxValue::Number(
+        numbers.into_iter().fold(f64::NEG_INFINITY, f64::max),
+    ))
+}
+
+/// `pow(base, exp)` - `base` raised to the power `exp`
+fn pow(
+    _interpreter: &mut Interpreter,
+    paren: &Token,
+    arguments: &[LoxValue],
+) -> Result<LoxValue, RuntimeError> {
+    let base = number_argument("pow", paren, &arguments[0])?;
+    let exponent = number_argument("pow", paren, &arguments[1])?;
+    Ok(LoxValue::Number(base.powf(exponent)))
+}
+
+/// `is_int(n)` - true if `n` is finite with no fractional part
+fn is_int(
+    _interpreter: &mut Interpreter,
+    paren: &Token,
+    arguments: &[LoxValue],
+) -> Result<LoxValue, RuntimeError> {
+    number_argument("is_int", paren, &arguments[0])?;
+    Ok(LoxValue::Bool(arguments[0].is_integer()))
+}
+
+/// `seed(n)` - restart `random` and `random_int` at a reproducible point
+fn seed(
+    interpreter: &mut Interpreter,
+    paren: &Token,
+    arguments: &[LoxValue],
+) -> Result<LoxValue, RuntimeError> {
+    let n = number_argument("seed", paren, &arguments[0])?;
+    interpreter.rng = Rng::new(n.to_bits());
+    Ok(LoxValue::Nil)
+}
+
+/// `random()` - pseudo-random number in [0, 1)
+fn random(
+    interpreter: &mut Interpreter,
+    _paren: &Token,
+    _arguments: &[LoxValue],
+) -> Result<LoxValue, RuntimeError> {
+    Ok(LoxValue::Number(interpreter.rng.next_f64()))
+}
+
+/// `random_int(lo, hi)` - pseudo-random integer with lo <= n <= hi
+fn random_int(
+    interpreter: &mut Interpreter,
+    paren: &Token,
+    arguments: &[LoxValue],
+) -> Result<LoxValue, RuntimeError> {
+    let lo = number_argument("random_int", paren, &arguments[0])?;
+    let hi = number_argument("random_int", paren, &arguments[1])?;
+    if lo.fract() != 0.0 || hi.fract() != 0.0 || lo > hi {
+        return Err(RuntimeError::new(
+            paren,
+            "Arguments to random_int must be integers with lo <= hi.",
+        ));
+    }
+
+    let span = hi - lo + 1.0;
+    let offset = (interpreter.rng.next_f64() * span).floor();
+    Ok(LoxValue::Number(lo + offset))
+}
+
+/// `arity(f)` - fewest arguments the function or class `f` accepts
+fn arity(
+    _interpreter: &mut Interpreter,
+    paren: &Token,
+    arguments: &[LoxValue],
+) -> Result<LoxValue, RuntimeError> {
+    match arguments[0].as_callable() {
+        Some(callable) => Ok(LoxValue::Number(callable.arity() as f64)),
+        None => Err(RuntimeError::new(
+            paren,
+            "Argument to arity must be a function or class.",
+        )),
+    }
+}
+
+/// `clock()` - seconds since the Unix epoch, with a fractional part
+fn clock(
+    interpreter: &mut Interpreter,
+    _paren: &Token,
+    _arguments: &[LoxValue],
+) -> Result<LoxValue, RuntimeError> {
+    Ok(LoxValue::Number(interpreter.clock.now().as_secs_f64()))
+}
+
+/// `clock_millis()` - whole milliseconds since the Unix epoch
+fn clock_millis(
+    interpreter: &mut Interpreter,
+    _paren: &Token,
+    _arguments: &[LoxValue],
+) -> Result<LoxValue, RuntimeError> {
+    Ok(LoxValue::Number(interpreter.clock.now().as_millis() as f64))
+}
+
+/// `sleep(ms)` - block for `ms` milliseconds, a non-negative number
+fn sleep(
+    interpreter: &mut Interpreter,
+    paren: &Token,
+    arguments: &[LoxValue],
+) -> Result<LoxValue, RuntimeError> {
+    let ms = number_argument("sleep", paren, &arguments[0])?;
+    let Ok(duration) = Duration::try_from_secs_f64(ms / 1000.0) else {
+        return Err(RuntimeError::new(
+            paren,
+            "Argument to sleep must be a non-negative number of milliseconds.",
+        ));
+    };
+    interpreter.clock.sleep(duration);
+    Ok(LoxValue::Nil)
+}
+
+/// `println(x)` - print `x` like the `print` statement, then return it,
+/// so `var y = println(compute());` both logs and keeps the value
+fn println(
+    interpreter: &mut Interpreter,
+    _paren: &Token,
+    arguments: &[LoxValue],
+) -> Result<LoxValue, RuntimeError> {
+    interpreter.print(arguments);
+    Ok(arguments[0].clone())
+}
+
+// Every argument of the native `name`, all of which must be numbers
+fn number_arguments(
+    name: &str,
+    paren: &Token,
+    arguments: &[LoxValue],
+) -> Result<Vec<f64>, RuntimeError> {
+    arguments
+        .iter()
+        .map(|value| number_argument(name, paren, value))
+        .collect()
+}
+
+// A numeric argument of the native `name`
+fn number_argument(name: &str, paren: &Token, value: &LoxValue) -> Result<f64, RuntimeError> {
+    value
+        .as_number()
+        .ok_or_else(|| RuntimeError::new(paren, &format!("Argument to {} must be a number.", name)))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::RefCell;
+    use std::io::Cursor;
+    use std::rc::Rc;
+    use std::time::Duration;
+
+    use crate::clock::Clock;
+    use crate::interpreter::Interpreter;
+    use crate::lox_error::LoxError;
+    use crate::lox_value::LoxValue;
+    use crate::test_support::SharedBuffer;
+
+    fn runtime_error_message(source: &str) -> String {
+        match Interpreter::new().eval(source) {
+            Err(LoxError::Runtime(error)) => error.message,
+            other => panic!("Expected runtime error, got {:?}", other),
+        }
+    }
+
+    // ===== assert =====
+
+    #[test]
+    fn test_assert_true_returns_nil() {
+        let mut interpreter = Interpreter::new();
+        assert_eq!(interpreter.eval("assert(true)").unwrap(), LoxValue::Nil);
+    }
+
+    #[test]
+    fn test_assert_false_errors() {
+        assert_eq!(runtime_error_message("assert(false)"), "Assertion failed.");
+    }
+
+    #[test]
+    fn test_assert_nil_errors() {
+        assert_eq!(runtime_error_message("assert(nil)"), "Assertion failed.");
+    }
+
+    // ===== assert_eq =====
+
+    #[test]
+    fn test_assert_eq_equal_values_returns_nil() {
+        let mut interpreter = Interpreter::new();
+        assert_eq!(
+            interpreter.eval("assert_eq(1 + 1, 2)").unwrap(),
+            LoxValue::Nil
+        );
+    }
+
+    #[test]
+    fn test_assert_eq_unequal_values_errors_with_both() {
+        let message = runtime_error_message("assert_eq(1, 2)");
+        assert!(message.contains('1'));
+        assert!(message.contains('2'));
+    }
+
+    // ===== assert_throws =====
+
+    #[test]
+    fn test_assert_throws_passes_when_function_errors() {
+        // Given
+        let mut interpreter = Interpreter::new();
+
+        // When
+        let result = interpreter.eval("assert_throws(fun () { return 1 / 0; })");
+
+        // Then
+        assert_eq!(result.unwrap(), LoxValue::Nil);
+    }
+
+    #[test]
+    fn test_assert_throws_fails_when_function_returns() {
+        assert_eq!(
+            runtime_error_message("assert_throws(fun () { return 1; })"),
+            "Expected a runtime error but none occurred."
+        );
+    }
+
+    #[test]
+    fn test_assert_throws_lets_script_continue() {
+        let mut interpreter = Interpreter::new();
+
+        interpreter
+            .run("var a = 1; assert_throws(fun () { var b = nil; b(); }); a = a + 1;")
+            .unwrap();
+
+        assert_eq!(interpreter.eval("a").unwrap(), LoxValue::Number(2.0));
+    }
+
+    #[test]
+    fn test_assert_throws_requires_zero_argument_function() {
+        for source in ["assert_throws(1)", "assert_throws(fun (x) {})"] {
+            assert_eq!(
+                runtime_error_message(source),
+                "Argument to assert_throws must be a function taking no arguments.",
+                "{}",
+                source
+            );
+        }
+    }
+
+    // ===== repeat =====
+
+    #[test]
+    fn test_repeat_string() {
+        let mut interpreter = Interpreter::new();
+        assert_eq!(
+            interpreter.eval("repeat(\"ab\", 3)").unwrap(),
+            LoxValue::String("ababab".to_string())
+        );
+    }
+
+    #[test]
+    fn test_repeat_zero_times_is_empty() {
+        let mut interpreter = Interpreter::new();
+        assert_eq!(
+            interpreter.eval("repeat(\"x\", 0)").unwrap(),
+            LoxValue::String(String::new())
+        );
+    }
+
+    #[test]
+    fn test_repeat_invalid_count_errors() {
+        let expected = "Repeat count must be a non-negative integer.";
+        assert_eq!(runtime_error_message("repeat(\"x\", -1)"), expected);
+        assert_eq!(runtime_error_message("repeat(\"x\", 1.5)"), expected);
+        assert_eq!(runtime_error_message("repeat(\"x\", \"2\")"), expected);
+    }
+
+    #[test]
+    fn test_repeat_too_long_errors() {
+        let expected = "Repeated string is too long.";
+        assert_eq!(
+            runtime_error_message("repeat(\"ab\", 18446744073709551616)"),
+            expected
+        );
+        assert_eq!(runtime_error_message("repeat(\"x\", 1000000000)"), expected);
+    }
+
+    #[test]
+    fn test_repeat_empty_string_any_number_of_times() {
+        let mut interpreter = Interpreter::new();
+        assert_eq!(
+            interpreter.eval("repeat(\"\", 9007199254740992)").unwrap(),
+            LoxValue::String(String::new())
+        );
+    }
+
+    #[test]
+    fn test_repeat_non_string_errors() {
+        assert_eq!(
+            runtime_error_message("repeat(1, 2)"),
+            "First argument to repeat must be a string."
+        );
+    }
+
+    // ===== input =====
+
+    #[test]
+    fn test_input_reads_lines_then_nil() {
+        // Given
+        let reader = Cursor::new("first line\r\nsecond\n");
+        let mut interpreter = Interpreter::new().with_input(Box::new(reader));
+
+        // When / Then
+        assert_eq!(
+            interpreter.eval("input()").unwrap(),
+            LoxValue::String("first line".to_string())
+        );
+        assert_eq!(
+            interpreter.eval("input()").unwrap(),
+            LoxValue::String("second".to_string())
+        );
+        assert_eq!(interpreter.eval("input()").unwrap(), LoxValue::Nil);
+    }
+
+    #[test]
+    fn test_input_writes_prompt() {
+        let output = SharedBuffer::default();
+        let mut interpreter = Interpreter::new()
+            .with_input(Box::new(Cursor::new("Bob")))
+            .with_output(Box::new(output.clone()));
+
+        let name = interpreter.eval("input(\"name? \")").unwrap();
+
+        assert_eq!(name, LoxValue::String("Bob".to_string()));
+        assert_eq!(output.contents(), "name? ");
+    }
+
+    #[test]
+    fn test_input_too_many_arguments() {
+        assert_eq!(
+            runtime_error_message("input(1, 2)"),
+            "Expected 0 to 1 arguments but got 2."
+        );
+    }
+
+    // ===== format =====
+
+    #[test]
+    fn test_format_positional_placeholders() {
+        let mut interpreter = Interpreter::new();
+        assert_eq!(
+            interpreter.eval("format(\"{}+{}={}\", 1, 2, 3)").unwrap(),
+            LoxValue::String("1+2=3".to_string())
+        );
+    }
+
+    #[test]
+    fn test_format_escaped_braces() {
+        let mut interpreter = Interpreter::new();
+        assert_eq!(
+            interpreter.eval("format(\"{{{}}}\", \"x\")").unwrap(),
+            LoxValue::String("{x}".to_string())
+        );
+    }
+
+    #[test]
+    fn test_format_without_placeholders() {
+        let mut interpreter = Interpreter::new();
+        assert_eq!(
+            interpreter.eval("format(\"plain\")").unwrap(),
+            LoxValue::String("plain".to_string())
+        );
+    }
+
+    #[test]
+    fn test_format_count_mismatch_errors() {
+        assert_eq!(
+            runtime_error_message("format(\"{} {}\", 1)"),
+            "Format string has 2 placeholders but got 1 arguments."
+        );
+        assert_eq!(
+            runtime_error_message("format(\"{}\", 1, 2)"),
+            "Format string has 1 placeholders but got 2 arguments."
+        );
+    }
+
+    #[test]
+    fn test_format_unmatched_brace_errors() {
+        assert!(runtime_error_message("format(\"{x\")").starts_with("Unmatched '{'"));
+    }
+
+    // ===== contains / index_of =====
+
+    #[test]
+    fn test_contains() {
+        let mut interpreter = Interpreter::new();
+        assert_eq!(
+            interpreter.eval("contains(\"hello\", \"ell\")").unwrap(),
+            LoxValue::Bool(true)
+        );
+        assert_eq!(
+            interpreter.eval("contains(\"hello\", \"xyz\")").unwrap(),
+            LoxValue::Bool(false)
+        );
+        assert_eq!(
+            interpreter.eval("contains(\"hello\", \"\")").unwrap(),
+            LoxValue::Bool(true)
+        );
+    }
+
+    #[test]
+    fn test_index_of_found_and_absent() {
+        let mut interpreter = Interpreter::new();
+        assert_eq!(
+            interpreter.eval("index_of(\"hello\", \"l\")").unwrap(),
+            LoxValue::Number(2.0)
+        );
+        assert_eq!(
+            interpreter.eval("index_of(\"hello\", \"z\")").unwrap(),
+            LoxValue::Number(-1.0)
+        );
+    }
+
+    #[test]
+    fn test_index_of_counts_characters_not_bytes() {
+        // Given a haystack whose first characters take several bytes each
+        let mut interpreter = Interpreter::new();
+
+        // When
+        let index = interpreter
+            .eval("index_of(\"héllo wörld\", \"w\")")
+            .unwrap();
+
+        // Then
+        assert_eq!(index, LoxValue::Number(6.0));
+        assert_eq!(
+            interpreter.eval("index_of(\"日本語\", \"語\")").unwrap(),
+            LoxValue::Number(2.0)
+        );
+    }
+
+    #[test]
+    fn test_contains_and_index_of_require_strings() {
+        assert_eq!(
+            runtime_error_message("contains(\"abc\", 1)"),
+            "Arguments to contains must be strings."
+        );
+        assert_eq!(
+            runtime_error_message("index_of(nil, \"a\")"),
+            "Arguments to index_of must be strings."
+        );
+    }
+
+    // ===== Math =====
+
+    fn eval_number(source: &str) -> f64 {
+        match Interpreter::new().eval(source).unwrap() {
+            LoxValue::Number(n) => n,
+            other => panic!("Expected number, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_floor_and_ceil() {
+        assert_eq!(eval_number("floor(2.7)"), 2.0);
+        assert_eq!(eval_number("floor(-2.2)"), -3.0);
+        assert_eq!(eval_number("ceil(2.2)"), 3.0);
+        assert_eq!(eval_number("ceil(-2.7)"), -2.0);
+    }
+
+    #[test]
+    fn test_round_half_to_even() {
+        assert_eq!(eval_number("round(2.5)"), 2.0);
+        assert_eq!(eval_number("round(3.5)"), 4.0);
+        assert_eq!(eval_number("round(-2.5)"), -2.0);
+        assert_eq!(eval_number("round(2.6)"), 3.0);
+    }
+
+    #[test]
+    fn test_abs() {
+        assert_eq!(eval_number("abs(-4)"), 4.0);
+        assert_eq!(eval_number("abs(4)"), 4.0);
+    }
+
+    #[test]
+    fn test_sqrt() {
+        assert_eq!(eval_number("sqrt(9)"), 3.0);
+        assert_eq!(eval_number("sqrt(0)"), 0.0);
+    }
+
+    #[test]
+    fn test_sqrt_negative_errors() {
+        assert_eq!(
+            runtime_error_message("sqrt(-1)"),
+            "Cannot take the square root of a negative number."
+        );
+    }
+
+    #[test]
+    fn test_math_non_number_errors() {
+        for name in ["floor", "ceil", "round", "abs", "sqrt"] {
+            assert_eq!(
+                runtime_error_message(&format!("{}(\"1\")", name)),
+                format!("Argument to {} must be a number.", name)
+            );
+        }
+    }
+
+    #[test]
+    fn test_min_and_max_of_two_and_three() {
+        assert_eq!(eval_number("min(3, 1)"), 1.0);
+        assert_eq!(eval_number("max(3, 1)"), 3.0);
+        assert_eq!(eval_number("min(4, -2, 7)"), -2.0);
+        assert_eq!(eval_number("max(4, -2, 7)"), 7.0);
+        assert_eq!(eval_number("max(5)"), 5.0);
+    }
+
+    #[test]
+    fn test_min_max_errors() {
+        assert_eq!(
+            runtime_error_message("min()"),
+            "Expected at least 1 arguments but got 0."
+        );
+        assert_eq!(
+            runtime_error_message("max(1, \"2\", 3)"),
+            "Argument to max must be a number."
+        );
+    }
+
+    #[test]
+    fn test_pow() {
+        assert_eq!(eval_number("pow(2, 10)"), 1024.0);
+        assert_eq!(eval_number("pow(9, 0.5)"), 3.0);
+        assert_eq!(
+            runtime_error_message("pow(2, nil)"),
+            "Argument to pow must be a number."
+        );
+    }
+
+    #[test]
+    fn test_is_int() {
+        let mut interpreter = Interpreter::new();
+
+        assert_eq!(interpreter.eval("is_int(2)").unwrap(), LoxValue::Bool(true));
+        assert_eq!(
+            interpreter.eval("is_int(2.5)").unwrap(),
+            LoxValue::Bool(false)
+        );
+        assert_eq!(
+            interpreter.eval("is_int(pow(10, 400))").unwrap(),
+            LoxValue::Bool(false)
+        );
+        assert_eq!(
+            runtime_error_message("is_int(\"2\")"),
+            "Argument to is_int must be a number."
+        );
+    }
+
+    // ===== Random =====
+
+    #[test]
+    fn test_seeded_random_is_reproducible() {
+        // Given
+        let mut first = Interpreter::new();
+        let mut second = Interpreter::new();
+        first.eval("seed(42)").unwrap();
+        second.eval("seed(42)").unwrap();
+
+        // When
+        let values: Vec<LoxValue> = (0..3).map(|_| first.eval("random()").unwrap()).collect();
+
+        // Then
+        for value in &values {
+            assert_eq!(*value, second.eval("random()").unwrap());
+            let LoxValue::Number(n) = value else {
+                panic!("Expected number, got {:?}", value);
+            };
+            assert!((0.0..1.0).contains(n));
+        }
+        assert_ne!(values[0], values[1]);
+    }
+
+    #[test]
+    fn test_seed_42_sequence_is_pinned() {
+        // Scripts relying on a seed must keep their output across releases
+        let mut interpreter = Interpreter::new();
+        interpreter.eval("seed(42)").unwrap();
+
+        for expected in [0.08215683177501132, 0.49379796880431914, 0.3525927388151645] {
+            assert_eq!(
+                interpreter.eval("random()").unwrap(),
+                LoxValue::Number(expected)
+            );
+        }
+    }
+
+    #[test]
+    fn test_random_int_stays_in_inclusive_range() {
+        let mut interpreter = Interpreter::new();
+        interpreter.eval("seed(1)").unwrap();
+
+        let mut seen = [false; 3];
+        for _ in 0..200 {
+            let n = match interpreter.eval("random_int(-1, 1)").unwrap() {
+                LoxValue::Number(n) => n,
+                other => panic!("Expected number, got {:?}", other),
+            };
+            assert!((-1.0..=1.0).contains(&n) && n.fract() == 0.0);
+            seen[(n + 1.0) as usize] = true;
+        }
+        assert_eq!(seen, [true; 3]);
+    }
+
+    #[test]
+    fn test_random_int_invalid_range_errors() {
+        let expected = "Arguments to random_int must be integers with lo <= hi.";
+        assert_eq!(runtime_error_message("random_int(3, 1)"), expected);
+        assert_eq!(runtime_error_message("random_int(0.5, 1)"), expected);
+    }
+
+    // ===== arity =====
+
+    #[test]
+    fn test_arity_of_native() {
+        assert_eq!(eval_number("arity(pow)"), 2.0);
+        // Variadic natives report their minimum
+        assert_eq!(eval_number("arity(min)"), 1.0);
+    }
+
+    #[test]
+    fn test_arity_of_user_function() {
+        let mut interpreter = Interpreter::new();
+        interpreter
+            .run("fun add(a, b, c) {} class P { init(x) {} }")
+            .unwrap();
+
+        assert_eq!(
+            interpreter.eval("arity(add)").unwrap(),
+            LoxValue::Number(3.0)
+        );
+        assert_eq!(interpreter.eval("arity(P)").unwrap(), LoxValue::Number(1.0));
+        assert_eq!(
+            interpreter.eval("arity(fun () {})").unwrap(),
+            LoxValue::Number(0.0)
+        );
+    }
+
+    #[test]
+    fn test_arity_of_non_callable_errors() {
+        assert_eq!(
+            runtime_error_message("arity(1)"),
+            "Argument to arity must be a function or class."
+        );
+    }
+
+    // ===== Time =====
+
+    // Clock stopped at `now` that records sleeps instead of sleeping
+    #[derive(Debug, Default)]
+    struct FakeClock {
+        now: Duration,
+        sleeps: Rc<RefCell<Vec<Duration>>>,
+    }
+
+    impl Clock for FakeClock {
+        fn now(&self) -> Duration {
+            self.now
+        }
+
+        fn sleep(&mut self, duration: Duration) {
+            self.sleeps.borrow_mut().push(duration);
+        }
+    }
+
+    #[test]
+    fn test_clock_reads_injected_clock() {
+        let clock = FakeClock {
+            now: Duration::from_millis(1_700_000_000_123),
+            ..FakeClock::default()
+        };
+        let mut interpreter = Interpreter::new().with_clock(Box::new(clock));
+
+        assert_eq!(
+            interpreter.eval("clock_millis()").unwrap(),
+            LoxValue::Number(1_700_000_000_123.0)
+        );
+        assert_eq!(
+            interpreter.eval("clock()").unwrap(),
+            LoxValue::Number(1_700_000_000.123)
+        );
+    }
+
+    #[test]
+    fn test_sleep_calls_injected_sleeper() {
+        // Given
+        let clock = FakeClock::default();
+        let sleeps = Rc::clone(&clock.sleeps);
+        let mut interpreter = Interpreter::new().with_clock(Box::new(clock));
+
+        // When
+        interpreter
+            .run("sleep(250); sleep(0); sleep(1.5);")
+            .unwrap();
+
+        // Then
+        assert_eq!(
+            *sleeps.borrow(),
+            vec![
+                Duration::from_millis(250),
+                Duration::ZERO,
+                Duration::from_micros(1500)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_sleep_rejects_negative_duration() {
+        let clock = FakeClock::default();
+        let sleeps = Rc::clone(&clock.sleeps);
+        let mut interpreter = Interpreter::new().with_clock(Box::new(clock));
+
+        let Err(LoxError::Runtime(error)) = interpreter.eval("sleep(-1)") else {
+            panic!("Expected runtime error");
+        };
+
+        assert_eq!(
+            error.message,
+            "Argument to sleep must be a non-negative number of milliseconds."
+        );
+        assert!(sleeps.borrow().is_empty());
+        assert_eq!(
+            runtime_error_message("sleep(\"1\")"),
+            "Argument to sleep must be a number."
+        );
+    }
+
+    // ===== println =====
+
+    #[test]
+    fn test_println_prints_and_returns_argument() {
+        // Given
+        let output = SharedBuffer::default();
+        let mut interpreter = Interpreter::new().with_output(Box::new(output.clone()));
+
+        // When
+        interpreter
+            .run("var y = println(\"a\" + \"b\"); println(nil);")
+            .unwrap();
+
+        // Then
+        assert_eq!(
+            interpreter.eval("y").unwrap(),
+            LoxValue::String("ab".to_string())
+        );
+        assert_eq!(output.contents(), "ab\nnil\n");
+    }
+
+    // ===== ord and chr =====
+
+    #[test]
+    fn test_ord_and_chr_convert_code_points() {
+        let mut interpreter = Interpreter::new();
+
+        assert_eq!(
+            interpreter.eval("ord(\"A\")").unwrap(),
+            LoxValue::Number(65.0)
+        );
+        assert_eq!(
+            interpreter.eval("chr(65)").unwrap(),
+            LoxValue::String("A".to_string())
+        );
+        assert_eq!(
+            interpreter.eval("chr(ord(\"é\"))").unwrap(),
+            LoxValue::String("é".to_string())
+        );
+    }
+
+    #[test]
+    fn test_ord_rejects_anything_but_one_character() {
+        for source in ["ord(\"\")", "ord(\"AB\")", "ord(65)"] {
+            assert_eq!(
+                runtime_error_message(source),
+                "Argument to ord must be a one-character string.",
+                "{}",
+                source
+            );
+        }
+    }
+
+    #[test]
+    fn test_chr_rejects_invalid_code_points() {
+        for source in ["chr(-1)", "chr(65.5)", "chr(55296)", "chr(1114112)"] {
+            assert_eq!(
+                runtime_error_message(source),
+                "Argument to chr must be a valid code point.",
+                "{}",
+                source
+            );
+        }
+        assert_eq!(
+            runtime_error_message("chr(\"A\")"),
+            "Argument to chr must be a number."
+        );
+    }
+
+    // ===== parse_int and parse_float =====
+
+    #[test]
+    fn test_parse_int_in_radix() {
+        assert_eq!(eval_number("parse_int(\"ff\", 16)"), 255.0);
+        assert_eq!(eval_number("parse_int(\"101\", 2)"), 5.0);
+        assert_eq!(eval_number("parse_int(\"-Z\", 36)"), -35.0);
+        assert_eq!(eval_number("parse_int(\"042\", 10)"), 42.0);
+    }
+
+    #[test]
+    fn test_parse_int_invalid_digits_error() {
+        assert_eq!(
+            runtime_error_message("parse_int(\"102\", 2)"),
+            "Invalid digits for radix 2: '102'."
+        );
+        assert_eq!(
+            runtime_error_message("parse_int(\"\", 10)"),
+            "Invalid digits for radix 10: ''."
+        );
+    }
+
+    #[test]
+    fn test_parse_int_argument_errors() {
+        assert_eq!(
+            runtime_error_message("parse_int(10, 10)"),
+            "First argument to parse_int must be a string."
+        );
+        for source in [
+            "parse_int(\"1\", 1)",
+            "parse_int(\"1\", 37)",
+            "parse_int(\"1\", 2.5)",
+        ] {
+            assert_eq!(
+                runtime_error_message(source),
+                "Radix must be an integer from 2 to 36.",
+                "{}",
+                source
+            );
+        }
+    }
+
+    #[test]
+    fn test_parse_float() {
+        assert_eq!(eval_number("parse_float(\"2.5\")"), 2.5);
+        assert_eq!(eval_number("parse_float(\"-1e3\")"), -1000.0);
+        assert_eq!(
+            runtime_error_message("parse_float(\"abc\")"),
+            "Invalid number: 'abc'."
+        );
+        assert_eq!(
+            runtime_error_message("parse_float(1)"),
+            "Argument to parse_float must be a string."
+        );
+    }
+}
@@ -0,0 +1,184 @@
+use crate::expr::{Binary, Call, Expr, Get, Grouping, Literal, LiteralValue, Logical, Set, Unary};
+use crate::interpreter::Interpreter;
+use crate::lox_value::LoxValue;
+use crate::token::TokenType;
+
+/// Replace constant sub-expressions with their values
+///
+/// Operators whose operands are all literals are evaluated now, as an
+/// interpreter with default settings would, and `and`, `or` and `??` with
+/// a literal left operand reduce to the operand they would return. Parts
+/// that read variables or call functions are kept. An operation that would
+/// fail, such as `1 / 0`, is left in place so it still fails at run time.
+/// Block and function bodies are statements and are not folded.
+///
+/// # Examples
+/// - `1 + 2 * 3` -> `7`
+/// - `x + (1 + 2)` -> `(+ x 3)`
+/// - `true and x` -> `x`
+/// - `1 / 0` -> `(/ 1 0)`
+pub fn fold(expr: Expr) -> Expr {
+    Folder {
+        interpreter: Interpreter::new(),
+    }
+    .fold(expr)
+}
+
+struct Folder {
+    // Evaluates operators on literals, so folding agrees with run time
+    interpreter: Interpreter,
+}
+
+impl Folder {
+    fn fold(&mut self, expr: Expr) -> Expr {
+        match expr {
+            Expr::Assign(mut assign) => {
+                assign.value = Box::new(self.fold(*assign.value));
+                Expr::Assign(assign)
+            }
+            Expr::Binary(binary) => self.fold_binary(binary),
+            Expr::Call(call) => Expr::Call(Call {
+                callee: Box::new(self.fold(*call.callee)),
+                paren: call.paren,
+                arguments: call.arguments.into_iter().map(|a| self.fold(a)).collect(),
+            }),
+            Expr::Get(get) => Expr::Get(Get {
+                object: Box::new(self.fold(*get.object)),
+                name: get.name,
+            }),
+            Expr::Grouping(grouping) => match self.fold(*grouping.expression) {
+                literal @ Expr::Literal(_) => literal,
+                expression => Expr::Grouping(Grouping {
+                    expression: Box::new(expression),
+                }),
+            },
+            Expr::Logical(logical) => self.fold_logical(logical),
+            Expr::Set(set) => Expr::Set(Set {
+                object: Box::new(self.fold(*set.object)),
+                name: set.name,
+                operator: set.operator,
+                value: Box::new(self.fold(*set.value)),
+            }),
+            Expr::Unary(unary) => {
+                let unary = Unary {
+                    operator: unary.operator,
+                    right: Box::new(self.fold(*unary.right)),
+                };
+                let constant = is_literal(&unary.right);
+                self.evaluated(Expr::Unary(unary), constant)
+            }
+            other => other,
+        }
+    }
+
+    fn fold_binary(&mut self, binary: Binary) -> Expr {
+        let binary = Binary {
+            left: Box::new(self.fold(*binary.left)),
+            operator: binary.operator,
+            right: Box::new(self.fold(*binary.right)),
+        };
+        let constant = is_literal(&binary.left) && is_literal(&binary.right);
+        self.evaluated(Expr::Binary(binary), constant)
+    }
+
+    // A literal left operand decides which operand the whole expression is
+    fn fold_logical(&mut self, logical: Logical) -> Expr {
+        let left = self.fold(*logical.left);
+        let right = self.fold(*logical.right);
+
+        let Expr::Literal(literal) = &left else {
+            return Expr::Logical(Logical {
+                left: Box::new(left),
+                operator: logical.operator,
+                right: Box::new(right),
+            });
+        };
+        let value = LoxValue::from(literal.value.clone());
+        let short_circuit = match logical.operator.token_type {
+            TokenType::Or => value.is_truthy(),
+            TokenType::And => value.is_falsey(),
+            _ => !value.as_nil(),
+        };
+        if short_circuit { left } else { right }
+    }
+
+    // `expr` as a literal if its operands are `constant` and it evaluates
+    // without error to a value a literal can hold, `expr` itself otherwise
+    fn evaluated(&mut self, expr: Expr, constant: bool) -> Expr {
+        if !constant {
+            return expr;
+        }
+
+        let value = match self.interpreter.evaluate(&expr) {
+            Ok(LoxValue::Number(n)) => LiteralValue::Number(n),
+            Ok(LoxValue::String(s)) => LiteralValue::String(s),
+            Ok(LoxValue::Bool(b)) => LiteralValue::Bool(b),
+            Ok(LoxValue::Nil) => LiteralValue::Nil,
+            _ => return expr,
+        };
+        Expr::Literal(Literal { value })
+    }
+}
+
+fn is_literal(expr: &Expr) -> bool {
+    matches!(expr, Expr::Literal(_))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ast_printer::AstPrinter;
+    use crate::parser::Parser;
+
+    fn folded(source: &str) -> String {
+        let expr = Parser::from_source(source).unwrap().parse().unwrap();
+        AstPrinter::new().print(&fold(expr))
+    }
+
+    #[test]
+    fn test_folds_arithmetic_constants() {
+        assert_eq!(folded("1 + 2"), "3");
+        assert_eq!(folded("(1 + 2) * -3"), "-9");
+        assert_eq!(folded("\"a\" + \"b\""), "ab");
+        assert_eq!(folded("2 < 3 == !false"), "true");
+    }
+
+    #[test]
+    fn test_folds_logical_constants() {
+        assert_eq!(folded("true and false"), "false");
+        assert_eq!(folded("nil or 2"), "2");
+        assert_eq!(folded("nil ?? \"x\""), "x");
+    }
+
+    #[test]
+    fn test_logical_with_literal_left_reduces_to_chosen_operand() {
+        assert_eq!(folded("true and x"), "x");
+        assert_eq!(folded("false and f()"), "false");
+        assert_eq!(folded("1 or f()"), "1");
+    }
+
+    #[test]
+    fn test_keeps_variable_dependent_parts() {
+        assert_eq!(folded("x + (1 + 2)"), "(+ x 3)");
+        assert_eq!(folded("f(2 * 3, y)"), "(call f 6 y)");
+        assert_eq!(folded("x and 1 + 1"), "(and x 2)");
+    }
+
+    #[test]
+    fn test_does_not_fold_division_by_zero() {
+        // Given
+        let source = "1 / 0";
+
+        // When
+        let result = folded(source);
+
+        // Then
+        assert_eq!(result, "(/ 1 0)");
+    }
+
+    #[test]
+    fn test_does_not_fold_type_errors() {
+        assert_eq!(folded("-\"a\""), "(- a)");
+        assert_eq!(folded("1 + nil"), "(+ 1 nil)");
+    }
+}
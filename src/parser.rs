@@ -1,43 +1,795 @@
+use std::fmt;
+
 use crate::{
-    expr::{Binary, Expr, LiteralValue, Unary},
+    expr::{
+        self, Assign, Binary, Call, Expr, Get, LiteralValue, Logical, Postfix, Set, This, Unary,
+        Variable,
+    },
+    lox_error::ErrorKind,
+    scanner::{ScanError, Scanner},
+    stmt::{self, Stmt},
     token::{Token, TokenType},
 };
 
-/// Recursive descent parser for Lox expressions
+/// Recursive descent parser for Lox programs and expressions
+///
+/// The binary levels (equality through factor) share one precedence-climbing
+/// loop driven by `BINARY_OPERATORS`, so a new binary operator only needs
+/// an entry in that table.
+///
+/// Statement grammar:
+/// ```text
+/// program     → declaration* EOF
+/// declaration → classDecl | "fun" IDENTIFIER function | varDecl | statement
+/// classDecl   → "class" IDENTIFIER "{" member* "}"
+/// member      → "class"? IDENTIFIER function | IDENTIFIER block
+/// function    → "(" parameters? ")" block
+/// parameters  → IDENTIFIER ( "," IDENTIFIER )*
+/// varDecl     → "var" IDENTIFIER ( "=" expression )? ";"
+/// statement   → exprStmt | forStmt | ifStmt | printStmt | returnStmt
+///             | switchStmt | whileStmt | breakStmt | continueStmt
+///             | labeledStmt | block
+/// labeledStmt → IDENTIFIER ":" ( forStmt | whileStmt )
+/// breakStmt   → "break" IDENTIFIER? ";"
+/// continueStmt → "continue" IDENTIFIER? ";"
+/// forStmt     → "for" "(" ( varDecl | exprStmt | ";" )
+///               expression? ";" expression? ")" statement
+/// ifStmt      → "if" "(" expression ")" statement ( "else" statement )?
+/// printStmt   → "print" expression ( "," expression )* ";"
+/// returnStmt  → "return" expression? ";"
+/// switchStmt  → "switch" "(" expression ")" "{"
+///               ( "case" expression ":" declaration* ( "fallthrough" ";" )? )*
+///               ( "default" ":" declaration* )? "}"
+/// whileStmt   → "while" "(" expression ")" statement
+/// block       → "{" declaration* "}"
+/// ```
 ///
-/// Grammar (in order of precedence, lowest to highest):
+/// Expression grammar (in order of precedence, lowest to highest):
 /// ```text
-/// expression → equality
+/// expression → assignment
+/// assignment → ( call "." )? IDENTIFIER ( "=" | "+=" | "-=" | "*=" | "/=" ) assignment
+///            | logic_or
+/// logic_or   → coalesce ( "or" coalesce )*
+/// coalesce   → logic_and ( "??" logic_and )*
+/// logic_and  → equality ( "and" equality )*
 /// equality   → comparison ( ( "!=" | "==" ) comparison )*
-/// comparison → term ( ( ">" | ">=" | "<" | "<=" ) term )*
+/// comparison → term ( ( ">" | ">=" | "<" | "<=" ) term )?
 /// term       → factor ( ( "-" | "+" ) factor )*
-/// factor     → unary ( ( "/" | "*" ) unary )*
-/// unary      → ( "!" | "-" ) unary | primary
-/// primary    → NUMBER | STRING | "true" | "false" | "nil"
-///            | "(" expression ")"
+/// factor     → unary ( ( "/" | "*" | "%" ) unary )*
+/// unary      → ( "!" | "-" ) unary | ( "++" | "--" ) IDENTIFIER | postfix
+/// postfix    → call ( "++" | "--" )?
+/// call       → primary ( "(" arguments? ")" | "." IDENTIFIER )*
+/// arguments  → expression ( "," expression )* ","?
+/// primary    → NUMBER | STRING | "true" | "false" | "nil" | "this"
+///            | "(" expression ")" | IDENTIFIER | "fun" function
+///            | "{" declaration* expression? "}"
 /// ```
+///
+/// A `{` at the start of a statement always opens a block statement; only
+/// in expression position, e.g. `var x = { ... };`, is it a block
+/// expression.
 pub struct Parser {
     tokens: Vec<Token>,
     current: usize,
+    warnings: Vec<ParseError>,
+    had_error: bool,
+    // Block expressions being parsed outside the innermost function
+    block_expression_depth: usize,
+    // Loops around the current statement; labels are checked by the resolver
+    loop_depth: usize,
+    // Whether the innermost function is a class's `init` method
+    in_initializer: bool,
+    max_args: Option<usize>,
+}
+
+/// Binding power of binary operators, loosest first
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum Precedence {
+    Equality,
+    Comparison,
+    Term,
+    Factor,
 }
 
+impl Precedence {
+    // Next level that binds tighter, None above the tightest binary level
+    fn tighter(self) -> Option<Precedence> {
+        match self {
+            Precedence::Equality => Some(Precedence::Comparison),
+            Precedence::Comparison => Some(Precedence::Term),
+            Precedence::Term => Some(Precedence::Factor),
+            Precedence::Factor => None,
+        }
+    }
+}
+
+/// Binary operators and their precedence
+const BINARY_OPERATORS: &[(TokenType, Precedence)] = &[
+    (TokenType::BangEqual, Precedence::Equality),
+    (TokenType::EqualEqual, Precedence::Equality),
+    (TokenType::Greater, Precedence::Comparison),
+    (TokenType::GreaterEqual, Precedence::Comparison),
+    (TokenType::Less, Precedence::Comparison),
+    (TokenType::LessEqual, Precedence::Comparison),
+    (TokenType::Minus, Precedence::Term),
+    (TokenType::Plus, Precedence::Term),
+    (TokenType::Slash, Precedence::Factor),
+    (TokenType::Star, Precedence::Factor),
+    (TokenType::Percent, Precedence::Factor),
+];
+
+/// Default for `Parser::with_max_args`
+pub const DEFAULT_MAX_ARGS: usize = 255;
+
 // ParseError 추가
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct ParseError {
+    pub kind: ErrorKind,
     pub message: String,
     pub token: Token,
 }
 
+/// `[line N] Error at 'lexeme': message`, or `... Error at end: ...` at EOF
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.token.token_type == TokenType::Eof {
+            write!(
+                f,
+                "[line {}] Error at end: {}",
+                self.token.line, self.message
+            )
+        } else {
+            write!(
+                f,
+                "[line {}] Error at '{}': {}",
+                self.token.line, self.token.lexeme, self.message
+            )
+        }
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+/// One REPL input: a statement, or a bare expression whose value is shown
+#[derive(Debug, Clone, PartialEq)]
+pub enum ReplUnit {
+    Expr(Expr),
+    Stmt(Stmt),
+}
+
 impl Parser {
-    pub fn new(tokens: Vec<Token>) -> Self {
-        Self { tokens, current: 0 }
+    pub fn new(mut tokens: Vec<Token>) -> Self {
+        // Lookahead relies on the stream ending with EOF, as the scanner's does
+        if tokens.last().map(|token| token.token_type) != Some(TokenType::Eof) {
+            let line = tokens.last().map_or(1, |token| token.line);
+            tokens.push(Token::eof(line));
+        }
+
+        Self {
+            tokens,
+            current: 0,
+            warnings: Vec::new(),
+            had_error: false,
+            block_expression_depth: 0,
+            loop_depth: 0,
+            in_initializer: false,
+            max_args: Some(DEFAULT_MAX_ARGS),
+        }
+    }
+
+    /// Scan `source` and parse its tokens, or return the scan errors
+    ///
+    /// # Example
+    /// ```ignore
+    /// let expr = Parser::from_source("1 + 2")?.parse()?;
+    /// ```
+    pub fn from_source(source: &str) -> Result<Self, Vec<ScanError>> {
+        let mut scanner = Scanner::new(source.to_string());
+        let tokens = scanner.scan_tokens();
+        if scanner.had_error() {
+            return Err(scanner.errors().to_vec());
+        }
+        Ok(Self::new(tokens))
+    }
+
+    /// Most arguments a call, or parameters a function, may have
+    ///
+    /// Defaults to 255, the limit of the book's bytecode VM. The tree-walker
+    /// has no such limit, so `None` turns the check off.
+    pub fn with_max_args(mut self, max_args: Option<usize>) -> Self {
+        self.max_args = max_args;
+        self
+    }
+
+    // Error if `count` items are already there and one more is coming
+    fn check_count(&self, count: usize, what: &str) -> Result<(), ParseError> {
+        match self.max_args {
+            Some(max) if count >= max => Err(self.error(
+                self.peek(),
+                ErrorKind::TooManyArguments,
+                &format!("Can't have more than {} {}.", max, what),
+            )),
+            _ => Ok(()),
+        }
     }
 
     // === Public API ===
 
     /// Main entry point for parsing - returns Result instead of panicking
     pub fn parse(&mut self) -> Result<Expr, ParseError> {
-        self.expression_result()
+        let result = self.expression_result();
+        self.track(result)
+    }
+
+    /// Parse a single expression that must span the whole token stream
+    pub fn parse_expression(&mut self) -> Result<Expr, ParseError> {
+        let result = self.expression_result().and_then(|expr| {
+            if self.is_at_end() {
+                Ok(expr)
+            } else {
+                Err(self.error(
+                    self.peek(),
+                    ErrorKind::ExpectedToken,
+                    "Expect end of expression.",
+                ))
+            }
+        });
+        self.track(result)
+    }
+
+    /// Parse a single REPL input: a statement, or an expression without `;`
+    ///
+    /// Statements take precedence, so `1 + 2;` is an expression statement
+    /// and only `1 + 2` is a bare expression. No backtracking is needed:
+    /// anything that starts like an expression is parsed as one first and
+    /// becomes a statement if a `;` follows. Tokens left over are an error.
+    ///
+    /// # Examples
+    /// - `var x = 3;` -> `ReplUnit::Stmt(Stmt::Var(..))`
+    /// - `1 + 2` -> `ReplUnit::Expr(..)`
+    pub fn parse_repl(&mut self) -> Result<ReplUnit, Vec<ParseError>> {
+        let result = self.repl_unit().and_then(|unit| {
+            if self.is_at_end() {
+                Ok(unit)
+            } else {
+                Err(self.error(
+                    self.peek(),
+                    ErrorKind::ExpectedToken,
+                    "Expect end of input.",
+                ))
+            }
+        });
+        self.track(result).map_err(|error| vec![error])
+    }
+
+    fn repl_unit(&mut self) -> Result<ReplUnit, ParseError> {
+        if self.starts_statement() {
+            return Ok(ReplUnit::Stmt(self.declaration()?));
+        }
+
+        let expression = self.expression_result()?;
+        if self.match_tokens(&[TokenType::Semicolon]) {
+            Ok(ReplUnit::Stmt(Stmt::Expression(stmt::Expression {
+                expression,
+            })))
+        } else {
+            Ok(ReplUnit::Expr(expression))
+        }
+    }
+
+    /// Parse a whole program
+    ///
+    /// After an error the parser synchronizes to the next statement
+    /// boundary, so every independent error in the source is reported.
+    pub fn parse_program(&mut self) -> Result<Vec<Stmt>, Vec<ParseError>> {
+        let mut statements = Vec::new();
+        let mut errors = Vec::new();
+
+        while !self.is_at_end() {
+            match self.declaration() {
+                Ok(statement) => statements.push(statement),
+                Err(error) => {
+                    errors.push(error);
+                    self.had_error = true;
+                    self.synchronize();
+                }
+            }
+        }
+
+        if errors.is_empty() {
+            Ok(statements)
+        } else {
+            Err(errors)
+        }
+    }
+
+    /// True once any parse on this parser has produced an error
+    ///
+    /// Errors the parser recovered from by synchronizing count too, so a
+    /// driver can pick its exit code after `parse_program` reported them.
+    /// Warnings do not set it.
+    pub fn had_error(&self) -> bool {
+        self.had_error
+    }
+
+    // Record an error result for `had_error`
+    fn track<T>(&mut self, result: Result<T, ParseError>) -> Result<T, ParseError> {
+        self.had_error |= result.is_err();
+        result
+    }
+
+    /// Suspicious but valid code found while parsing
+    ///
+    /// Warnings never stop parsing; the code means what it says.
+    pub fn warnings(&self) -> &[ParseError] {
+        &self.warnings
+    }
+
+    // === Statements ===
+
+    // True when the next token starts a declaration or a statement other
+    // than an expression statement
+    fn starts_statement(&self) -> bool {
+        matches!(
+            self.peek().token_type,
+            TokenType::Class
+                | TokenType::Var
+                | TokenType::Print
+                | TokenType::If
+                | TokenType::While
+                | TokenType::For
+                | TokenType::Return
+                | TokenType::Switch
+                | TokenType::Break
+                | TokenType::Continue
+                | TokenType::Fallthrough
+                | TokenType::LeftBrace
+        ) || (self.check(TokenType::Fun) && self.check_next(TokenType::Identifier))
+            || (self.check(TokenType::Identifier) && self.check_next(TokenType::Colon))
+    }
+
+    fn declaration(&mut self) -> Result<Stmt, ParseError> {
+        if self.match_tokens(&[TokenType::Class]) {
+            return self.class_declaration();
+        }
+        // `fun (` starts a lambda expression statement, not a declaration
+        if self.check(TokenType::Fun) && self.check_next(TokenType::Identifier) {
+            self.advance();
+            return self.function_declaration();
+        }
+        if self.match_tokens(&[TokenType::Var]) {
+            return self.var_declaration();
+        }
+        self.statement()
+    }
+
+    fn class_declaration(&mut self) -> Result<Stmt, ParseError> {
+        let name = self.consume(TokenType::Identifier, "Expect class name.")?;
+        self.consume(TokenType::LeftBrace, "Expect '{' before class body.")?;
+
+        let mut methods = Vec::new();
+        let mut getters = Vec::new();
+        let mut class_methods = Vec::new();
+        while !self.check(TokenType::RightBrace) && !self.is_at_end() {
+            if self.match_tokens(&[TokenType::Class]) {
+                class_methods.push(self.function("method")?);
+            } else if self.check(TokenType::Identifier) && self.check_next(TokenType::LeftBrace) {
+                // A getter has a body but no parameter list
+                let name = self.consume(TokenType::Identifier, "Expect method name.")?;
+                let body = self.function_body(false)?;
+                getters.push(stmt::Function {
+                    name,
+                    params: Vec::new(),
+                    body,
+                });
+            } else {
+                methods.push(self.method()?);
+            }
+        }
+
+        self.consume(TokenType::RightBrace, "Expect '}' after class body.")?;
+        Ok(Stmt::Class(stmt::Class {
+            name,
+            methods,
+            getters,
+            class_methods,
+        }))
+    }
+
+    fn function_declaration(&mut self) -> Result<Stmt, ParseError> {
+        Ok(Stmt::Function(self.function("function")?))
+    }
+
+    // Named function or method; `kind` is used in error messages
+    fn function(&mut self, kind: &str) -> Result<stmt::Function, ParseError> {
+        let name = self.consume(TokenType::Identifier, &format!("Expect {} name.", kind))?;
+        self.consume(
+            TokenType::LeftParen,
+            &format!("Expect '(' after {} name.", kind),
+        )?;
+        let (params, body) = self.function_rest(false)?;
+        Ok(stmt::Function { name, params, body })
+    }
+
+    // Instance method, where `init` may not return a value
+    fn method(&mut self) -> Result<stmt::Function, ParseError> {
+        let name = self.consume(TokenType::Identifier, "Expect method name.")?;
+        self.consume(TokenType::LeftParen, "Expect '(' after method name.")?;
+        let (params, body) = self.function_rest(name.lexeme == "init")?;
+        Ok(stmt::Function { name, params, body })
+    }
+
+    // Parameters and body of a function, after its opening parenthesis
+    fn function_rest(&mut self, initializer: bool) -> Result<(Vec<Token>, Vec<Stmt>), ParseError> {
+        let mut params = Vec::new();
+
+        if !self.check(TokenType::RightParen) {
+            loop {
+                self.check_count(params.len(), "parameters")?;
+                params.push(self.consume(TokenType::Identifier, "Expect parameter name.")?);
+                if !self.match_tokens(&[TokenType::Comma]) {
+                    break;
+                }
+            }
+        }
+        self.consume(TokenType::RightParen, "Expect ')' after parameters.")?;
+
+        let body = self.function_body(initializer)?;
+        Ok((params, body))
+    }
+
+    fn function_body(&mut self, initializer: bool) -> Result<Vec<Stmt>, ParseError> {
+        self.consume(TokenType::LeftBrace, "Expect '{' before function body.")?;
+        // `return` in the body leaves this function, not an enclosing block expression
+        // and `break` cannot reach the loops around the function
+        let depth = std::mem::take(&mut self.block_expression_depth);
+        let loop_depth = std::mem::take(&mut self.loop_depth);
+        let in_initializer = std::mem::replace(&mut self.in_initializer, initializer);
+        let body = self.block();
+        self.block_expression_depth = depth;
+        self.loop_depth = loop_depth;
+        self.in_initializer = in_initializer;
+        body
+    }
+
+    fn var_declaration(&mut self) -> Result<Stmt, ParseError> {
+        let name = self.consume(TokenType::Identifier, "Expect variable name.")?;
+
+        let initializer = if self.match_tokens(&[TokenType::Equal]) {
+            Some(self.expression_result()?)
+        } else {
+            None
+        };
+
+        self.consume(
+            TokenType::Semicolon,
+            "Expect ';' after variable declaration.",
+        )?;
+        Ok(Stmt::Var(stmt::Var { name, initializer }))
+    }
+
+    fn statement(&mut self) -> Result<Stmt, ParseError> {
+        if self.check(TokenType::Identifier) && self.check_next(TokenType::Colon) {
+            return self.labeled_statement();
+        }
+        if self.match_tokens(&[TokenType::Break]) {
+            return self.break_statement();
+        }
+        if self.match_tokens(&[TokenType::Continue]) {
+            return self.continue_statement();
+        }
+        // `case_body` takes a `fallthrough` that ends a case before it gets here
+        if self.check(TokenType::Fallthrough) {
+            return Err(self.error(
+                self.peek(),
+                ErrorKind::InvalidFallthrough,
+                "'fallthrough' can only end a case.",
+            ));
+        }
+        if self.match_tokens(&[TokenType::For]) {
+            return self.for_statement(None);
+        }
+        if self.match_tokens(&[TokenType::If]) {
+            return self.if_statement();
+        }
+        if self.match_tokens(&[TokenType::Print]) {
+            return self.print_statement();
+        }
+        if self.match_tokens(&[TokenType::Return]) {
+            return self.return_statement();
+        }
+        if self.match_tokens(&[TokenType::Switch]) {
+            return self.switch_statement();
+        }
+        if self.match_tokens(&[TokenType::While]) {
+            return self.while_statement(None);
+        }
+        if self.match_tokens(&[TokenType::LeftBrace]) {
+            return Ok(Stmt::Block(stmt::Block {
+                statements: self.block()?,
+            }));
+        }
+        self.expression_statement()
+    }
+
+    // `label:` in front of a loop, so `break label;` can leave it from a nested loop
+    fn labeled_statement(&mut self) -> Result<Stmt, ParseError> {
+        self.advance();
+        let label = self.take_previous();
+        self.advance();
+        if self.match_tokens(&[TokenType::For]) {
+            return self.for_statement(Some(label));
+        }
+        if self.match_tokens(&[TokenType::While]) {
+            return self.while_statement(Some(label));
+        }
+        Err(self.error(
+            self.peek(),
+            ErrorKind::ExpectedToken,
+            "Expect loop after label.",
+        ))
+    }
+
+    fn break_statement(&mut self) -> Result<Stmt, ParseError> {
+        let keyword = self.take_previous();
+        let label = self.loop_target(&keyword)?;
+        self.consume(TokenType::Semicolon, "Expect ';' after 'break'.")?;
+        Ok(Stmt::Break(stmt::Break { keyword, label }))
+    }
+
+    fn continue_statement(&mut self) -> Result<Stmt, ParseError> {
+        let keyword = self.take_previous();
+        let label = self.loop_target(&keyword)?;
+        self.consume(TokenType::Semicolon, "Expect ';' after 'continue'.")?;
+        Ok(Stmt::Continue(stmt::Continue { keyword, label }))
+    }
+
+    // Optional label after `break`/`continue`; the resolver checks that it
+    // names an enclosing loop
+    fn loop_target(&mut self, keyword: &Token) -> Result<Option<Token>, ParseError> {
+        if self.loop_depth == 0 {
+            return Err(self.error(
+                keyword,
+                ErrorKind::InvalidLoopControl,
+                &format!("Can't use '{}' outside of a loop.", keyword.lexeme),
+            ));
+        }
+        if !self.match_tokens(&[TokenType::Identifier]) {
+            return Ok(None);
+        }
+        Ok(Some(self.take_previous()))
+    }
+
+    // Body of a loop, with `break` and `continue` allowed inside it
+    fn loop_body(&mut self) -> Result<Stmt, ParseError> {
+        self.loop_depth += 1;
+        let body = self.statement();
+        self.loop_depth -= 1;
+        body
+    }
+
+    // `for` has no node of its own; it desugars to a while loop in a block
+    fn for_statement(&mut self, label: Option<Token>) -> Result<Stmt, ParseError> {
+        let keyword = self.take_previous();
+        self.consume(TokenType::LeftParen, "Expect '(' after 'for'.")?;
+
+        let initializer = if self.match_tokens(&[TokenType::Semicolon]) {
+            None
+        } else if self.match_tokens(&[TokenType::Var]) {
+            Some(self.var_declaration()?)
+        } else {
+            Some(self.expression_statement()?)
+        };
+
+        let condition = if self.check(TokenType::Semicolon) {
+            None
+        } else {
+            Some(self.expression_result()?)
+        };
+        self.consume(TokenType::Semicolon, "Expect ';' after loop condition.")?;
+
+        let increment = if self.check(TokenType::RightParen) {
+            None
+        } else {
+            Some(self.expression_result()?)
+        };
+        self.consume(TokenType::RightParen, "Expect ')' after for clauses.")?;
+
+        let body = self.loop_body()?;
+
+        let condition = condition.unwrap_or(Expr::Literal(expr::Literal {
+            value: LiteralValue::Bool(true),
+        }));
+        // The increment stays out of the body, so `continue` still runs it
+        let mut body = Stmt::While(stmt::While {
+            keyword,
+            label,
+            condition,
+            body: Box::new(body),
+            increment: increment.map(Box::new),
+        });
+
+        if let Some(initializer) = initializer {
+            body = Stmt::Block(stmt::Block {
+                statements: vec![initializer, body],
+            });
+        }
+        Ok(body)
+    }
+
+    fn if_statement(&mut self) -> Result<Stmt, ParseError> {
+        self.consume(TokenType::LeftParen, "Expect '(' after 'if'.")?;
+        let condition = self.condition()?;
+        self.consume(TokenType::RightParen, "Expect ')' after if condition.")?;
+
+        let then_branch = Box::new(self.statement()?);
+        let else_branch = if self.match_tokens(&[TokenType::Else]) {
+            Some(Box::new(self.statement()?))
+        } else {
+            None
+        };
+
+        Ok(Stmt::If(stmt::If {
+            condition,
+            then_branch,
+            else_branch,
+        }))
+    }
+
+    fn print_statement(&mut self) -> Result<Stmt, ParseError> {
+        let mut expressions = vec![self.expression_result()?];
+        while self.match_tokens(&[TokenType::Comma]) {
+            expressions.push(self.expression_result()?);
+        }
+        self.consume(TokenType::Semicolon, "Expect ';' after value.")?;
+        Ok(Stmt::Print(stmt::Print { expressions }))
+    }
+
+    fn return_statement(&mut self) -> Result<Stmt, ParseError> {
+        let keyword = self.take_previous();
+        if self.block_expression_depth > 0 {
+            return Err(self.error(
+                &keyword,
+                ErrorKind::InvalidReturn,
+                "Can't return from inside a block expression.",
+            ));
+        }
+        let value = if self.check(TokenType::Semicolon) {
+            None
+        } else {
+            Some(self.expression_result()?)
+        };
+        if value.is_some() && self.in_initializer {
+            return Err(self.error(
+                &keyword,
+                ErrorKind::InvalidReturn,
+                "Can't return a value from an initializer.",
+            ));
+        }
+
+        self.consume(TokenType::Semicolon, "Expect ';' after return value.")?;
+        Ok(Stmt::Return(stmt::Return { keyword, value }))
+    }
+
+    fn switch_statement(&mut self) -> Result<Stmt, ParseError> {
+        self.consume(TokenType::LeftParen, "Expect '(' after 'switch'.")?;
+        let discriminant = self.expression_result()?;
+        self.consume(TokenType::RightParen, "Expect ')' after switch value.")?;
+        self.consume(TokenType::LeftBrace, "Expect '{' before switch body.")?;
+
+        let mut cases = Vec::new();
+        let mut default = None;
+        while !self.check(TokenType::RightBrace) && !self.is_at_end() {
+            if self.match_tokens(&[TokenType::Case]) {
+                let value = self.expression_result()?;
+                self.consume(TokenType::Colon, "Expect ':' after case value.")?;
+                cases.push((value, self.case_body(true)?));
+            } else if self.check(TokenType::Default) {
+                if default.is_some() {
+                    return Err(self.error(
+                        self.peek(),
+                        ErrorKind::ExpectedToken,
+                        "A switch can have only one default.",
+                    ));
+                }
+                self.advance();
+                self.consume(TokenType::Colon, "Expect ':' after 'default'.")?;
+                default = Some(self.case_body(false)?);
+            } else {
+                return Err(self.error(
+                    self.peek(),
+                    ErrorKind::ExpectedToken,
+                    "Expect 'case' or 'default' in switch body.",
+                ));
+            }
+        }
+
+        self.consume(TokenType::RightBrace, "Expect '}' after switch body.")?;
+        Ok(Stmt::Switch(stmt::Switch {
+            discriminant,
+            cases,
+            default,
+        }))
+    }
+
+    // Statements of one case, up to the next label or the end of the switch.
+    // With `can_fall_through`, the last one may be `fallthrough;` when
+    // another case follows.
+    fn case_body(&mut self, can_fall_through: bool) -> Result<Vec<Stmt>, ParseError> {
+        let mut statements = Vec::new();
+        while !self.check(TokenType::Case)
+            && !self.check(TokenType::Default)
+            && !self.check(TokenType::RightBrace)
+            && !self.is_at_end()
+        {
+            if can_fall_through && self.match_tokens(&[TokenType::Fallthrough]) {
+                let keyword = self.take_previous();
+                self.consume(TokenType::Semicolon, "Expect ';' after 'fallthrough'.")?;
+                if !self.check(TokenType::Case) {
+                    return Err(self.error(
+                        &keyword,
+                        ErrorKind::InvalidFallthrough,
+                        "'fallthrough' must be followed by another case.",
+                    ));
+                }
+                statements.push(Stmt::Fallthrough(stmt::Fallthrough { keyword }));
+                break;
+            }
+            statements.push(self.declaration()?);
+        }
+        Ok(statements)
+    }
+
+    fn while_statement(&mut self, label: Option<Token>) -> Result<Stmt, ParseError> {
+        let keyword = self.take_previous();
+        self.consume(TokenType::LeftParen, "Expect '(' after 'while'.")?;
+        let condition = self.condition()?;
+        self.consume(TokenType::RightParen, "Expect ')' after condition.")?;
+        let body = Box::new(self.loop_body()?);
+
+        Ok(Stmt::While(stmt::While {
+            keyword,
+            label,
+            condition,
+            body,
+            increment: None,
+        }))
+    }
+
+    // Condition of `if`/`while`, warning when it is a bare `x = value`.
+    // Wrapping it in parentheses, `if ((x = value))`, silences the warning.
+    fn condition(&mut self) -> Result<Expr, ParseError> {
+        let bare_assignment =
+            self.check(TokenType::Identifier) && self.check_next(TokenType::Equal);
+        let condition = self.expression_result()?;
+
+        if bare_assignment && let Expr::Assign(assign) = &condition {
+            let warning = self.error(
+                &assign.name,
+                ErrorKind::AssignmentInCondition,
+                "Assignment used as a condition; did you mean '=='?",
+            );
+            self.warnings.push(warning);
+        }
+        Ok(condition)
+    }
+
+    // Statements up to the closing brace; the opening brace is already consumed
+    fn block(&mut self) -> Result<Vec<Stmt>, ParseError> {
+        let mut statements = Vec::new();
+
+        while !self.check(TokenType::RightBrace) && !self.is_at_end() {
+            statements.push(self.declaration()?);
+        }
+
+        self.consume(TokenType::RightBrace, "Expect '}' after block.")?;
+        Ok(statements)
+    }
+
+    fn expression_statement(&mut self) -> Result<Stmt, ParseError> {
+        let expression = self.expression_result()?;
+        self.consume(TokenType::Semicolon, "Expect ';' after expression.")?;
+        Ok(Stmt::Expression(stmt::Expression { expression }))
     }
 
     // === Helper methods ===
@@ -47,6 +799,11 @@ impl Parser {
         !self.is_at_end() && self.peek().token_type == token_type
     }
 
+    // Check the token after the current one without consuming anything
+    fn check_next(&self, token_type: TokenType) -> bool {
+        self.peek_at(1).token_type == token_type
+    }
+
     // Consume current token and return it
     pub fn advance(&mut self) -> &Token {
         if !self.is_at_end() {
@@ -60,110 +817,141 @@ impl Parser {
     }
 
     pub fn peek(&self) -> &Token {
-        &self.tokens[self.current]
+        self.peek_at(0)
+    }
+
+    /// Token `offset` positions after the current one, without consuming it
+    ///
+    /// Reading past the end of the stream yields the EOF token.
+    pub fn peek_at(&self, offset: usize) -> &Token {
+        let eof = &self.tokens[self.tokens.len() - 1];
+        self.tokens.get(self.current + offset).unwrap_or(eof)
     }
 
+    // Move the last consumed token out of the stream instead of cloning its
+    // strings. The slot keeps the type, line and source name, all that
+    // lookbehind and error locations need.
+    fn take_previous(&mut self) -> Token {
+        debug_assert!(
+            self.current > 0,
+            "take_previous before any token was consumed"
+        );
+        let previous = &mut self.tokens[self.current - 1];
+        let mut placeholder = Token::new(previous.token_type, String::new(), None, previous.line);
+        placeholder.source_name = previous.source_name.clone();
+        std::mem::replace(previous, placeholder)
+    }
+
+    /// Last consumed token
+    ///
+    /// Tokens the parser moved into the AST keep only their type, line and
+    /// source name here.
+    /// Before anything is consumed there is no such token, and this returns
+    /// the first token of the stream (EOF for empty input) instead.
     pub fn previous(&self) -> &Token {
-        &self.tokens[self.current - 1]
+        &self.tokens[self.current.saturating_sub(1)]
     }
 
     // Error handling version
     fn expression_result(&mut self) -> Result<Expr, ParseError> {
-        self.equality_result()
+        self.assignment_result()
     }
 
-    // Original panic version for backward compatibility
-    pub fn expression(&mut self) -> Expr {
-        self.equality()
+    // Public entry points to single grammar rules, for tests and tools
+    pub fn expression(&mut self) -> Result<Expr, ParseError> {
+        self.expression_result()
     }
 
-    fn equality_result(&mut self) -> Result<Expr, ParseError> {
-        let mut expr = self.comparison_result()?;
+    fn assignment_result(&mut self) -> Result<Expr, ParseError> {
+        let expr = self.or_result()?;
 
-        while self.match_tokens(&[TokenType::BangEqual, TokenType::EqualEqual]) {
-            let operator = self.previous().clone();
-            let right = self.comparison_result()?;
-            expr = Expr::Binary(Binary {
-                left: Box::new(expr),
-                operator,
-                right: Box::new(right),
-            });
+        if self.match_tokens(&[
+            TokenType::Equal,
+            TokenType::PlusEqual,
+            TokenType::MinusEqual,
+            TokenType::StarEqual,
+            TokenType::SlashEqual,
+        ]) {
+            let equals = self.take_previous();
+            let value = self.assignment_result()?;
+
+            let operator = Self::compound_operator(&equals);
+            match expr {
+                // `x += 1` desugars to `x = x + 1`
+                Expr::Variable(variable) => {
+                    let value = match operator {
+                        Some(operator) => Expr::Binary(Binary {
+                            left: Box::new(Expr::Variable(variable.clone())),
+                            operator,
+                            right: Box::new(value),
+                        }),
+                        None => value,
+                    };
+                    return Ok(Expr::Assign(Assign {
+                        id: expr::next_id(),
+                        name: variable.name,
+                        value: Box::new(value),
+                    }));
+                }
+                // `o.x += 1` keeps its operator, so `o` is evaluated once
+                Expr::Get(get) => {
+                    return Ok(Expr::Set(Set {
+                        object: get.object,
+                        name: get.name,
+                        operator,
+                        value: Box::new(value),
+                    }));
+                }
+                _ => {}
+            }
+
+            return Err(self.error(
+                &equals,
+                ErrorKind::InvalidAssignTarget,
+                "Invalid assignment target.",
+            ));
         }
         Ok(expr)
     }
 
-    fn equality(&mut self) -> Expr {
-        let mut expr = self.comparison();
+    fn or_result(&mut self) -> Result<Expr, ParseError> {
+        let mut expr = self.coalesce_result()?;
 
-        while self.match_tokens(&[TokenType::BangEqual, TokenType::EqualEqual]) {
-            let operator = self.previous().clone();
-            let right = self.comparison();
-            expr = Expr::Binary(Binary {
+        while self.match_tokens(&[TokenType::Or]) {
+            let operator = self.take_previous();
+            let right = self.coalesce_result()?;
+            expr = Expr::Logical(Logical {
                 left: Box::new(expr),
                 operator,
                 right: Box::new(right),
             });
         }
-        expr
-    }
-
-    pub fn match_tokens(&mut self, types: &[TokenType]) -> bool {
-        for token_type in types.iter() {
-            if self.check(token_type.clone()) {
-                self.advance();
-                return true;
-            }
-        }
-        false
-    }
-
-    fn comparison_result(&mut self) -> Result<Expr, ParseError> {
-        let mut expr = self.term_result()?;
-
-        while self.match_tokens(&[
-            TokenType::Greater,
-            TokenType::GreaterEqual,
-            TokenType::Less,
-            TokenType::LessEqual,
-        ]) {
-            let operator = self.previous().clone();
-            let right = self.term_result()?;
-            expr = Expr::Binary(Binary {
-                left: Box::new(expr),
-                operator,
-                right: Box::new(right),
-            })
-        }
         Ok(expr)
     }
 
-    pub fn comparison(&mut self) -> Expr {
-        let mut expr = self.term();
+    // `a ?? b` binds tighter than `or` and looser than `and`
+    fn coalesce_result(&mut self) -> Result<Expr, ParseError> {
+        let mut expr = self.and_result()?;
 
-        while self.match_tokens(&[
-            TokenType::Greater,
-            TokenType::GreaterEqual,
-            TokenType::Less,
-            TokenType::LessEqual,
-        ]) {
-            let operator = self.previous().clone();
-            let right = self.term();
-            expr = Expr::Binary(Binary {
+        while self.match_tokens(&[TokenType::QuestionQuestion]) {
+            let operator = self.take_previous();
+            let right = self.and_result()?;
+            expr = Expr::Logical(Logical {
                 left: Box::new(expr),
                 operator,
                 right: Box::new(right),
-            })
+            });
         }
-        expr
+        Ok(expr)
     }
 
-    fn term_result(&mut self) -> Result<Expr, ParseError> {
-        let mut expr = self.factor_result()?;
+    fn and_result(&mut self) -> Result<Expr, ParseError> {
+        let mut expr = self.binary_result(Precedence::Equality)?;
 
-        while self.match_tokens(&[TokenType::Minus, TokenType::Plus]) {
-            let operator = self.previous().clone();
-            let right = self.factor_result()?;
-            expr = Expr::Binary(Binary {
+        while self.match_tokens(&[TokenType::And]) {
+            let operator = self.take_previous();
+            let right = self.binary_result(Precedence::Equality)?;
+            expr = Expr::Logical(Logical {
                 left: Box::new(expr),
                 operator,
                 right: Box::new(right),
@@ -172,27 +960,58 @@ impl Parser {
         Ok(expr)
     }
 
-    pub fn term(&mut self) -> Expr {
-        let mut expr = self.factor();
+    // Binary operator a compound assignment stands for, None for plain `=`
+    fn compound_operator(equals: &Token) -> Option<Token> {
+        let (token_type, lexeme) = match equals.token_type {
+            TokenType::PlusEqual => (TokenType::Plus, "+"),
+            TokenType::MinusEqual => (TokenType::Minus, "-"),
+            TokenType::StarEqual => (TokenType::Star, "*"),
+            TokenType::SlashEqual => (TokenType::Slash, "/"),
+            _ => return None,
+        };
+        Some(Token::new(
+            token_type,
+            lexeme.to_string(),
+            None,
+            equals.line,
+        ))
+    }
 
-        while self.match_tokens(&[TokenType::Minus, TokenType::Plus]) {
-            let operator = self.previous().clone();
-            let right = self.factor();
-            expr = Expr::Binary(Binary {
-                left: Box::new(expr),
-                operator,
-                right: Box::new(right),
-            });
+    pub fn match_tokens(&mut self, types: &[TokenType]) -> bool {
+        for token_type in types.iter() {
+            if self.check(*token_type) {
+                self.advance();
+                return true;
+            }
         }
-        expr
+        false
     }
 
-    fn factor_result(&mut self) -> Result<Expr, ParseError> {
+    // Precedence climbing over BINARY_OPERATORS; every level is left-associative
+    // except comparison, which does not chain: `1 < 2 < 3` is an error
+    fn binary_result(&mut self, min: Precedence) -> Result<Expr, ParseError> {
         let mut expr = self.unary_result()?;
+        let mut after_comparison = false;
 
-        while self.match_tokens(&[TokenType::Slash, TokenType::Star]) {
-            let operator = self.previous().clone();
-            let right = self.unary_result()?;
+        while let Some(precedence) = self.binary_precedence() {
+            if precedence < min {
+                break;
+            }
+            if precedence == Precedence::Comparison && after_comparison {
+                return Err(self.error(
+                    self.peek(),
+                    ErrorKind::ChainedComparison,
+                    "Chained comparison is not allowed; use 'and'.",
+                ));
+            }
+            after_comparison = precedence == Precedence::Comparison;
+
+            self.advance();
+            let operator = self.take_previous();
+            let right = match precedence.tighter() {
+                Some(tighter) => self.binary_result(tighter)?,
+                None => self.unary_result()?,
+            };
             expr = Expr::Binary(Binary {
                 left: Box::new(expr),
                 operator,
@@ -202,49 +1021,149 @@ impl Parser {
         Ok(expr)
     }
 
-    pub fn factor(&mut self) -> Expr {
-        let mut expr = self.unary();
+    // Precedence of the current token if it is a binary operator
+    fn binary_precedence(&self) -> Option<Precedence> {
+        let token_type = self.peek().token_type;
+        BINARY_OPERATORS
+            .iter()
+            .find(|(operator, _)| *operator == token_type)
+            .map(|(_, precedence)| *precedence)
+    }
+
+    pub fn equality(&mut self) -> Result<Expr, ParseError> {
+        self.binary_result(Precedence::Equality)
+    }
 
-        while self.match_tokens(&[TokenType::Slash, TokenType::Star]) {
-            let operator = self.previous().clone();
-            let right = self.unary();
-            expr = Expr::Binary(Binary {
-                left: Box::new(expr),
-                operator,
-                right: Box::new(right),
-            });
-        }
-        expr
+    pub fn comparison(&mut self) -> Result<Expr, ParseError> {
+        self.binary_result(Precedence::Comparison)
+    }
+
+    pub fn term(&mut self) -> Result<Expr, ParseError> {
+        self.binary_result(Precedence::Term)
+    }
+
+    pub fn factor(&mut self) -> Result<Expr, ParseError> {
+        self.binary_result(Precedence::Factor)
     }
 
     fn unary_result(&mut self) -> Result<Expr, ParseError> {
         if self.match_tokens(&[TokenType::Bang, TokenType::Minus]) {
-            let operator = self.previous().clone();
+            let operator = self.take_previous();
             let right = self.unary_result()?;
             return Ok(Expr::Unary(Unary {
                 operator,
                 right: Box::new(right),
             }));
         }
-        self.primary_result()
-    }
 
-    pub fn unary(&mut self) -> Expr {
-        if self.match_tokens(&[TokenType::Bang, TokenType::Minus]) {
-            let operator = self.previous().clone();
-            let right = self.unary();
-            return Expr::Unary(Unary {
-                operator,
-                right: Box::new(right),
-            });
+        if self.match_tokens(&[TokenType::PlusPlus, TokenType::MinusMinus]) {
+            let operator = self.take_previous();
+            let target = self.unary_result()?;
+            let Expr::Variable(variable) = target else {
+                return Err(self.error(
+                    &operator,
+                    ErrorKind::InvalidAssignTarget,
+                    "Invalid increment target.",
+                ));
+            };
+
+            // `++x` desugars to `x = x + 1`
+            return Ok(Expr::Assign(Assign {
+                id: expr::next_id(),
+                name: variable.name.clone(),
+                value: Box::new(Expr::Binary(Binary {
+                    left: Box::new(Expr::Variable(variable)),
+                    operator: Self::step_operator(&operator),
+                    right: Box::new(Expr::Literal(crate::expr::Literal {
+                        value: LiteralValue::Number(1.0),
+                    })),
+                })),
+            }));
         }
-        self.primary()
+        self.postfix_result()
     }
 
-    fn primary_result(&mut self) -> Result<Expr, ParseError> {
-        if self.match_tokens(&[TokenType::False]) {
-            return Ok(Expr::Literal(crate::expr::Literal {
-                value: LiteralValue::Bool(false),
+    fn postfix_result(&mut self) -> Result<Expr, ParseError> {
+        let expr = self.call_result()?;
+
+        if self.match_tokens(&[TokenType::PlusPlus, TokenType::MinusMinus]) {
+            let operator = self.take_previous();
+            let Expr::Variable(variable) = expr else {
+                return Err(self.error(
+                    &operator,
+                    ErrorKind::InvalidAssignTarget,
+                    "Invalid increment target.",
+                ));
+            };
+            return Ok(Expr::Postfix(Postfix {
+                id: expr::next_id(),
+                name: variable.name,
+                operator,
+            }));
+        }
+        Ok(expr)
+    }
+
+    fn call_result(&mut self) -> Result<Expr, ParseError> {
+        let mut expr = self.primary_result()?;
+
+        loop {
+            if self.match_tokens(&[TokenType::LeftParen]) {
+                expr = self.finish_call(expr)?;
+            } else if self.match_tokens(&[TokenType::Dot]) {
+                let name =
+                    self.consume(TokenType::Identifier, "Expect property name after '.'.")?;
+                expr = Expr::Get(Get {
+                    object: Box::new(expr),
+                    name,
+                });
+            } else {
+                break;
+            }
+        }
+        Ok(expr)
+    }
+
+    fn finish_call(&mut self, callee: Expr) -> Result<Expr, ParseError> {
+        let opening_line = self.previous().line;
+        let mut arguments = Vec::new();
+
+        if !self.check(TokenType::RightParen) {
+            loop {
+                self.check_count(arguments.len(), "arguments")?;
+                arguments.push(self.expression_result()?);
+                // A single trailing comma may precede the closing parenthesis
+                if !self.match_tokens(&[TokenType::Comma]) || self.check(TokenType::RightParen) {
+                    break;
+                }
+            }
+        }
+
+        let paren = self.consume_closing_paren("arguments", opening_line)?;
+        Ok(Expr::Call(Call {
+            callee: Box::new(callee),
+            paren,
+            arguments,
+        }))
+    }
+
+    // `+` for `++`, `-` for `--`
+    fn step_operator(operator: &Token) -> Token {
+        let (token_type, lexeme) = match operator.token_type {
+            TokenType::PlusPlus => (TokenType::Plus, "+"),
+            _ => (TokenType::Minus, "-"),
+        };
+        Token::new(token_type, lexeme.to_string(), None, operator.line)
+    }
+
+    pub fn unary(&mut self) -> Result<Expr, ParseError> {
+        self.unary_result()
+    }
+
+    fn primary_result(&mut self) -> Result<Expr, ParseError> {
+        if self.match_tokens(&[TokenType::False]) {
+            return Ok(Expr::Literal(crate::expr::Literal {
+                value: LiteralValue::Bool(false),
             }));
         }
 
@@ -260,102 +1179,142 @@ impl Parser {
             }));
         }
 
-        if self.match_tokens(&[TokenType::Number, TokenType::String]) {
-            let token = self.previous();
-            if let Some(literal) = &token.literal {
-                return Ok(Expr::Literal(crate::expr::Literal {
-                    value: match literal {
-                        crate::token::Literal::Number(n) => LiteralValue::Number(*n),
-                        crate::token::Literal::String(s) => LiteralValue::String(s.clone()),
-                        _ => {
-                            return Err(ParseError {
-                                message: "Unexpected literal type".to_string(),
-                                token: token.clone(),
-                            });
-                        }
-                    },
-                }));
-            }
+        if self.match_tokens(&[TokenType::Number, TokenType::String])
+            && let Some(literal) = self.take_previous().literal
+        {
+            return Ok(Expr::Literal(crate::expr::Literal {
+                value: match literal {
+                    crate::token::Literal::Number(n) => LiteralValue::Number(n),
+                    crate::token::Literal::String(s) => LiteralValue::String(s),
+                },
+            }));
+        }
+
+        if self.match_tokens(&[TokenType::This]) {
+            return Ok(Expr::This(This {
+                id: expr::next_id(),
+                keyword: self.take_previous(),
+            }));
+        }
+
+        if self.match_tokens(&[TokenType::Identifier]) {
+            return Ok(Expr::Variable(Variable {
+                id: expr::next_id(),
+                name: self.take_previous(),
+            }));
+        }
+
+        if self.match_tokens(&[TokenType::Fun]) {
+            let keyword = self.take_previous();
+            self.consume(TokenType::LeftParen, "Expect '(' after 'fun'.")?;
+            let (params, body) = self.function_rest(false)?;
+            return Ok(Expr::Function(expr::Function {
+                keyword,
+                params,
+                body,
+            }));
+        }
+
+        if self.match_tokens(&[TokenType::LeftBrace]) {
+            // `break` cannot leave a loop from inside an expression either
+            let loop_depth = std::mem::take(&mut self.loop_depth);
+            self.block_expression_depth += 1;
+            let block = self.block_expression();
+            self.block_expression_depth -= 1;
+            self.loop_depth = loop_depth;
+            return block;
         }
 
         if self.match_tokens(&[TokenType::LeftParen]) {
+            let opening_line = self.previous().line;
             let expr = self.expression_result()?;
-            self.consume(TokenType::RightParen, "Expect ')' after expression.")?;
+            self.consume_closing_paren("expression", opening_line)?;
             return Ok(Expr::Grouping(crate::expr::Grouping {
                 expression: Box::new(expr),
             }));
         }
 
-        Err(ParseError {
-            message: "Expect expression.".to_string(),
-            token: self.peek().clone(),
-        })
-    }
-
-    pub fn primary(&mut self) -> Expr {
-        if self.match_tokens(&[TokenType::False]) {
-            return Expr::Literal(crate::expr::Literal {
-                value: LiteralValue::Bool(false),
-            });
+        // From a scanner run `with_error_tokens`
+        if self.check(TokenType::Error) {
+            let message = format!("Unexpected character '{}'.", self.peek().lexeme);
+            return Err(self.error(self.peek(), ErrorKind::UnexpectedChar, &message));
         }
 
-        if self.match_tokens(&[TokenType::True]) {
-            return Expr::Literal(crate::expr::Literal {
-                value: LiteralValue::Bool(true),
-            });
-        }
+        Err(self.error(
+            self.peek(),
+            ErrorKind::ExpectedExpression,
+            "Expect expression.",
+        ))
+    }
 
-        if self.match_tokens(&[TokenType::Nil]) {
-            return Expr::Literal(crate::expr::Literal {
-                value: LiteralValue::Nil,
-            });
-        }
+    // Rest of a block expression after its `{`. An expression followed by
+    // `;` is an expression statement; one followed by `}` is the value.
+    fn block_expression(&mut self) -> Result<Expr, ParseError> {
+        let mut statements = Vec::new();
+        let mut value = None;
 
-        if self.match_tokens(&[TokenType::Number, TokenType::String]) {
-            let token = self.previous();
-            if let Some(literal) = &token.literal {
-                return Expr::Literal(crate::expr::Literal {
-                    value: match literal {
-                        crate::token::Literal::Number(n) => LiteralValue::Number(*n),
-                        crate::token::Literal::String(s) => LiteralValue::String(s.clone()),
-                        _ => panic!("Unexpected literal type"),
-                    },
-                });
+        while !self.check(TokenType::RightBrace) && !self.is_at_end() {
+            if self.starts_statement() {
+                statements.push(self.declaration()?);
+                continue;
+            }
+            let expression = self.expression_result()?;
+            if self.match_tokens(&[TokenType::Semicolon]) {
+                statements.push(Stmt::Expression(stmt::Expression { expression }));
+            } else {
+                value = Some(Box::new(expression));
+                break;
             }
         }
 
-        if self.match_tokens(&[TokenType::LeftParen]) {
-            let expr = self.expression();
-            self.consume(TokenType::RightParen, "Expect ')' after expression.");
-            return Expr::Grouping(crate::expr::Grouping {
-                expression: Box::new(expr),
-            });
-        }
+        self.consume(TokenType::RightBrace, "Expect '}' after block expression.")?;
+        Ok(Expr::Block(expr::Block { statements, value }))
+    }
 
-        panic!("Expect expression.");
+    pub fn primary(&mut self) -> Result<Expr, ParseError> {
+        self.primary_result()
     }
 
     // Result version of consume
-    fn consume(&mut self, token_type: TokenType, message: &str) -> Result<&Token, ParseError> {
+    fn consume(&mut self, token_type: TokenType, message: &str) -> Result<Token, ParseError> {
         if self.check(token_type) {
-            Ok(self.advance())
+            self.advance();
+            Ok(self.take_previous())
         } else {
-            Err(ParseError {
-                message: message.to_string(),
-                token: self.peek().clone(),
-            })
+            Err(self.error(self.peek(), ErrorKind::ExpectedToken, message))
         }
     }
 
+    // The `)` closing a group or call, whose error points back at the `(`
+    // since the two can be far apart
+    fn consume_closing_paren(
+        &mut self,
+        after: &str,
+        opening_line: usize,
+    ) -> Result<Token, ParseError> {
+        self.consume(
+            TokenType::RightParen,
+            &format!(
+                "Expect ')' after {} to match '(' on line {}.",
+                after, opening_line
+            ),
+        )
+    }
+
     // Error reporting
-    fn error(&self, token: &Token, message: &str) -> ParseError {
+    fn error(&self, token: &Token, kind: ErrorKind, message: &str) -> ParseError {
         ParseError {
+            kind,
             message: message.to_string(),
             token: token.clone(),
         }
     }
 
-    // Synchronization for error recovery
+    /// Skip to the next statement boundary after an error
+    ///
+    /// Stops just after a `;` or just before a keyword that starts a
+    /// statement. `parse_program` calls it after every error; the
+    /// single-expression entry points stop at the first error instead.
     pub fn synchronize(&mut self) {
         self.advance();
 
@@ -372,7 +1331,10 @@ impl Parser {
                 | TokenType::If
                 | TokenType::While
                 | TokenType::Print
-                | TokenType::Return => return,
+                | TokenType::Return
+                | TokenType::Break
+                | TokenType::Continue
+                | TokenType::Switch => return,
                 _ => {}
             }
 
@@ -386,11 +1348,13 @@ impl Parser {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::{
-        ast_printer::{self, AstPrinter},
-        expr, parser,
-    };
-    use std::vec;
+    use crate::ast_printer::AstPrinter;
+    use crate::scanner::Scanner;
+
+    fn parse_source(source: &str) -> Result<Expr, ParseError> {
+        let tokens = Scanner::new(source.to_string()).scan_tokens();
+        Parser::new(tokens).parse_expression()
+    }
 
     #[test]
     fn test_make_parser() {
@@ -401,13 +1365,9 @@ mod tests {
                 lexeme: "42".to_string(),
                 literal: None,
                 line: 1,
+                source_name: None,
             },
-            Token {
-                token_type: TokenType::Eof,
-                lexeme: "".to_string(),
-                literal: None,
-                line: 1,
-            },
+            Token::eof(1),
         ];
 
         // When
@@ -418,6 +1378,54 @@ mod tests {
         assert_eq!(parser.peek().token_type, TokenType::Number);
     }
 
+    #[test]
+    fn test_from_source_scans_then_parses() {
+        // Given
+        let mut parser = Parser::from_source("1 + 2").unwrap();
+
+        // When
+        let expr = parser.parse().unwrap();
+
+        // Then
+        assert_eq!(AstPrinter::new().print(&expr), "(+ 1 2)");
+    }
+
+    #[test]
+    fn test_from_source_returns_scan_errors() {
+        let Err(errors) = Parser::from_source("1 + @") else {
+            panic!("Expected scan errors");
+        };
+
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].message, "Unexpected character.");
+    }
+
+    #[test]
+    fn test_parser_with_only_eof() {
+        // Given
+        let mut parser = Parser::new(vec![Token::eof(1)]);
+
+        // When: nothing has been consumed yet
+        parser.synchronize();
+
+        // Then
+        assert_eq!(parser.previous().token_type, TokenType::Eof);
+        assert_eq!(parser.advance().token_type, TokenType::Eof);
+        assert_eq!(parser.parse_program().unwrap(), vec![]);
+    }
+
+    #[test]
+    fn test_parser_with_no_tokens() {
+        let mut parser = Parser::new(vec![]);
+
+        assert_eq!(parser.previous().token_type, TokenType::Eof);
+        let error = parser.parse().unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "[line 1] Error at end: Expect expression."
+        );
+    }
+
     #[test]
     fn test_eqality_bang_equal() {
         // Given
@@ -428,29 +1436,27 @@ mod tests {
                 lexeme: "3".to_string(),
                 literal: Some(crate::token::Literal::Number(3.0)),
                 line: 0,
+                source_name: None,
             },
             Token {
                 token_type: TokenType::BangEqual,
                 lexeme: "!=".to_string(),
                 literal: None,
                 line: 0,
+                source_name: None,
             },
             Token {
                 token_type: TokenType::Number,
                 lexeme: "5".to_string(),
                 literal: Some(crate::token::Literal::Number(5.0)),
                 line: 0,
+                source_name: None,
             },
-            Token {
-                token_type: TokenType::Eof,
-                lexeme: "".to_string(),
-                literal: None,
-                line: 1,
-            },
+            Token::eof(1),
         ]);
 
         // When
-        let expr: Expr = parser.expression();
+        let expr: Expr = parser.expression().unwrap();
         // Then
         let printer = AstPrinter::new();
         assert_eq!(printer.print(&expr), "(!= 3 5)");
@@ -466,28 +1472,26 @@ mod tests {
                 lexeme: "3".to_string(),
                 literal: Some(crate::token::Literal::Number(3.0)),
                 line: 1,
+                source_name: None,
             },
             Token {
                 token_type: TokenType::EqualEqual,
                 lexeme: "==".to_string(),
                 literal: None,
                 line: 1,
+                source_name: None,
             },
             Token {
                 token_type: TokenType::Number,
                 lexeme: "3".to_string(),
                 literal: Some(crate::token::Literal::Number(3.0)),
                 line: 1,
+                source_name: None,
             },
-            Token {
-                token_type: TokenType::Eof,
-                lexeme: "".to_string(),
-                literal: None,
-                line: 1,
-            },
+            Token::eof(1),
         ]);
         // When
-        let expr = parser.equality();
+        let expr = parser.equality().unwrap();
 
         // Then
         let printer = AstPrinter::new();
@@ -503,29 +1507,27 @@ mod tests {
                 lexeme: "5".to_string(),
                 literal: Some(crate::token::Literal::Number(5.0)),
                 line: 1,
+                source_name: None,
             },
             Token {
                 token_type: TokenType::Greater,
                 lexeme: ">".to_string(),
                 literal: None,
                 line: 1,
+                source_name: None,
             },
             Token {
                 token_type: TokenType::Number,
                 lexeme: "3".to_string(),
                 literal: Some(crate::token::Literal::Number(3.0)),
                 line: 1,
+                source_name: None,
             },
-            Token {
-                token_type: TokenType::Eof,
-                lexeme: "".to_string(),
-                literal: None,
-                line: 1,
-            },
+            Token::eof(1),
         ]);
 
         // When
-        let expr = parser.comparison();
+        let expr = parser.comparison().unwrap();
 
         // Then
         let printer = AstPrinter::new();
@@ -541,29 +1543,27 @@ mod tests {
                 lexeme: "5".to_string(),
                 literal: Some(crate::token::Literal::Number(5.0)),
                 line: 1,
+                source_name: None,
             },
             Token {
                 token_type: TokenType::GreaterEqual,
                 lexeme: ">=".to_string(),
                 literal: None,
                 line: 1,
+                source_name: None,
             },
             Token {
                 token_type: TokenType::Number,
                 lexeme: "5".to_string(),
                 literal: Some(crate::token::Literal::Number(5.0)),
                 line: 1,
+                source_name: None,
             },
-            Token {
-                token_type: TokenType::Eof,
-                lexeme: "".to_string(),
-                literal: None,
-                line: 1,
-            },
+            Token::eof(1),
         ]);
 
         // When
-        let expr = parser.comparison();
+        let expr = parser.comparison().unwrap();
 
         // Then
         let printer = AstPrinter::new();
@@ -579,29 +1579,27 @@ mod tests {
                 lexeme: "3".to_string(),
                 literal: Some(crate::token::Literal::Number(3.0)),
                 line: 1,
+                source_name: None,
             },
             Token {
                 token_type: TokenType::Less,
                 lexeme: "<".to_string(),
                 literal: None,
                 line: 1,
+                source_name: None,
             },
             Token {
                 token_type: TokenType::Number,
                 lexeme: "5".to_string(),
                 literal: Some(crate::token::Literal::Number(5.0)),
                 line: 1,
+                source_name: None,
             },
-            Token {
-                lexeme: "".to_string(),
-                token_type: TokenType::Eof,
-                literal: None,
-                line: 1,
-            },
+            Token::eof(1),
         ]);
 
         // When
-        let expr = parser.comparison();
+        let expr = parser.comparison().unwrap();
 
         // Then
         let printer = AstPrinter::new();
@@ -618,29 +1616,27 @@ mod tests {
                 lexeme: "3".to_string(),
                 literal: Some(crate::token::Literal::Number(3.0)),
                 line: 1,
+                source_name: None,
             },
             Token {
                 token_type: TokenType::Less,
                 lexeme: "<=".to_string(),
                 literal: None,
                 line: 1,
+                source_name: None,
             },
             Token {
                 token_type: TokenType::Number,
                 lexeme: "3".to_string(),
                 literal: Some(crate::token::Literal::Number(3.0)),
                 line: 1,
+                source_name: None,
             },
-            Token {
-                lexeme: "".to_string(),
-                token_type: TokenType::Eof,
-                literal: None,
-                line: 1,
-            },
+            Token::eof(1),
         ]);
 
         // When
-        let expr = parser.comparison();
+        let expr = parser.comparison().unwrap();
 
         // Then
         let printer = AstPrinter::new();
@@ -657,29 +1653,27 @@ mod tests {
                 lexeme: "5".to_string(),
                 literal: Some(crate::token::Literal::Number(5.0)),
                 line: 1,
+                source_name: None,
             },
             Token {
                 token_type: TokenType::Minus,
                 lexeme: "-".to_string(),
                 literal: None,
                 line: 1,
+                source_name: None,
             },
             Token {
                 token_type: TokenType::Number,
                 lexeme: "3".to_string(),
                 literal: Some(crate::token::Literal::Number(3.0)),
                 line: 1,
+                source_name: None,
             },
-            Token {
-                lexeme: "".to_string(),
-                token_type: TokenType::Eof,
-                literal: None,
-                line: 1,
-            },
+            Token::eof(1),
         ]);
 
         // When
-        let expr = parser.term();
+        let expr = parser.term().unwrap();
 
         // Then
         let printer = AstPrinter::new();
@@ -696,29 +1690,27 @@ mod tests {
                 lexeme: "5".to_string(),
                 literal: Some(crate::token::Literal::Number(5.0)),
                 line: 1,
+                source_name: None,
             },
             Token {
                 token_type: TokenType::Plus,
                 lexeme: "+".to_string(),
                 literal: None,
                 line: 1,
+                source_name: None,
             },
             Token {
                 token_type: TokenType::Number,
                 lexeme: "3".to_string(),
                 literal: Some(crate::token::Literal::Number(3.0)),
                 line: 1,
+                source_name: None,
             },
-            Token {
-                lexeme: "".to_string(),
-                token_type: TokenType::Eof,
-                literal: None,
-                line: 1,
-            },
+            Token::eof(1),
         ]);
 
         // When
-        let expr = parser.term();
+        let expr = parser.term().unwrap();
 
         // Then
         let printer = AstPrinter::new();
@@ -735,29 +1727,27 @@ mod tests {
                 lexeme: "6".to_string(),
                 literal: Some(crate::token::Literal::Number(6.0)),
                 line: 1,
+                source_name: None,
             },
             Token {
                 token_type: TokenType::Slash,
                 lexeme: "/".to_string(),
                 literal: None,
                 line: 1,
+                source_name: None,
             },
             Token {
                 token_type: TokenType::Number,
                 lexeme: "2".to_string(),
                 literal: Some(crate::token::Literal::Number(2.0)),
                 line: 1,
+                source_name: None,
             },
-            Token {
-                lexeme: "".to_string(),
-                token_type: TokenType::Eof,
-                literal: None,
-                line: 1,
-            },
+            Token::eof(1),
         ]);
 
         // When
-        let expr = parser.factor();
+        let expr = parser.factor().unwrap();
 
         // Then
         let printer = AstPrinter::new();
@@ -774,29 +1764,27 @@ mod tests {
                 lexeme: "3".to_string(),
                 literal: Some(crate::token::Literal::Number(3.0)),
                 line: 1,
+                source_name: None,
             },
             Token {
                 token_type: TokenType::Star,
                 lexeme: "*".to_string(),
                 literal: None,
                 line: 1,
+                source_name: None,
             },
             Token {
                 token_type: TokenType::Number,
                 lexeme: "4".to_string(),
                 literal: Some(crate::token::Literal::Number(4.0)),
                 line: 1,
+                source_name: None,
             },
-            Token {
-                lexeme: "".to_string(),
-                token_type: TokenType::Eof,
-                literal: None,
-                line: 1,
-            },
+            Token::eof(1),
         ]);
 
         // When
-        let expr = parser.factor();
+        let expr = parser.factor().unwrap();
 
         // Then
         let printer = AstPrinter::new();
@@ -813,23 +1801,20 @@ mod tests {
                 lexeme: "!".to_string(),
                 literal: None,
                 line: 1,
+                source_name: None,
             },
             Token {
                 token_type: TokenType::True,
                 lexeme: "true".to_string(),
                 literal: None,
                 line: 1,
+                source_name: None,
             },
-            Token {
-                lexeme: "".to_string(),
-                token_type: TokenType::Eof,
-                literal: None,
-                line: 1,
-            },
+            Token::eof(1),
         ]);
 
         // When
-        let expr = parser.unary();
+        let expr = parser.unary().unwrap();
 
         // Then
         let printer = AstPrinter::new();
@@ -846,23 +1831,20 @@ mod tests {
                 lexeme: "-".to_string(),
                 literal: None,
                 line: 1,
+                source_name: None,
             },
             Token {
                 token_type: TokenType::Number,
                 lexeme: "5".to_string(),
                 literal: Some(crate::token::Literal::Number(5.0)),
                 line: 1,
+                source_name: None,
             },
-            Token {
-                lexeme: "".to_string(),
-                token_type: TokenType::Eof,
-                literal: None,
-                line: 1,
-            },
+            Token::eof(1),
         ]);
 
         // When
-        let expr = parser.unary();
+        let expr = parser.unary().unwrap();
 
         // Then
         let printer = AstPrinter::new();
@@ -878,17 +1860,13 @@ mod tests {
                 lexeme: "42".to_string(),
                 literal: Some(crate::token::Literal::Number(42.0)),
                 line: 1,
+                source_name: None,
             },
-            Token {
-                token_type: TokenType::Eof,
-                lexeme: "".to_string(),
-                literal: None,
-                line: 1,
-            },
+            Token::eof(1),
         ]);
 
         // When
-        let expr = parser.primary();
+        let expr = parser.primary().unwrap();
 
         // Then
         let printer = AstPrinter::new();
@@ -904,17 +1882,13 @@ mod tests {
                 lexeme: "\"hello\"".to_string(),
                 literal: Some(crate::token::Literal::String("hello".to_string())),
                 line: 1,
+                source_name: None,
             },
-            Token {
-                token_type: TokenType::Eof,
-                lexeme: "".to_string(),
-                literal: None,
-                line: 1,
-            },
+            Token::eof(1),
         ]);
 
         // When
-        let expr = parser.primary();
+        let expr = parser.primary().unwrap();
 
         // Then
         let printer = AstPrinter::new();
@@ -931,35 +1905,53 @@ mod tests {
                 lexeme: "(".to_string(),
                 literal: None,
                 line: 1,
+                source_name: None,
             },
             Token {
                 token_type: TokenType::Number,
                 lexeme: "5".to_string(),
                 literal: Some(crate::token::Literal::Number(5.0)),
                 line: 1,
+                source_name: None,
             },
             Token {
                 token_type: TokenType::RightParen,
                 lexeme: ")".to_string(),
                 literal: None,
                 line: 1,
+                source_name: None,
             },
-            Token {
-                token_type: TokenType::Eof,
-                lexeme: "".to_string(),
-                literal: None,
-                line: 1,
-            },
+            Token::eof(1),
         ]);
 
         // When
-        let expr = parser.primary();
+        let expr = parser.primary().unwrap();
 
         // Then
         let printer = AstPrinter::new();
         assert_eq!(printer.print(&expr), "(group 5)");
     }
 
+    #[test]
+    fn test_primary_unclosed_group_is_parse_error() {
+        // Given
+        let mut parser = Parser::from_source("(5").unwrap();
+
+        // When
+        let error = parser.primary().unwrap_err();
+
+        // Then
+        assert_eq!(error.kind, ErrorKind::ExpectedToken);
+        assert_eq!(error.token.token_type, TokenType::Eof);
+    }
+
+    #[test]
+    fn test_primary_without_expression_is_parse_error() {
+        let mut parser = Parser::from_source(";").unwrap();
+
+        assert_eq!(parser.primary().unwrap_err().message, "Expect expression.");
+    }
+
     #[test]
     fn test_complex_expression() {
         // Given
@@ -970,62 +1962,1004 @@ mod tests {
                 lexeme: "-".to_string(),
                 literal: None,
                 line: 1,
+                source_name: None,
             },
             Token {
                 token_type: TokenType::Number,
                 lexeme: "5".to_string(),
                 literal: Some(crate::token::Literal::Number(5.0)),
                 line: 1,
+                source_name: None,
             },
             Token {
                 token_type: TokenType::Plus,
                 lexeme: "+".to_string(),
                 literal: None,
                 line: 1,
+                source_name: None,
             },
             Token {
                 token_type: TokenType::Number,
                 lexeme: "3".to_string(),
                 literal: Some(crate::token::Literal::Number(3.0)),
                 line: 1,
+                source_name: None,
             },
             Token {
                 token_type: TokenType::Star,
                 lexeme: "*".to_string(),
                 literal: None,
                 line: 1,
+                source_name: None,
             },
             Token {
                 token_type: TokenType::Number,
                 lexeme: "2".to_string(),
                 literal: Some(crate::token::Literal::Number(2.0)),
                 line: 1,
+                source_name: None,
             },
             Token {
                 token_type: TokenType::EqualEqual,
                 lexeme: "==".to_string(),
                 literal: None,
                 line: 1,
+                source_name: None,
             },
             Token {
                 token_type: TokenType::Number,
                 lexeme: "1".to_string(),
                 literal: Some(crate::token::Literal::Number(1.0)),
                 line: 1,
+                source_name: None,
             },
-            Token {
-                token_type: TokenType::Eof,
-                lexeme: "".to_string(),
-                literal: None,
-                line: 1,
-            },
+            Token::eof(1),
         ]);
 
         // When
-        let expr = parser.expression();
+        let expr = parser.expression().unwrap();
 
         // Then
         let printer = AstPrinter::new();
         assert_eq!(printer.print(&expr), "(== (+ (- 5) (* 3 2)) 1)");
     }
+
+    #[test]
+    fn test_assignment() {
+        // Given
+        // x = 1
+        let expr = parse_source("x = 1").unwrap();
+
+        // Then
+        let printer = AstPrinter::new();
+        assert_eq!(printer.print(&expr), "(= x 1)");
+    }
+
+    #[test]
+    fn test_compound_assignment_desugars() {
+        let cases = [
+            ("x += 1", "(= x (+ x 1))"),
+            ("x -= 1", "(= x (- x 1))"),
+            ("x *= 2", "(= x (* x 2))"),
+            ("x /= 2", "(= x (/ x 2))"),
+        ];
+
+        let printer = AstPrinter::new();
+        for (source, expected) in cases {
+            let expr = parse_source(source).unwrap();
+            assert_eq!(printer.print(&expr), expected, "source: {}", source);
+        }
+    }
+
+    #[test]
+    fn test_compound_property_assignment_keeps_operator() {
+        // Given
+        // o.x += 1
+        let expr = parse_source("o.x += 1").unwrap();
+
+        // Then: the object is not duplicated into a `Get`
+        let printer = AstPrinter::new();
+        assert_eq!(printer.print(&expr), "(+= (. o x) 1)");
+    }
+
+    #[test]
+    fn test_compound_assignment_is_right_associative() {
+        // Given
+        // a += b = 2
+        let expr = parse_source("a += b = 2").unwrap();
+
+        // Then
+        let printer = AstPrinter::new();
+        assert_eq!(printer.print(&expr), "(= a (+ a (= b 2)))");
+    }
+
+    #[test]
+    fn test_compound_assignment_invalid_target() {
+        // Given
+        // 1 += 2
+        let error = parse_source("1 += 2").unwrap_err();
+
+        // Then
+        assert_eq!(error.message, "Invalid assignment target.");
+        assert_eq!(error.token.token_type, TokenType::PlusEqual);
+    }
+
+    #[test]
+    fn test_prefix_increment_desugars() {
+        let printer = AstPrinter::new();
+        assert_eq!(
+            printer.print(&parse_source("++x").unwrap()),
+            "(= x (+ x 1))"
+        );
+        assert_eq!(
+            printer.print(&parse_source("--x").unwrap()),
+            "(= x (- x 1))"
+        );
+    }
+
+    #[test]
+    fn test_postfix_increment() {
+        let printer = AstPrinter::new();
+        assert_eq!(printer.print(&parse_source("x++").unwrap()), "(x ++)");
+        assert_eq!(printer.print(&parse_source("x--").unwrap()), "(x --)");
+    }
+
+    #[test]
+    fn test_increment_invalid_target() {
+        let error = parse_source("++1").unwrap_err();
+        assert_eq!(error.message, "Invalid increment target.");
+
+        let error = parse_source("(x)++").unwrap_err();
+        assert_eq!(error.message, "Invalid increment target.");
+    }
+
+    #[test]
+    fn test_call_without_arguments() {
+        let expr = parse_source("f()").unwrap();
+
+        let printer = AstPrinter::new();
+        assert_eq!(printer.print(&expr), "(call f)");
+    }
+
+    #[test]
+    fn test_call_with_arguments() {
+        let expr = parse_source("f(1, x + 2)(3)").unwrap();
+
+        let printer = AstPrinter::new();
+        assert_eq!(printer.print(&expr), "(call (call f 1 (+ x 2)) 3)");
+    }
+
+    #[test]
+    fn test_call_missing_close_paren() {
+        let error = parse_source("f(1, 2").unwrap_err();
+        assert_eq!(
+            error.message,
+            "Expect ')' after arguments to match '(' on line 1."
+        );
+    }
+
+    #[test]
+    fn test_modulo_operator() {
+        let expr = parse_source("7 % 3").unwrap();
+
+        let printer = AstPrinter::new();
+        assert_eq!(printer.print(&expr), "(% 7 3)");
+    }
+
+    #[test]
+    fn test_modulo_precedence_matches_factor() {
+        let expr = parse_source("1 + 6 % 4 * 2").unwrap();
+
+        let printer = AstPrinter::new();
+        assert_eq!(printer.print(&expr), "(+ 1 (* (% 6 4) 2))");
+    }
+
+    #[test]
+    fn test_binary_levels_are_left_associative() {
+        let expr = parse_source("1 - 2 - 3 == 4 == 5").unwrap();
+
+        let printer = AstPrinter::new();
+        assert_eq!(printer.print(&expr), "(== (== (- (- 1 2) 3) 4) 5)");
+    }
+
+    #[test]
+    fn test_logical_operators() {
+        let expr = parse_source("a or b and c").unwrap();
+
+        let printer = AstPrinter::new();
+        assert_eq!(printer.print(&expr), "(or a (and b c))");
+    }
+
+    #[test]
+    fn test_coalesce_precedence_between_or_and_and() {
+        let printer = AstPrinter::new();
+
+        let expr = parse_source("a or b ?? c").unwrap();
+        assert_eq!(printer.print(&expr), "(or a (?? b c))");
+
+        let expr = parse_source("a ?? b and c").unwrap();
+        assert_eq!(printer.print(&expr), "(?? a (and b c))");
+    }
+
+    // ===== Statements =====
+
+    fn parse_program_source(source: &str) -> Result<Vec<Stmt>, Vec<ParseError>> {
+        let tokens = Scanner::new(source.to_string()).scan_tokens();
+        Parser::new(tokens).parse_program()
+    }
+
+    #[test]
+    fn test_switch_statement() {
+        let statements =
+            parse_program_source("switch (x) { case 1: case 2: print 2; default: print 0; }")
+                .unwrap();
+
+        let Stmt::Switch(switch) = &statements[0] else {
+            panic!("Expected switch, got {:?}", statements[0]);
+        };
+        assert_eq!(switch.cases.len(), 2);
+        assert!(switch.cases[0].1.is_empty());
+        assert_eq!(switch.cases[1].1.len(), 1);
+        assert_eq!(switch.default.as_ref().map(Vec::len), Some(1));
+    }
+
+    #[test]
+    fn test_switch_errors() {
+        for (source, message) in [
+            (
+                "switch (x) { print 1; }",
+                "Expect 'case' or 'default' in switch body.",
+            ),
+            (
+                "switch (x) { case 1 print 1; }",
+                "Expect ':' after case value.",
+            ),
+            (
+                "switch (x) { default: default: }",
+                "A switch can have only one default.",
+            ),
+            (
+                "switch (x) { case 1: print 1;",
+                "Expect '}' after switch body.",
+            ),
+        ] {
+            let errors = parse_program_source(source).unwrap_err();
+            assert_eq!(errors[0].message, message, "{}", source);
+        }
+    }
+
+    #[test]
+    fn test_fallthrough_ends_case() {
+        let statements =
+            parse_program_source("switch (x) { case 1: print 1; fallthrough; case 2: }").unwrap();
+
+        let Stmt::Switch(switch) = &statements[0] else {
+            panic!("Expected switch, got {:?}", statements[0]);
+        };
+        assert!(matches!(switch.cases[0].1[1], Stmt::Fallthrough(_)));
+    }
+
+    #[test]
+    fn test_fallthrough_errors() {
+        for (source, message) in [
+            ("fallthrough;", "'fallthrough' can only end a case."),
+            (
+                "switch (x) { case 1: if (y) fallthrough; case 2: }",
+                "'fallthrough' can only end a case.",
+            ),
+            (
+                "switch (x) { case 1: fallthrough; print 1; case 2: }",
+                "'fallthrough' must be followed by another case.",
+            ),
+            (
+                "switch (x) { case 1: fallthrough; }",
+                "'fallthrough' must be followed by another case.",
+            ),
+            (
+                "switch (x) { case 1: fallthrough; default: }",
+                "'fallthrough' must be followed by another case.",
+            ),
+            (
+                "switch (x) { default: fallthrough; case 1: }",
+                "'fallthrough' can only end a case.",
+            ),
+            (
+                "var a = { fallthrough; 1 };",
+                "'fallthrough' can only end a case.",
+            ),
+        ] {
+            let errors = parse_program_source(source).unwrap_err();
+
+            assert_eq!(errors[0].kind, ErrorKind::InvalidFallthrough, "{}", source);
+            assert_eq!(errors[0].message, message, "{}", source);
+        }
+    }
+
+    #[test]
+    fn test_lambda_expression() {
+        let expr = parse_source("fun (a, b) { return a + b; }").unwrap();
+
+        let Expr::Function(function) = &expr else {
+            panic!("Expected lambda, got {:?}", expr);
+        };
+        assert_eq!(function.params.len(), 2);
+        assert_eq!(function.body.len(), 1);
+        assert_eq!(AstPrinter::new().print(&expr), "(fun (a b))");
+    }
+
+    #[test]
+    fn test_lambda_as_call_argument() {
+        let expr = parse_source("map(fun (x) { return x; }, list)").unwrap();
+
+        let printer = AstPrinter::new();
+        assert_eq!(printer.print(&expr), "(call map (fun (x)) list)");
+    }
+
+    #[test]
+    fn test_fun_with_name_is_declaration() {
+        let statements = parse_program_source("fun f() {} fun () {};").unwrap();
+
+        assert!(matches!(statements[0], Stmt::Function(_)));
+        assert!(matches!(
+            &statements[1],
+            Stmt::Expression(stmt::Expression {
+                expression: Expr::Function(_)
+            })
+        ));
+    }
+
+    #[test]
+    fn test_for_desugars_to_while() {
+        let statements = parse_program_source("for (var i = 0; i < 3; i++) print i;").unwrap();
+
+        // { var i = 0; while (i < 3) { print i; i++; } }
+        let Stmt::Block(block) = &statements[0] else {
+            panic!("Expected block, got {:?}", statements[0]);
+        };
+        assert!(matches!(block.statements[0], Stmt::Var(_)));
+        assert!(matches!(block.statements[1], Stmt::While(_)));
+    }
+
+    #[test]
+    fn test_parse_program_reports_every_error() {
+        let errors = parse_program_source("var = 1; print 2; var x = ;").unwrap_err();
+
+        assert_eq!(errors.len(), 2);
+        assert_eq!(errors[0].message, "Expect variable name.");
+        assert_eq!(errors[1].message, "Expect expression.");
+    }
+
+    #[test]
+    fn test_class_with_getter() {
+        let statements =
+            parse_program_source("class C { area { return 1; } scale(k) { return k; } }").unwrap();
+
+        let Stmt::Class(class) = &statements[0] else {
+            panic!("Expected class, got {:?}", statements[0]);
+        };
+        assert_eq!(class.getters.len(), 1);
+        assert_eq!(class.getters[0].name.lexeme, "area");
+        assert_eq!(class.methods.len(), 1);
+        assert_eq!(class.methods[0].name.lexeme, "scale");
+    }
+
+    #[test]
+    fn test_initializer_cannot_return_a_value() {
+        // Given
+        // class C { init() { return 1; } }
+        let errors = parse_program_source("class C { init() { return 1; } }").unwrap_err();
+
+        // Then
+        assert_eq!(
+            errors[0].message,
+            "Can't return a value from an initializer."
+        );
+        assert_eq!(errors[0].kind, ErrorKind::InvalidReturn);
+    }
+
+    #[test]
+    fn test_initializer_may_return_early() {
+        // A bare `return;` is fine, and so is a value returned from a function nested in `init`
+        let source =
+            "class C { init() { fun f() { return 1; } return; } class init() { return 2; } }";
+
+        assert!(parse_program_source(source).is_ok());
+    }
+
+    fn parser_for(source: &str) -> Parser {
+        Parser::new(Scanner::new(source.to_string()).scan_tokens())
+    }
+
+    #[test]
+    fn test_synchronize_skips_past_semicolon() {
+        // Given: the error is at `2`, in the middle of the statement
+        let mut parser = parser_for("print 1 2 3; print 4;");
+        assert!(parser.declaration().is_err());
+
+        // When
+        parser.synchronize();
+
+        // Then
+        assert_eq!(parser.peek().token_type, TokenType::Print);
+        assert!(matches!(parser.declaration(), Ok(Stmt::Print(_))));
+    }
+
+    #[test]
+    fn test_synchronize_when_bad_token_is_semicolon() {
+        let mut parser = parser_for("var x = ; var y = 2;");
+        let error = parser.declaration().unwrap_err();
+        assert_eq!(error.token.lexeme, ";");
+
+        parser.synchronize();
+
+        let Ok(Stmt::Var(var)) = parser.declaration() else {
+            panic!("Expected the next declaration to parse");
+        };
+        assert_eq!(var.name.lexeme, "y");
+    }
+
+    #[test]
+    fn test_synchronize_stops_before_statement_keyword() {
+        let mut parser = parser_for("x = 1 2 if (x) print x;");
+        assert!(parser.declaration().is_err());
+
+        parser.synchronize();
+
+        assert_eq!(parser.peek().token_type, TokenType::If);
+    }
+
+    #[test]
+    fn test_parse_program_keeps_parsing_after_error() {
+        let mut parser = parser_for("var x = ; print 1 +; var ok = 1; var = 2;");
+
+        let errors = parser.parse_program().unwrap_err();
+
+        // `var ok = 1;` parsed cleanly between the errors
+        let messages: Vec<&str> = errors.iter().map(|e| e.message.as_str()).collect();
+        assert_eq!(
+            messages,
+            vec![
+                "Expect expression.",
+                "Expect expression.",
+                "Expect variable name."
+            ]
+        );
+    }
+
+    #[test]
+    fn test_recovered_error_sets_had_error() {
+        // Given
+        let tokens = Scanner::new("var = 1; print 2;".to_string()).scan_tokens();
+        let mut parser = Parser::new(tokens);
+
+        // When
+        let result = parser.parse_program();
+
+        // Then: parsing went on past the error, and the flag remembers it
+        assert_eq!(result.unwrap_err().len(), 1);
+        assert!(parser.is_at_end());
+        assert!(parser.had_error());
+    }
+
+    #[test]
+    fn test_clean_parse_leaves_had_error_false() {
+        let tokens = Scanner::new("if (x = 1) print x;".to_string()).scan_tokens();
+        let mut parser = Parser::new(tokens);
+
+        parser.parse_program().unwrap();
+
+        // A warning is not an error
+        assert_eq!(parser.warnings().len(), 1);
+        assert!(!parser.had_error());
+    }
+
+    #[test]
+    fn test_failed_expression_parse_sets_had_error() {
+        let tokens = Scanner::new("1 +".to_string()).scan_tokens();
+        let mut parser = Parser::new(tokens);
+
+        assert!(parser.parse_expression().is_err());
+        assert!(parser.had_error());
+    }
+
+    #[test]
+    fn test_assignment_in_condition_warns() {
+        // Given
+        let tokens = Scanner::new("if (x = 1) print x;".to_string()).scan_tokens();
+        let mut parser = Parser::new(tokens);
+
+        // When
+        let statements = parser.parse_program().unwrap();
+
+        // Then: still parsed as an assignment, with a suggestion
+        assert_eq!(statements.len(), 1);
+        assert_eq!(parser.warnings().len(), 1);
+        assert_eq!(parser.warnings()[0].token.lexeme, "x");
+        assert!(parser.warnings()[0].message.contains("'=='"));
+    }
+
+    #[test]
+    fn test_assignment_statement_does_not_warn() {
+        let tokens = Scanner::new("x = 1; while (x == 1) x = 2;".to_string()).scan_tokens();
+        let mut parser = Parser::new(tokens);
+
+        parser.parse_program().unwrap();
+        assert!(parser.warnings().is_empty());
+    }
+
+    #[test]
+    fn test_parenthesized_assignment_in_condition_does_not_warn() {
+        let tokens = Scanner::new("if ((x = next())) print x;".to_string()).scan_tokens();
+        let mut parser = Parser::new(tokens);
+
+        parser.parse_program().unwrap();
+        assert!(parser.warnings().is_empty());
+    }
+
+    // ===== Lookahead =====
+
+    #[test]
+    fn test_peek_at_in_range() {
+        let tokens = Scanner::new("fun (a)".to_string()).scan_tokens();
+        let parser = Parser::new(tokens);
+
+        assert_eq!(parser.peek_at(0).token_type, TokenType::Fun);
+        assert_eq!(parser.peek_at(1).token_type, TokenType::LeftParen);
+        assert_eq!(parser.peek_at(2).lexeme, "a");
+    }
+
+    #[test]
+    fn test_peek_at_past_end_returns_eof() {
+        let tokens = Scanner::new("a".to_string()).scan_tokens();
+        let parser = Parser::new(tokens);
+
+        assert_eq!(parser.peek_at(1).token_type, TokenType::Eof);
+        assert_eq!(parser.peek_at(100).token_type, TokenType::Eof);
+    }
+
+    #[test]
+    fn test_stream_without_eof_gets_one() {
+        let parser = Parser::new(Vec::new());
+
+        assert!(parser.is_at_end());
+        assert_eq!(parser.peek_at(3).token_type, TokenType::Eof);
+    }
+
+    // ===== Chained comparisons =====
+
+    #[test]
+    fn test_chained_comparison_is_error() {
+        let error = parse_source("1 < 2 < 3").unwrap_err();
+
+        assert_eq!(
+            error.message,
+            "Chained comparison is not allowed; use 'and'."
+        );
+        assert_eq!(error.token.lexeme, "<");
+    }
+
+    #[test]
+    fn test_chained_comparison_inside_equality_is_error() {
+        assert!(parse_source("a == 1 <= 2 >= 3").is_err());
+    }
+
+    #[test]
+    fn test_comparisons_joined_by_and_parse() {
+        let expr = parse_source("1 < 2 and 2 < 3").unwrap();
+
+        let printer = AstPrinter::new();
+        assert_eq!(printer.print(&expr), "(and (< 1 2) (< 2 3))");
+    }
+
+    #[test]
+    fn test_comparisons_on_both_sides_of_equality_parse() {
+        let expr = parse_source("a < b == c < d").unwrap();
+
+        let printer = AstPrinter::new();
+        assert_eq!(printer.print(&expr), "(== (< a b) (< c d))");
+    }
+
+    // ===== Literals =====
+
+    #[test]
+    fn test_every_literal_token_parses() {
+        let printer = AstPrinter::new();
+        for (source, expected) in [
+            ("12.5", "12.5"),
+            ("\"text\"", "text"),
+            ("true", "true"),
+            ("false", "false"),
+            ("nil", "nil"),
+        ] {
+            let expr = parse_source(source).unwrap();
+            assert_eq!(printer.print(&expr), expected);
+        }
+    }
+
+    #[test]
+    fn test_keyword_literals_carry_no_token_literal() {
+        let tokens = Scanner::new("true false nil".to_string()).scan_tokens();
+
+        assert!(tokens.iter().all(|token| token.literal.is_none()));
+    }
+
+    // ===== ParseError display =====
+
+    #[test]
+    fn test_parse_error_display_mid_stream() {
+        let error = parse_source("1 +\n* 2").unwrap_err();
+
+        assert_eq!(
+            error.to_string(),
+            "[line 2] Error at '*': Expect expression."
+        );
+    }
+
+    #[test]
+    fn test_parse_error_display_at_end() {
+        let error = parse_source("(1 + 2").unwrap_err();
+
+        assert_eq!(
+            error.to_string(),
+            "[line 1] Error at end: Expect ')' after expression to match '(' on line 1."
+        );
+    }
+
+    #[test]
+    fn test_unclosed_paren_points_at_opening_line() {
+        // Given
+        let source = "(1 +\n2\n+ 3";
+
+        // When
+        let error = parse_source(source).unwrap_err();
+
+        // Then
+        assert_eq!(error.token.line, 3);
+        assert_eq!(
+            error.message,
+            "Expect ')' after expression to match '(' on line 1."
+        );
+    }
+
+    #[test]
+    fn test_unclosed_call_points_at_opening_line() {
+        let error = parse_source("f\n(1,\n2").unwrap_err();
+
+        assert_eq!(
+            error.message,
+            "Expect ')' after arguments to match '(' on line 2."
+        );
+    }
+
+    // ===== Error kinds =====
+
+    #[test]
+    fn test_missing_paren_is_expected_token() {
+        let error = parse_source("(1 + 2").unwrap_err();
+
+        assert_eq!(error.kind, ErrorKind::ExpectedToken);
+    }
+
+    #[test]
+    fn test_error_kinds() {
+        for (source, kind) in [
+            ("1 +", ErrorKind::ExpectedExpression),
+            ("1 = 2", ErrorKind::InvalidAssignTarget),
+            ("1++", ErrorKind::InvalidAssignTarget),
+            ("1 < 2 < 3", ErrorKind::ChainedComparison),
+            ("{ return 1; }", ErrorKind::InvalidReturn),
+        ] {
+            assert_eq!(parse_source(source).unwrap_err().kind, kind, "{}", source);
+        }
+    }
+
+    #[test]
+    fn test_condition_warning_kind() {
+        let tokens = Scanner::new("if (a = 1) print a;".to_string()).scan_tokens();
+        let mut parser = Parser::new(tokens);
+
+        parser.parse_program().unwrap();
+
+        assert_eq!(parser.warnings()[0].kind, ErrorKind::AssignmentInCondition);
+    }
+
+    #[test]
+    fn test_error_token_is_reported_in_context() {
+        // Given
+        let tokens = Scanner::new("var a = @;\nprint a;\nprint 1 @ 2;".to_string())
+            .with_error_tokens()
+            .scan_tokens();
+
+        // When
+        let errors = Parser::new(tokens).parse_program().unwrap_err();
+
+        // Then: one error per bad statement, the parser recovers in between
+        assert_eq!(errors.len(), 2);
+        assert_eq!(errors[0].kind, ErrorKind::UnexpectedChar);
+        assert_eq!(
+            errors[0].to_string(),
+            "[line 1] Error at '@': Unexpected character '@'."
+        );
+        assert_eq!(errors[1].token.line, 3);
+        assert_eq!(errors[1].message, "Expect ';' after value.");
+    }
+
+    // ===== Trailing commas =====
+
+    #[test]
+    fn test_call_trailing_comma_accepted() {
+        let expr = parse_source("f(1, 2,)").unwrap();
+
+        let printer = AstPrinter::new();
+        assert_eq!(printer.print(&expr), "(call f 1 2)");
+    }
+
+    #[test]
+    fn test_call_trailing_comma_across_lines() {
+        let expr = parse_source("f(\n  1,\n  2,\n)").unwrap();
+
+        let Expr::Call(call) = expr else {
+            panic!("Expected call");
+        };
+        assert_eq!(call.arguments.len(), 2);
+    }
+
+    #[test]
+    fn test_call_doubled_trailing_comma_rejected() {
+        let error = parse_source("f(1,,)").unwrap_err();
+
+        assert_eq!(error.message, "Expect expression.");
+        assert_eq!(error.token.lexeme, ",");
+    }
+
+    #[test]
+    fn test_call_lone_comma_rejected() {
+        assert!(parse_source("f(,)").is_err());
+    }
+
+    // ===== Token ownership =====
+
+    #[test]
+    fn test_consumed_tokens_move_into_the_ast() {
+        let tokens = Scanner::new("a + \"s\"".to_string()).scan_tokens();
+        let mut parser = Parser::new(tokens);
+
+        let expr = parser.parse_expression().unwrap();
+
+        // The AST owns the full tokens...
+        assert_eq!(AstPrinter::new().print(&expr), "(+ a s)");
+        // ...while the stream keeps what lookbehind needs
+        assert_eq!(parser.previous().token_type, TokenType::String);
+        assert_eq!(parser.previous().line, 1);
+    }
+
+    #[test]
+    fn test_taken_token_keeps_its_source_name() {
+        let tokens = Scanner::named("return;".to_string(), "foo.lox").scan_tokens();
+        let mut parser = Parser::new(tokens);
+        parser.advance();
+
+        let keyword = parser.take_previous();
+
+        assert_eq!(keyword.lexeme, "return");
+        assert_eq!(parser.previous().lexeme, "");
+        assert_eq!(parser.previous().location(), "foo.lox:1");
+    }
+
+    // ===== REPL input =====
+
+    fn parse_repl_source(source: &str) -> Result<ReplUnit, Vec<ParseError>> {
+        let tokens = Scanner::new(source.to_string()).scan_tokens();
+        Parser::new(tokens).parse_repl()
+    }
+
+    #[test]
+    fn test_parse_repl_bare_expression() {
+        let unit = parse_repl_source("1 + 2").unwrap();
+
+        let ReplUnit::Expr(expr) = unit else {
+            panic!("Expected expression, got {:?}", unit);
+        };
+        assert_eq!(AstPrinter::new().print(&expr), "(+ 1 2)");
+    }
+
+    #[test]
+    fn test_parse_repl_declaration() {
+        let unit = parse_repl_source("var x = 3;").unwrap();
+
+        assert!(matches!(unit, ReplUnit::Stmt(Stmt::Var(_))));
+    }
+
+    #[test]
+    fn test_parse_repl_expression_statement() {
+        let unit = parse_repl_source("f(1);").unwrap();
+
+        assert!(matches!(unit, ReplUnit::Stmt(Stmt::Expression(_))));
+    }
+
+    #[test]
+    fn test_parse_repl_lambda_is_expression() {
+        let unit = parse_repl_source("fun (a) { return a; }").unwrap();
+
+        assert!(matches!(unit, ReplUnit::Expr(Expr::Function(_))));
+    }
+
+    #[test]
+    fn test_parse_repl_trailing_tokens_error() {
+        let errors = parse_repl_source("1 + 2 3").unwrap_err();
+
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].message, "Expect end of input.");
+        assert_eq!(errors[0].token.lexeme, "3");
+    }
+
+    #[test]
+    fn test_parse_repl_statement_errors_are_reported() {
+        let errors = parse_repl_source("var = 3;").unwrap_err();
+
+        assert_eq!(errors[0].message, "Expect variable name.");
+    }
+
+    // ===== Block expressions =====
+
+    #[test]
+    fn test_block_expression_with_value() {
+        let statements = parse_program_source("var x = { var t = 1; print t; t + 1 };").unwrap();
+
+        let Stmt::Var(stmt::Var {
+            initializer: Some(Expr::Block(block)),
+            ..
+        }) = &statements[0]
+        else {
+            panic!("Expected block expression, got {:?}", statements[0]);
+        };
+        assert_eq!(block.statements.len(), 2);
+        assert!(matches!(block.value.as_deref(), Some(Expr::Binary(_))));
+    }
+
+    #[test]
+    fn test_block_expression_without_value() {
+        let expr = parse_source("{ f(); }").unwrap();
+
+        let Expr::Block(block) = expr else {
+            panic!("Expected block expression, got {:?}", expr);
+        };
+        assert_eq!(block.statements.len(), 1);
+        assert_eq!(block.value, None);
+    }
+
+    #[test]
+    fn test_brace_at_statement_start_is_block_statement() {
+        let statements = parse_program_source("{ var t = 1; }").unwrap();
+
+        assert!(matches!(statements[0], Stmt::Block(_)));
+    }
+
+    #[test]
+    fn test_block_expression_tail_must_end_block() {
+        let error = parse_source("{ 1 2 }").unwrap_err();
+
+        assert_eq!(error.message, "Expect '}' after block expression.");
+    }
+
+    #[test]
+    fn test_return_allowed_in_function_inside_block_expression() {
+        let source = "var f = { fun g() { return 1; } g };";
+
+        assert!(parse_program_source(source).is_ok());
+    }
+
+    // ===== Loop control =====
+
+    #[test]
+    fn test_labeled_break_in_nested_loop() {
+        let source = "outer: while (a) { while (b) { break outer; } }";
+
+        let statements = parse_program_source(source).unwrap();
+
+        let Stmt::While(outer) = &statements[0] else {
+            panic!("Expected while loop, got {:?}", statements[0]);
+        };
+        assert_eq!(outer.label.as_ref().unwrap().lexeme, "outer");
+        let printed = AstPrinter::new().print_stmt(&statements[0]);
+        assert_eq!(
+            printed,
+            "(label outer (while a (block (while b (block (break outer))))))"
+        );
+    }
+
+    #[test]
+    fn test_break_to_undefined_label_is_left_to_resolver() {
+        let source = "outer: while (a) { while (b) { break inner; } }";
+
+        assert!(parse_program_source(source).is_ok());
+    }
+
+    #[test]
+    fn test_break_and_continue_outside_loop_are_errors() {
+        for (source, message) in [
+            ("break;", "Can't use 'break' outside of a loop."),
+            ("continue;", "Can't use 'continue' outside of a loop."),
+            (
+                "while (a) { fun f() { break; } }",
+                "Can't use 'break' outside of a loop.",
+            ),
+            (
+                "while (a) { var x = { break; }; }",
+                "Can't use 'break' outside of a loop.",
+            ),
+        ] {
+            let errors = parse_program_source(source).unwrap_err();
+
+            assert_eq!(errors[0].kind, ErrorKind::InvalidLoopControl, "{}", source);
+            assert_eq!(errors[0].message, message, "{}", source);
+        }
+    }
+
+    #[test]
+    fn test_label_must_precede_loop() {
+        let errors = parse_program_source("outer: print 1;").unwrap_err();
+
+        assert_eq!(errors[0].message, "Expect loop after label.");
+    }
+
+    #[test]
+    fn test_for_loop_keeps_increment_out_of_body() {
+        let statements = parse_program_source("for (;;i++) continue;").unwrap();
+
+        let Stmt::While(while_stmt) = &statements[0] else {
+            panic!("Expected while loop, got {:?}", statements[0]);
+        };
+        assert!(matches!(*while_stmt.body, Stmt::Continue(_)));
+        assert!(matches!(
+            while_stmt.increment.as_deref(),
+            Some(Expr::Postfix(_))
+        ));
+    }
+
+    // ===== Argument limit =====
+
+    fn call_source(count: usize) -> String {
+        let arguments: Vec<String> = (0..count).map(|i| i.to_string()).collect();
+        format!("f({})", arguments.join(", "))
+    }
+
+    #[test]
+    fn test_default_limit_is_255_arguments() {
+        assert!(parse_source(&call_source(255)).is_ok());
+
+        let error = parse_source(&call_source(256)).unwrap_err();
+        assert_eq!(error.kind, ErrorKind::TooManyArguments);
+        assert_eq!(error.message, "Can't have more than 255 arguments.");
+    }
+
+    #[test]
+    fn test_custom_limit_on_arguments_and_parameters() {
+        let tokens = Scanner::new("f(1, 2, 3)".to_string()).scan_tokens();
+        let error = Parser::new(tokens)
+            .with_max_args(Some(2))
+            .parse()
+            .unwrap_err();
+        assert_eq!(error.message, "Can't have more than 2 arguments.");
+        assert_eq!(error.token.lexeme, "3");
+
+        let tokens = Scanner::new("fun f(a, b, c) {}".to_string()).scan_tokens();
+        let errors = Parser::new(tokens)
+            .with_max_args(Some(2))
+            .parse_program()
+            .unwrap_err();
+        assert_eq!(errors[0].message, "Can't have more than 2 parameters.");
+    }
+
+    #[test]
+    fn test_no_limit_allows_many_arguments() {
+        let tokens = Scanner::new(call_source(1000)).scan_tokens();
+
+        let expr = Parser::new(tokens).with_max_args(None).parse().unwrap();
+
+        let Expr::Call(call) = expr else {
+            panic!("Expected call, got {:?}", expr);
+        };
+        assert_eq!(call.arguments.len(), 1000);
+    }
 }
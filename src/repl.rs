@@ -0,0 +1,260 @@
+use crate::scanner::{ScanOutcome, Scanner};
+use crate::token::{Token, TokenType};
+
+/// Collects REPL lines until they form a source worth running
+///
+/// A source is held back while it is unfinished: `Scanner::scan` reports
+/// it `Incomplete` (inside a string), or its tokens leave a `(`/`{` open or
+/// end with a dangling operator.
+/// This lets users type a function across several lines.
+///
+/// In paste mode, started with `start_paste`, every line is held until a
+/// blank line, so a pasted program runs as a whole instead of line by line.
+///
+/// # Example
+/// ```ignore
+/// let mut lines = LineBuffer::new();
+/// assert_eq!(lines.push_line("fun f() {"), None);
+/// assert_eq!(lines.push_line("}"), Some("fun f() {\n}".to_string()));
+/// ```
+pub struct LineBuffer {
+    buffer: String,
+    checker: Scanner,
+    paste: bool,
+}
+
+impl LineBuffer {
+    pub fn new() -> Self {
+        Self {
+            buffer: String::new(),
+            checker: Scanner::new(String::new()),
+            paste: false,
+        }
+    }
+
+    /// Add a line; returns the whole source once it is complete
+    pub fn push_line(&mut self, line: &str) -> Option<String> {
+        if self.paste && line.trim().is_empty() {
+            return self.finish();
+        }
+
+        if !self.buffer.is_empty() {
+            self.buffer.push('\n');
+        }
+        self.buffer.push_str(line);
+
+        self.checker.reset(self.buffer.clone());
+        let waiting = match self.checker.scan() {
+            ScanOutcome::Incomplete => true,
+            ScanOutcome::Complete(tokens) => unfinished(&tokens),
+            ScanOutcome::Error(_) => false,
+        };
+        if self.paste || waiting {
+            None
+        } else {
+            Some(std::mem::take(&mut self.buffer))
+        }
+    }
+
+    /// Hold every line from now until a blank line ends the paste
+    ///
+    /// # Example
+    /// ```ignore
+    /// lines.start_paste();
+    /// assert_eq!(lines.push_line("var a = 1;"), None);
+    /// assert_eq!(lines.push_line("print a;"), None);
+    /// assert_eq!(lines.push_line(""), Some("var a = 1;\nprint a;".to_string()));
+    /// ```
+    pub fn start_paste(&mut self) {
+        self.paste = true;
+    }
+
+    /// End paste mode early, e.g. at end of input, returning what was
+    /// pasted so far; None outside paste mode or when nothing was pasted
+    pub fn finish(&mut self) -> Option<String> {
+        if !std::mem::take(&mut self.paste) || self.buffer.is_empty() {
+            return None;
+        }
+        Some(std::mem::take(&mut self.buffer))
+    }
+
+    /// True when no unfinished input is waiting for more lines
+    pub fn is_empty(&self) -> bool {
+        self.buffer.is_empty() && !self.paste
+    }
+}
+
+// Whether the tokens leave a bracket open or end where an operand must follow
+fn unfinished(tokens: &[Token]) -> bool {
+    let mut depth: i64 = 0;
+    for token in tokens {
+        match token.token_type {
+            TokenType::LeftParen | TokenType::LeftBrace => depth += 1,
+            TokenType::RightParen | TokenType::RightBrace => depth -= 1,
+            _ => {}
+        }
+    }
+
+    // tokens always ends with EOF; look at the one before it
+    let last = tokens.iter().rev().nth(1).map(|token| token.token_type);
+    let dangling = matches!(
+        last,
+        Some(
+            TokenType::Comma
+                | TokenType::Dot
+                | TokenType::Minus
+                | TokenType::Plus
+                | TokenType::Slash
+                | TokenType::Star
+                | TokenType::Percent
+                | TokenType::Bang
+                | TokenType::BangEqual
+                | TokenType::Equal
+                | TokenType::EqualEqual
+                | TokenType::Greater
+                | TokenType::GreaterEqual
+                | TokenType::Less
+                | TokenType::LessEqual
+                | TokenType::PlusEqual
+                | TokenType::MinusEqual
+                | TokenType::StarEqual
+                | TokenType::SlashEqual
+                | TokenType::QuestionQuestion
+                | TokenType::And
+                | TokenType::Or
+        )
+    );
+
+    depth > 0 || dangling
+}
+
+impl Default for LineBuffer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::interpreter::Interpreter;
+    use crate::lox_value::LoxValue;
+
+    #[test]
+    fn test_single_line_is_complete() {
+        let mut lines = LineBuffer::new();
+
+        assert_eq!(lines.push_line("print 1;"), Some("print 1;".to_string()));
+        assert!(lines.is_empty());
+    }
+
+    #[test]
+    fn test_function_across_three_lines_is_run_once() {
+        // Given
+        let mut lines = LineBuffer::new();
+        let mut interpreter = Interpreter::new();
+        let mut runs = 0;
+
+        // When
+        for line in ["fun add(a, b) {", "  return a + b;", "}"] {
+            if let Some(source) = lines.push_line(line) {
+                interpreter.run(&source).unwrap();
+                runs += 1;
+            }
+        }
+
+        // Then
+        assert_eq!(runs, 1);
+        assert!(lines.is_empty());
+        assert_eq!(
+            interpreter.eval("add(1, 2)").unwrap(),
+            LoxValue::Number(3.0)
+        );
+    }
+
+    #[test]
+    fn test_unterminated_string_keeps_newline() {
+        let mut lines = LineBuffer::new();
+
+        assert_eq!(lines.push_line("print \"a"), None);
+        assert!(!lines.is_empty());
+        assert_eq!(lines.push_line("b\";"), Some("print \"a\nb\";".to_string()));
+    }
+
+    #[test]
+    fn test_unclosed_bracket_waits_for_more() {
+        let mut lines = LineBuffer::new();
+
+        assert_eq!(lines.push_line("print (1"), None);
+        assert_eq!(lines.push_line(");"), Some("print (1\n);".to_string()));
+    }
+
+    #[test]
+    fn test_trailing_operator_waits_for_more() {
+        let mut lines = LineBuffer::new();
+
+        assert_eq!(lines.push_line("var a = 1 +"), None);
+        assert_eq!(lines.push_line("2;"), Some("var a = 1 +\n2;".to_string()));
+    }
+
+    #[test]
+    fn test_overclosed_input_is_complete() {
+        // Extra closers can never be fixed by more input; let the parser report them
+        let mut lines = LineBuffer::new();
+
+        assert_eq!(lines.push_line("f(1));"), Some("f(1));".to_string()));
+    }
+
+    // ===== Paste mode =====
+
+    #[test]
+    fn test_paste_block_runs_in_one_pass() {
+        // Given
+        let mut lines = LineBuffer::new();
+        let mut interpreter = Interpreter::new();
+        let mut runs = 0;
+        lines.start_paste();
+
+        // When
+        for line in ["var a = 1;", "var b = a + 1;", ""] {
+            if let Some(source) = lines.push_line(line) {
+                interpreter.run(&source).unwrap();
+                runs += 1;
+            }
+        }
+
+        // Then
+        assert_eq!(runs, 1);
+        assert!(lines.is_empty());
+        assert_eq!(interpreter.eval("b").unwrap(), LoxValue::Number(2.0));
+    }
+
+    #[test]
+    fn test_paste_holds_complete_lines() {
+        let mut lines = LineBuffer::new();
+        lines.start_paste();
+
+        assert_eq!(lines.push_line("print 1;"), None);
+        assert!(!lines.is_empty());
+    }
+
+    #[test]
+    fn test_finish_returns_unterminated_paste() {
+        let mut lines = LineBuffer::new();
+        lines.start_paste();
+        lines.push_line("print 1;");
+
+        assert_eq!(lines.finish(), Some("print 1;".to_string()));
+        assert_eq!(lines.finish(), None);
+        assert!(lines.is_empty());
+    }
+
+    #[test]
+    fn test_finish_outside_paste_mode_keeps_buffer() {
+        let mut lines = LineBuffer::new();
+        lines.push_line("fun f() {");
+
+        assert_eq!(lines.finish(), None);
+        assert!(!lines.is_empty());
+    }
+}
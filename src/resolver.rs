@@ -0,0 +1,456 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::expr::{self, Expr};
+use crate::lox_error::ErrorKind;
+use crate::parser::ParseError;
+use crate::stmt::{self, Stmt};
+use crate::token::Token;
+
+/// Static pass that finds, for each variable use, the scope it refers to
+///
+/// Each `Variable`, `Assign`, `Postfix` and `This` node found in a local
+/// scope is mapped from its id (see `expr::next_id`) to its depth: how many
+/// scopes out from the innermost one its binding lives. Names found in no
+/// local scope are left out and looked up as globals.
+///
+/// The scopes mirror the environments the interpreter creates: one per
+/// block, block expression, switch case and function call (parameters and
+/// body together), plus one holding `this` around each bound method.
+///
+/// It also collects warnings about valid but suspicious code, such as
+/// statements that follow a `return` and can never run, and errors such as
+/// a `break` naming no enclosing loop.
+///
+/// # Example
+/// ```ignore
+/// let mut resolver = Resolver::new();
+/// let locals = resolver.resolve(&statements);
+/// for warning in resolver.warnings() { ... }
+/// if let Some(error) = resolver.errors().first() { ... }
+/// ```
+#[derive(Debug, Default)]
+pub struct Resolver {
+    // Names declared in each enclosing local scope, innermost last
+    scopes: Vec<HashSet<String>>,
+    locals: HashMap<usize, usize>,
+    // Labels of the loops around the current statement, innermost last
+    loops: Vec<Option<String>>,
+    warnings: Vec<ParseError>,
+    errors: Vec<ParseError>,
+}
+
+impl Resolver {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Depth of every local variable use in `statements`, by node id
+    pub fn resolve(&mut self, statements: &[Stmt]) -> HashMap<usize, usize> {
+        self.resolve_stmts(statements);
+        std::mem::take(&mut self.locals)
+    }
+
+    /// Depth of every local variable use in `expr`, by node id
+    pub fn resolve_expression(&mut self, expr: &Expr) -> HashMap<usize, usize> {
+        self.resolve_expr(expr);
+        std::mem::take(&mut self.locals)
+    }
+
+    /// Suspicious but valid code found so far
+    ///
+    /// Warnings never stop resolution or execution.
+    pub fn warnings(&self) -> &[ParseError] {
+        &self.warnings
+    }
+
+    /// Errors found so far, in source order
+    ///
+    /// Code with errors must not be run.
+    pub fn errors(&self) -> &[ParseError] {
+        &self.errors
+    }
+
+    fn resolve_stmts(&mut self, statements: &[Stmt]) {
+        for (i, statement) in statements.iter().enumerate() {
+            self.resolve_stmt(statement);
+            // Only a `return` directly in this list is sure to be taken
+            if let Stmt::Return(return_stmt) = statement
+                && i + 1 < statements.len()
+            {
+                self.warnings.push(ParseError {
+                    kind: ErrorKind::UnreachableCode,
+                    message: "Unreachable code after return.".to_string(),
+                    token: return_stmt.keyword.clone(),
+                });
+            }
+        }
+    }
+
+    fn resolve_stmt(&mut self, stmt: &Stmt) {
+        match stmt {
+            Stmt::Block(block) => self.scoped(|resolver| resolver.resolve_stmts(&block.statements)),
+            Stmt::Break(break_stmt) => self.check_label(&break_stmt.label),
+            Stmt::Continue(continue_stmt) => self.check_label(&continue_stmt.label),
+            Stmt::Fallthrough(_) => {}
+            Stmt::Class(class) => self.visit_class(class),
+            Stmt::Expression(expression) => self.resolve_expr(&expression.expression),
+            Stmt::Function(function) => {
+                self.declare(&function.name);
+                self.resolve_function(&function.params, &function.body);
+            }
+            Stmt::If(if_stmt) => {
+                self.resolve_expr(&if_stmt.condition);
+                self.resolve_stmt(&if_stmt.then_branch);
+                if let Some(else_branch) = &if_stmt.else_branch {
+                    self.resolve_stmt(else_branch);
+                }
+            }
+            Stmt::Print(print) => {
+                for expression in &print.expressions {
+                    self.resolve_expr(expression);
+                }
+            }
+            Stmt::Return(return_stmt) => {
+                if let Some(value) = &return_stmt.value {
+                    self.resolve_expr(value);
+                }
+            }
+            Stmt::Switch(switch) => self.visit_switch(switch),
+            // The initializer cannot see the variable it initializes
+            Stmt::Var(var) => {
+                if let Some(initializer) = &var.initializer {
+                    self.resolve_expr(initializer);
+                }
+                self.declare(&var.name);
+            }
+            Stmt::While(while_stmt) => {
+                self.resolve_expr(&while_stmt.condition);
+                self.loops
+                    .push(while_stmt.label.as_ref().map(|label| label.lexeme.clone()));
+                self.resolve_stmt(&while_stmt.body);
+                self.loops.pop();
+                if let Some(increment) = &while_stmt.increment {
+                    self.resolve_expr(increment);
+                }
+            }
+        }
+    }
+
+    // Methods and getters run bound to an instance, class methods do not
+    fn visit_class(&mut self, class: &stmt::Class) {
+        self.declare(&class.name);
+
+        self.scoped(|resolver| {
+            resolver.declare_name("this");
+            for method in class.methods.iter().chain(&class.getters) {
+                resolver.resolve_function(&method.params, &method.body);
+            }
+        });
+        for method in &class.class_methods {
+            self.resolve_function(&method.params, &method.body);
+        }
+    }
+
+    // Case values are evaluated in the enclosing scope, bodies in their own
+    fn visit_switch(&mut self, switch: &stmt::Switch) {
+        self.resolve_expr(&switch.discriminant);
+        for (value, body) in &switch.cases {
+            self.resolve_expr(value);
+            self.scoped(|resolver| resolver.resolve_stmts(body));
+        }
+        if let Some(default) = &switch.default {
+            self.scoped(|resolver| resolver.resolve_stmts(default));
+        }
+    }
+
+    fn resolve_expr(&mut self, expr: &Expr) {
+        match expr {
+            Expr::Assign(assign) => {
+                self.resolve_expr(&assign.value);
+                self.resolve_local(assign.id, &assign.name);
+            }
+            Expr::Binary(binary) => {
+                self.resolve_expr(&binary.left);
+                self.resolve_expr(&binary.right);
+            }
+            Expr::Block(block) => self.visit_block_expr(block),
+            Expr::Call(call) => {
+                self.resolve_expr(&call.callee);
+                for argument in &call.arguments {
+                    self.resolve_expr(argument);
+                }
+            }
+            Expr::Function(function) => self.resolve_function(&function.params, &function.body),
+            Expr::Get(get) => self.resolve_expr(&get.object),
+            Expr::Grouping(grouping) => self.resolve_expr(&grouping.expression),
+            Expr::Literal(_) | Expr::Super(_) => {}
+            Expr::Logical(logical) => {
+                self.resolve_expr(&logical.left);
+                self.resolve_expr(&logical.right);
+            }
+            Expr::Postfix(postfix) => self.resolve_local(postfix.id, &postfix.name),
+            Expr::Set(set) => {
+                self.resolve_expr(&set.value);
+                self.resolve_expr(&set.object);
+            }
+            Expr::This(this) => self.resolve_local(this.id, &this.keyword),
+            Expr::Unary(unary) => self.resolve_expr(&unary.right),
+            Expr::Variable(variable) => self.resolve_local(variable.id, &variable.name),
+        }
+    }
+
+    // Like a function body, a block expression cannot leave the loops around it
+    fn visit_block_expr(&mut self, block: &expr::Block) {
+        let loops = std::mem::take(&mut self.loops);
+        self.scoped(|resolver| {
+            resolver.resolve_stmts(&block.statements);
+            if let Some(value) = &block.value {
+                resolver.resolve_expr(value);
+            }
+        });
+        self.loops = loops;
+    }
+
+    // One scope holds both the parameters and the body, as at run time
+    fn resolve_function(&mut self, params: &[Token], body: &[Stmt]) {
+        let loops = std::mem::take(&mut self.loops);
+        self.scoped(|resolver| {
+            for param in params {
+                resolver.declare(param);
+            }
+            resolver.resolve_stmts(body);
+        });
+        self.loops = loops;
+    }
+
+    // A `break` or `continue` label must name a loop around it
+    fn check_label(&mut self, label: &Option<Token>) {
+        let Some(label) = label else {
+            return;
+        };
+        if !self
+            .loops
+            .iter()
+            .flatten()
+            .any(|name| *name == label.lexeme)
+        {
+            self.errors.push(ParseError {
+                kind: ErrorKind::UndefinedLabel,
+                message: format!("Undefined loop label '{}'.", label.lexeme),
+                token: label.clone(),
+            });
+        }
+    }
+
+    fn resolve_local(&mut self, id: usize, name: &Token) {
+        let depth = self
+            .scopes
+            .iter()
+            .rev()
+            .position(|scope| scope.contains(&name.lexeme));
+        if let Some(depth) = depth {
+            self.locals.insert(id, depth);
+        }
+    }
+
+    fn scoped(&mut self, body: impl FnOnce(&mut Self)) {
+        self.scopes.push(HashSet::new());
+        body(self);
+        self.scopes.pop();
+    }
+
+    // Globals are not tracked; they are looked up by name at run time
+    fn declare(&mut self, name: &Token) {
+        self.declare_name(&name.lexeme);
+    }
+
+    fn declare_name(&mut self, name: &str) {
+        if let Some(scope) = self.scopes.last_mut() {
+            scope.insert(name.to_string());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::Parser;
+    use crate::scanner::Scanner;
+
+    fn parse(source: &str) -> Vec<Stmt> {
+        let tokens = Scanner::new(source.to_string()).scan_tokens();
+        Parser::new(tokens).parse_program().unwrap()
+    }
+
+    // Ids of the variables read by `print` statements, in source order
+    fn printed_variables(statements: &[Stmt], ids: &mut Vec<usize>) {
+        for statement in statements {
+            match statement {
+                Stmt::Print(print) => {
+                    for expression in &print.expressions {
+                        if let Expr::Variable(variable) = expression {
+                            ids.push(variable.id);
+                        }
+                    }
+                }
+                Stmt::Block(block) => printed_variables(&block.statements, ids),
+                Stmt::Function(function) => printed_variables(&function.body, ids),
+                _ => {}
+            }
+        }
+    }
+
+    fn resolved(source: &str) -> (Vec<usize>, HashMap<usize, usize>) {
+        let statements = parse(source);
+        let mut ids = Vec::new();
+        printed_variables(&statements, &mut ids);
+        (ids, Resolver::new().resolve(&statements))
+    }
+
+    #[test]
+    fn test_same_name_in_different_scopes_gets_distinct_ids_and_depths() {
+        // Given
+        let source = "{ var a = 1; print a; { print a; } }";
+
+        // When
+        let (ids, locals) = resolved(source);
+
+        // Then
+        assert_eq!(ids.len(), 2);
+        assert_ne!(ids[0], ids[1]);
+        assert_eq!(locals[&ids[0]], 0);
+        assert_eq!(locals[&ids[1]], 1);
+    }
+
+    #[test]
+    fn test_globals_are_not_resolved() {
+        let (ids, locals) = resolved("var a = 1; print a; { print a; }");
+
+        assert!(ids.iter().all(|id| !locals.contains_key(id)));
+    }
+
+    #[test]
+    fn test_parameters_share_scope_with_body() {
+        let (ids, locals) = resolved("fun f(a) { var b; print a, b; }");
+
+        assert_eq!(locals[&ids[0]], 0);
+        assert_eq!(locals[&ids[1]], 0);
+    }
+
+    #[test]
+    fn test_initializer_sees_outer_variable() {
+        let statements = parse("{ var a = 1; { var a = a; } }");
+
+        let Stmt::Block(outer) = &statements[0] else {
+            panic!("Expected block, got {:?}", statements[0]);
+        };
+        let Stmt::Block(inner) = &outer.statements[1] else {
+            panic!("Expected block, got {:?}", outer.statements[1]);
+        };
+        let Stmt::Var(stmt::Var {
+            initializer: Some(Expr::Variable(read)),
+            ..
+        }) = &inner.statements[0]
+        else {
+            panic!("Expected var, got {:?}", inner.statements[0]);
+        };
+        assert_eq!(Resolver::new().resolve(&statements)[&read.id], 1);
+    }
+
+    #[test]
+    fn test_ids_are_unique_across_parses() {
+        let (first, _) = resolved("{ var a; print a; }");
+        let (second, _) = resolved("{ var a; print a; }");
+
+        assert_ne!(first, second);
+    }
+
+    // ===== Unreachable code =====
+
+    fn warnings_for(source: &str) -> Vec<ParseError> {
+        let mut resolver = Resolver::new();
+        resolver.resolve(&parse(source));
+        resolver.warnings
+    }
+
+    #[test]
+    fn test_code_after_return_is_flagged() {
+        // Given
+        let source = "fun f() {\n  return 1;\n  print \"never\";\n}";
+
+        // When
+        let warnings = warnings_for(source);
+
+        // Then
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(warnings[0].kind, ErrorKind::UnreachableCode);
+        assert_eq!(warnings[0].message, "Unreachable code after return.");
+        assert_eq!(warnings[0].token.line, 2);
+    }
+
+    #[test]
+    fn test_conditional_return_is_not_flagged() {
+        let source = "fun f(a) { if (a) return 1; print a; return 2; }";
+
+        assert!(warnings_for(source).is_empty());
+    }
+
+    #[test]
+    fn test_unreachable_code_does_not_stop_execution() {
+        let mut interpreter = crate::interpreter::Interpreter::new();
+
+        let result = interpreter.eval("fun () { return 1; print 2; }()");
+
+        assert_eq!(result.unwrap(), crate::lox_value::LoxValue::Number(1.0));
+    }
+
+    // ===== Loop labels =====
+
+    fn errors_for(source: &str) -> Vec<ParseError> {
+        let mut resolver = Resolver::new();
+        resolver.resolve(&parse(source));
+        resolver.errors
+    }
+
+    #[test]
+    fn test_break_to_enclosing_label_is_accepted() {
+        let source = "outer: while (a) { while (b) { break outer; continue outer; } }";
+
+        assert!(errors_for(source).is_empty());
+    }
+
+    #[test]
+    fn test_break_to_undefined_label_is_error() {
+        // Given
+        let source = "outer: while (a) { while (b) { break inner; } }";
+
+        // When
+        let errors = errors_for(source);
+
+        // Then
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].kind, ErrorKind::UndefinedLabel);
+        assert_eq!(errors[0].message, "Undefined loop label 'inner'.");
+        assert_eq!(errors[0].token.lexeme, "inner");
+    }
+
+    #[test]
+    fn test_label_of_finished_loop_is_out_of_scope() {
+        let errors = errors_for("outer: while (a) {} while (b) { continue outer; }");
+
+        assert_eq!(errors[0].message, "Undefined loop label 'outer'.");
+    }
+
+    #[test]
+    fn test_labels_do_not_reach_into_functions_or_block_expressions() {
+        for source in [
+            "outer: while (a) { fun f() { while (b) { break outer; } } }",
+            "outer: while (a) { var x = { while (b) { break outer; } }; }",
+        ] {
+            let errors = errors_for(source);
+
+            assert_eq!(errors.len(), 1, "{}", source);
+            assert_eq!(errors[0].kind, ErrorKind::UndefinedLabel, "{}", source);
+        }
+    }
+}
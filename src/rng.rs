@@ -0,0 +1,88 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Small seedable pseudo-random generator (xorshift64*)
+///
+/// Not suitable for cryptography; meant for reproducible scripts and tests.
+/// The same seed always yields the same sequence.
+///
+/// # Example
+/// ```ignore
+/// let mut rng = Rng::new(42);
+/// let x = rng.next_f64(); // always the same for seed 42
+/// ```
+#[derive(Debug, Clone)]
+pub struct Rng {
+    state: u64,
+}
+
+impl Rng {
+    pub fn new(seed: u64) -> Self {
+        // splitmix64 spreads small seeds and never leaves the state zero,
+        // which would make xorshift output only zeros
+        let mut z = seed.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^= z >> 31;
+        Self {
+            state: if z == 0 { 1 } else { z },
+        }
+    }
+
+    /// Generator seeded from the clock, for runs that did not call `seed`
+    pub fn from_time() -> Self {
+        let nanos = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |elapsed| elapsed.as_nanos() as u64);
+        Self::new(nanos)
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state ^= self.state >> 12;
+        self.state ^= self.state << 25;
+        self.state ^= self.state >> 27;
+        self.state.wrapping_mul(0x2545_F491_4F6C_DD1D)
+    }
+
+    /// Uniform in [0, 1)
+    pub fn next_f64(&mut self) -> f64 {
+        // The top 53 bits fill an f64 mantissa exactly
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_same_seed_same_sequence() {
+        let mut a = Rng::new(7);
+        let mut b = Rng::new(7);
+
+        for _ in 0..10 {
+            assert_eq!(a.next_u64(), b.next_u64());
+        }
+    }
+
+    #[test]
+    fn test_different_seeds_differ() {
+        assert_ne!(Rng::new(1).next_u64(), Rng::new(2).next_u64());
+    }
+
+    #[test]
+    fn test_zero_seed_is_usable() {
+        let mut rng = Rng::new(0);
+
+        assert!((0..10).any(|_| rng.next_u64() != 0));
+    }
+
+    #[test]
+    fn test_next_f64_in_unit_interval() {
+        let mut rng = Rng::new(123);
+
+        for _ in 0..1000 {
+            let x = rng.next_f64();
+            assert!((0.0..1.0).contains(&x));
+        }
+    }
+}
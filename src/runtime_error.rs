@@ -2,10 +2,21 @@ use core::fmt;
 
 use crate::token::Token;
 
+/// A Lox function call that was active when an error occurred
+#[derive(Debug, Clone, PartialEq)]
+pub struct CallFrame {
+    /// The function as displayed, e.g. `<fn add>`
+    pub function: String,
+    /// Line of the call site
+    pub line: usize,
+}
+
 #[derive(Debug, Clone)]
 pub struct RuntimeError {
     pub token: Token,
     pub message: String,
+    /// Calls the error unwound through, innermost first; empty at top level
+    pub call_trace: Vec<CallFrame>,
 }
 
 impl RuntimeError {
@@ -13,23 +24,56 @@ impl RuntimeError {
         Self {
             token: token.clone(),
             message: message.to_string(),
+            call_trace: Vec::new(),
         }
     }
 
     pub fn format_error(&self) -> String {
         format!(
-            "[line {}] Runtime Error at '{}': {}",
-            self.token.line, self.token.lexeme, self.message
+            "[line {}] Runtime Error at '{}': {}{}",
+            self.token.line, self.token.lexeme, self.message, self.format_trace()
         )
     }
+
+    /// One indented line per call frame, each starting with a newline. A run
+    /// of identical frames, as left by deep recursion, prints once followed by
+    /// a count of the repeats
+    ///
+    /// # Example
+    /// ```text
+    ///
+    ///     in <fn count>, called at line 2
+    ///     ... repeated 62 more times
+    ///     in <fn outer>, called at line 9
+    /// ```
+    pub fn format_trace(&self) -> String {
+        let mut trace = String::new();
+        let mut frames = self.call_trace.iter().peekable();
+        while let Some(frame) = frames.next() {
+            trace.push_str(&format!(
+                "\n    in {}, called at line {}",
+                frame.function, frame.line
+            ));
+            let mut repeats = 0;
+            while frames.next_if_eq(&frame).is_some() {
+                repeats += 1;
+            }
+            match repeats {
+                0 => {}
+                1 => trace.push_str("\n    ... repeated 1 more time"),
+                _ => trace.push_str(&format!("\n    ... repeated {} more times", repeats)),
+            }
+        }
+        trace
+    }
 }
 
 impl fmt::Display for RuntimeError {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         write!(
             f,
-            "[line {}] Runtime Error at '{}': {}",
-            self.token.line, self.token.lexeme, self.message
+            "[line {}] Runtime Error at '{}': {}{}",
+            self.token.line, self.token.lexeme, self.message, self.format_trace()
         )
     }
 }
@@ -49,6 +93,7 @@ mod tests {
             lexeme: lexeme.to_string(),
             literal: None,
             line,
+            source_name: None,
         }
     }
 
@@ -203,13 +248,13 @@ mod tests {
     #[test]
     fn test_clone_with_different_errors() {
         // Given
-        let errors = vec![
+        let errors = [
             RuntimeError::new(&make_token(TokenType::Plus, "+", 1), "Error 1"),
             RuntimeError::new(&make_token(TokenType::Minus, "-", 2), "Error 2"),
         ];
 
         // When
-        let cloned: Vec<_> = errors.iter().cloned().collect();
+        let cloned: Vec<_> = errors.to_vec();
 
         // Then
         assert_eq!(errors.len(), cloned.len());
@@ -281,12 +326,7 @@ mod tests {
     #[test]
     fn test_empty_lexeme() {
         // Given
-        let token = Token {
-            token_type: TokenType::Eof,
-            lexeme: String::new(),
-            literal: None,
-            line: 1,
-        };
+        let token = Token::eof(1);
 
         // When
         let error = RuntimeError::new(&token, "Unexpected EOF");
@@ -393,5 +433,81 @@ mod tests {
         assert!(debug.contains("Test"));
     }
 
+    // ===== 8. Call trace test =====
+
+    fn frame(function: &str, line: usize) -> CallFrame {
+        CallFrame {
+            function: function.to_string(),
+            line,
+        }
+    }
+
+    #[test]
+    fn test_format_trace_lists_distinct_frames() {
+        // Given
+        let mut error = RuntimeError::new(&make_token(TokenType::Plus, "+", 1), "Test");
+        error.call_trace = vec![frame("<fn inner>", 5), frame("<fn outer>", 9)];
+
+        // When
+        let trace = error.format_trace();
+
+        // Then
+        assert_eq!(
+            trace,
+            "\n    in <fn inner>, called at line 5\n    in <fn outer>, called at line 9"
+        );
+    }
+
+    #[test]
+    fn test_format_trace_collapses_repeated_frames() {
+        // Given
+        let mut error = RuntimeError::new(&make_token(TokenType::Plus, "+", 1), "Test");
+        error.call_trace = vec![frame("<fn count>", 2); 63];
+        error.call_trace.push(frame("<fn outer>", 9));
+
+        // When
+        let trace = error.format_trace();
+
+        // Then
+        assert_eq!(
+            trace,
+            "\n    in <fn count>, called at line 2\
+             \n    ... repeated 62 more times\
+             \n    in <fn outer>, called at line 9"
+        );
+    }
+
+    #[test]
+    fn test_format_trace_single_repeat_is_singular() {
+        // Given
+        let mut error = RuntimeError::new(&make_token(TokenType::Plus, "+", 1), "Test");
+        error.call_trace = vec![frame("<fn count>", 2); 2];
+
+        // When
+        let trace = error.format_trace();
+
+        // Then
+        assert_eq!(
+            trace,
+            "\n    in <fn count>, called at line 2\n    ... repeated 1 more time"
+        );
+    }
+
+    #[test]
+    fn test_format_trace_keeps_frames_from_different_lines() {
+        // Given
+        let mut error = RuntimeError::new(&make_token(TokenType::Plus, "+", 1), "Test");
+        error.call_trace = vec![frame("<fn count>", 2), frame("<fn count>", 7)];
+
+        // When
+        let trace = error.format_trace();
+
+        // Then
+        assert_eq!(
+            trace,
+            "\n    in <fn count>, called at line 2\n    in <fn count>, called at line 7"
+        );
+    }
+
  
 }
\ No newline at end of file
@@ -1,58 +1,265 @@
-use crate::token::{Literal, Token, TokenType};
-use std::collections::HashMap;
+use crate::lox_error::ErrorKind;
+use crate::token::{self, Literal, Token, TokenType};
+use std::collections::{HashMap, VecDeque};
+use std::rc::Rc;
+use std::sync::OnceLock;
+
+/// Error found while scanning source text
+#[derive(Debug, Clone, PartialEq)]
+pub struct ScanError {
+    pub line: usize,
+    pub kind: ErrorKind,
+    pub message: String,
+    /// File the error was found in, None for a single unnamed source
+    pub source_name: Option<Rc<String>>,
+}
+
+impl ScanError {
+    /// Where the error is, `foo.lox:12` or `line 12` like `Token::location`
+    pub fn location(&self) -> String {
+        token::location(&self.source_name, self.line)
+    }
+}
+
+/// Result of scanning a whole source, for callers reading input interactively
+#[derive(Debug, Clone, PartialEq)]
+pub enum ScanOutcome {
+    Complete(Vec<Token>),
+    /// More input may finish the source: it ended inside a string
+    Incomplete,
+    Error(Vec<ScanError>),
+}
+
+/// Reserved words, built once and shared by every scanner
+fn keywords() -> &'static HashMap<&'static str, TokenType> {
+    static KEYWORDS: OnceLock<HashMap<&'static str, TokenType>> = OnceLock::new();
+    KEYWORDS.get_or_init(|| {
+        HashMap::from([
+            ("and", TokenType::And),
+            ("break", TokenType::Break),
+            ("case", TokenType::Case),
+            ("class", TokenType::Class),
+            ("continue", TokenType::Continue),
+            ("default", TokenType::Default),
+            ("else", TokenType::Else),
+            ("fallthrough", TokenType::Fallthrough),
+            ("false", TokenType::False),
+            ("for", TokenType::For),
+            ("fun", TokenType::Fun),
+            ("if", TokenType::If),
+            ("nil", TokenType::Nil),
+            ("or", TokenType::Or),
+            ("print", TokenType::Print),
+            ("return", TokenType::Return),
+            ("super", TokenType::Super),
+            ("switch", TokenType::Switch),
+            ("this", TokenType::This),
+            ("true", TokenType::True),
+            ("var", TokenType::Var),
+            ("while", TokenType::While),
+        ])
+    })
+}
 
 pub struct Scanner {
-    keywords: HashMap<String, TokenType>,
-    source: String,
-    tokens: Vec<Token>,
+    source: Vec<char>,
+    // Tokens and errors of the current lexeme, not yet handed out by `scan_one`
+    pending: VecDeque<Result<Token, ScanError>>,
+    // Errors collected by `scan_tokens`
+    errors: Vec<ScanError>,
+    incomplete: bool,
     start: usize,
     current: usize,
     line: usize,
+    start_line: usize,
+    error_tokens: bool,
+    // `//` starts a comment; off, it is two `/` tokens
+    comments: bool,
+    // Stamped on every token and error
+    source_name: Option<Rc<String>>,
+    // EOF has been added to `pending`
+    finished: bool,
 }
 
 impl Scanner {
     pub fn new(source: String) -> Self {
+        Self::new_at(source, 1)
+    }
+
+    /// Scanner whose first line is `start_line` instead of 1
+    ///
+    /// Lines of tokens and errors then match the document the source is
+    /// embedded in.
+    ///
+    /// # Example
+    /// ```ignore
+    /// // A snippet found on line 10 of a template
+    /// let mut scanner = Scanner::new_at("print x;".to_string(), 10);
+    /// assert_eq!(scanner.scan_tokens()[0].line, 10);
+    /// ```
+    pub fn new_at(source: String, start_line: usize) -> Self {
         Self {
-            keywords: HashMap::from([
-                ("and".to_string(), TokenType::And),
-                ("class".to_string(), TokenType::Class),
-                ("else".to_string(), TokenType::Else),
-                ("false".to_string(), TokenType::False),
-                ("for".to_string(), TokenType::For),
-                ("fun".to_string(), TokenType::Fun),
-                ("if".to_string(), TokenType::If),
-                ("nil".to_string(), TokenType::Nil),
-                ("or".to_string(), TokenType::Or),
-                ("print".to_string(), TokenType::Print),
-                ("return".to_string(), TokenType::Return),
-                ("super".to_string(), TokenType::Super),
-                ("this".to_string(), TokenType::This),
-                ("true".to_string(), TokenType::True),
-                ("var".to_string(), TokenType::Var),
-                ("while".to_string(), TokenType::While),
-            ]),
-            source,
-            tokens: Vec::new(),
+            source: source.chars().collect(),
+            pending: VecDeque::new(),
+            errors: Vec::new(),
+            incomplete: false,
             start: 0,
             current: 0,
-            line: 1,
+            line: start_line,
+            start_line,
+            error_tokens: false,
+            comments: true,
+            source_name: None,
+            finished: false,
+        }
+    }
+
+    /// Scanner for the file `name`, which every token and error carries
+    ///
+    /// Diagnostics can then point into one of several files.
+    ///
+    /// # Example
+    /// ```ignore
+    /// let mut scanner = Scanner::named("print x;".to_string(), "foo.lox");
+    /// assert_eq!(scanner.scan_tokens()[0].location(), "foo.lox:1");
+    /// ```
+    pub fn named(source: String, name: &str) -> Self {
+        Self {
+            source_name: Some(Rc::new(name.to_string())),
+            ..Self::new(source)
         }
     }
 
+    /// Also emit an `Error` token for each unexpected character
+    ///
+    /// The error is still recorded; the token keeps the bad character in
+    /// the stream so a parser can report it in context instead of a gap.
+    ///
+    /// # Example
+    /// ```ignore
+    /// let tokens = Scanner::new("a @ b".to_string()).with_error_tokens().scan_tokens();
+    /// assert_eq!(tokens[1].token_type, TokenType::Error);
+    /// ```
+    pub fn with_error_tokens(mut self) -> Self {
+        self.error_tokens = true;
+        self
+    }
+
+    /// Scan `//` as two `/` tokens instead of starting a comment
+    ///
+    /// For data expressions, where a comment is never intended and a stray
+    /// `//` should reach the parser as an error.
+    ///
+    /// # Example
+    /// ```ignore
+    /// let tokens = Scanner::new("a // b".to_string()).without_comments().scan_tokens();
+    /// assert_eq!(tokens.len(), 5);
+    /// ```
+    pub fn without_comments(mut self) -> Self {
+        self.comments = false;
+        self
+    }
+
+    /// Start over on a new source, reusing this scanner's buffers
+    ///
+    /// # Example
+    /// ```ignore
+    /// let mut scanner = Scanner::new("1 + 2".to_string());
+    /// scanner.scan_tokens();
+    /// scanner.reset("var a;".to_string());
+    /// scanner.scan_tokens();
+    /// ```
+    pub fn reset(&mut self, source: String) {
+        self.source.clear();
+        self.source.extend(source.chars());
+        self.pending.clear();
+        self.errors.clear();
+        self.incomplete = false;
+        self.start = 0;
+        self.current = 0;
+        self.line = self.start_line;
+        self.finished = false;
+    }
+
+    /// Scan the rest of the source, keeping its errors for `errors()`
     pub fn scan_tokens(&mut self) -> Vec<Token> {
-        while !self.is_at_end() {
+        let mut tokens = Vec::new();
+        while let Some(item) = self.scan_one() {
+            match item {
+                Ok(token) => tokens.push(token),
+                Err(error) => self.errors.push(error),
+            }
+        }
+        tokens
+    }
+
+    /// Scan just far enough to produce the next token or error
+    ///
+    /// Errors come before the token of the same lexeme, if any. Returns
+    /// `None` once the `Eof` token has been returned.
+    ///
+    /// # Example
+    /// ```ignore
+    /// let mut scanner = Scanner::new("print 1;".to_string());
+    /// while let Some(item) = scanner.scan_one() {
+    ///     match item {
+    ///         Ok(token) => println!("{}", token),
+    ///         Err(error) => eprintln!("{}", error.message),
+    ///     }
+    /// }
+    /// ```
+    pub fn scan_one(&mut self) -> Option<Result<Token, ScanError>> {
+        loop {
+            if let Some(item) = self.pending.pop_front() {
+                return Some(item);
+            }
+            if self.finished {
+                return None;
+            }
+            self.step();
+        }
+    }
+
+    // Scan one lexeme, or add EOF at the end of the source
+    fn step(&mut self) {
+        if self.is_at_end() {
+            let mut eof = Token::eof(self.line);
+            eof.source_name = self.source_name.clone();
+            self.pending.push_back(Ok(eof));
+            self.finished = true;
+        } else {
             self.start = self.current;
             self.scan_token();
         }
+    }
+
+    /// Scan the source, telling incomplete input apart from real errors
+    ///
+    /// A REPL can keep reading lines while this returns `Incomplete`.
+    pub fn scan(&mut self) -> ScanOutcome {
+        let tokens = self.scan_tokens();
+        let genuine_errors = self.errors.len() - usize::from(self.incomplete);
+
+        if genuine_errors > 0 {
+            ScanOutcome::Error(self.errors.clone())
+        } else if self.incomplete {
+            ScanOutcome::Incomplete
+        } else {
+            ScanOutcome::Complete(tokens)
+        }
+    }
 
-        self.tokens
-            .push(Token::new(TokenType::Eof, String::new(), None, self.line));
+    // Errors collected by the last scan_tokens call
+    pub fn errors(&self) -> &[ScanError] {
+        &self.errors
+    }
 
-        self.tokens.clone()
+    pub fn had_error(&self) -> bool {
+        !self.errors.is_empty()
     }
 
     fn advance(&mut self) -> char {
-        let c = self.source.chars().nth(self.current).unwrap();
+        let c = self.source[self.current];
         self.current += 1;
         c
     }
@@ -62,9 +269,10 @@ impl Scanner {
     }
 
     fn add_token_literal(&mut self, token_type: TokenType, literal: Option<Literal>) {
-        let text = self.source[self.start..self.current].to_string();
-        self.tokens
-            .push(Token::new(token_type, text, literal, self.line));
+        let text = self.substring(self.start, self.current);
+        let mut token = Token::new(token_type, text, literal, self.line);
+        token.source_name = self.source_name.clone();
+        self.pending.push_back(Ok(token));
     }
 
     fn scan_token(&mut self) {
@@ -75,11 +283,52 @@ impl Scanner {
             '{' => self.add_token(TokenType::LeftBrace),
             '}' => self.add_token(TokenType::RightBrace),
             ',' => self.add_token(TokenType::Comma),
-            '.' => self.add_token(TokenType::Dot),
-            '-' => self.add_token(TokenType::Minus),
-            '+' => self.add_token(TokenType::Plus),
+            ':' => self.add_token(TokenType::Colon),
+            '.' => {
+                if self.is_digit(self.peek()) {
+                    self.leading_dot_number();
+                } else {
+                    self.add_token(TokenType::Dot);
+                }
+            }
             ';' => self.add_token(TokenType::Semicolon),
-            '*' => self.add_token(TokenType::Star),
+            '%' => self.add_token(TokenType::Percent),
+            '?' => {
+                if self.token_match('?') {
+                    self.add_token(TokenType::QuestionQuestion);
+                } else {
+                    self.unexpected_character();
+                }
+            }
+
+            '-' => {
+                let token_type = if self.token_match('=') {
+                    TokenType::MinusEqual
+                } else if self.token_match('-') {
+                    TokenType::MinusMinus
+                } else {
+                    TokenType::Minus
+                };
+                self.add_token(token_type);
+            }
+            '+' => {
+                let token_type = if self.token_match('=') {
+                    TokenType::PlusEqual
+                } else if self.token_match('+') {
+                    TokenType::PlusPlus
+                } else {
+                    TokenType::Plus
+                };
+                self.add_token(token_type);
+            }
+            '*' => {
+                let token_type = if self.token_match('=') {
+                    TokenType::StarEqual
+                } else {
+                    TokenType::Star
+                };
+                self.add_token(token_type);
+            }
 
             '!' => {
                 let token_type = if self.token_match('=') {
@@ -114,15 +363,24 @@ impl Scanner {
                 self.add_token(token_type);
             }
             '/' => {
-                if self.token_match('/') {
+                if self.comments && self.token_match('/') {
                     while self.peek() != '\n' && !self.is_at_end() {
                         self.advance();
                     }
+                } else if self.token_match('=') {
+                    self.add_token(TokenType::SlashEqual);
                 } else {
                     self.add_token(TokenType::Slash);
                 }
             }
 
+            // A shebang line, `#!/usr/bin/env rlox`, only at the very start
+            '#' if self.start == 0 && self.peek() == '!' => {
+                while self.peek() != '\n' && !self.is_at_end() {
+                    self.advance();
+                }
+            }
+
             ' ' | '\r' | '\t' => {}
             '\n' => self.line += 1,
 
@@ -131,17 +389,37 @@ impl Scanner {
             _ => {
                 if self.is_digit(c) {
                     self.number();
+                } else if c == 'r' && self.peek() == '"' {
+                    self.advance();
+                    self.raw_string();
                 } else if self.is_alpha(c) {
                     self.identifier();
                 } else {
-                    eprintln!("Unexpected character at line {}", self.line);
+                    self.unexpected_character();
                 }
             }
         }
     }
 
+    fn unexpected_character(&mut self) {
+        self.error(ErrorKind::UnexpectedChar, "Unexpected character.");
+        if self.error_tokens {
+            self.add_token(TokenType::Error);
+        }
+    }
+
     fn string(&mut self) {
+        // Report errors where the string began, not where the input ran out
+        let start_line = self.line;
+
         while self.peek() != '"' && !self.is_at_end() {
+            // Skip the escaped character so `\"` does not end the string
+            if self.peek() == '\\' {
+                self.advance();
+                if self.is_at_end() {
+                    break;
+                }
+            }
             if self.peek() == '\n' {
                 self.line += 1;
             }
@@ -149,16 +427,130 @@ impl Scanner {
         }
 
         if self.is_at_end() {
-            eprintln!("[line {}] Error: Unterminated string.", self.line);
+            self.incomplete = true;
+            self.error_at(
+                start_line,
+                ErrorKind::UnterminatedString,
+                "Unterminated string.",
+            );
             return;
         }
 
         self.advance();
 
-        let value = self.source[self.start + 1..self.current - 1].to_string();
+        let raw: Vec<char> = self.source[self.start + 1..self.current - 1].to_vec();
+        let value = self.unescape(&raw, start_line);
         self.add_token_literal(TokenType::String, Some(Literal::String(value)));
     }
 
+    // `r"..."`: backslashes are kept as written and the first `"` ends it
+    fn raw_string(&mut self) {
+        let start_line = self.line;
+
+        while self.peek() != '"' && !self.is_at_end() {
+            if self.peek() == '\n' {
+                self.line += 1;
+            }
+            self.advance();
+        }
+
+        if self.is_at_end() {
+            self.incomplete = true;
+            self.error_at(
+                start_line,
+                ErrorKind::UnterminatedString,
+                "Unterminated string.",
+            );
+            return;
+        }
+
+        self.advance();
+
+        let value = self.substring(self.start + 2, self.current - 1);
+        self.add_token_literal(TokenType::String, Some(Literal::String(value)));
+    }
+
+    // Decode escape sequences in a string body starting on `line`.
+    // Bad escapes are reported and dropped; the rest of the string is kept.
+    //
+    // \n \t \r \0 \\ \"   common escapes
+    // \xNN                 code point given by two hex digits, e.g. \x41 -> A
+    // \u{N...}             code point given by 1-6 hex digits, e.g. \u{1F600}
+    // \<newline>           line continuation, skips the newline and the next
+    //                      line's leading spaces and tabs
+    fn unescape(&mut self, raw: &[char], mut line: usize) -> String {
+        let mut value = String::with_capacity(raw.len());
+        let mut chars = raw.iter().copied().peekable();
+
+        while let Some(c) = chars.next() {
+            if c == '\n' {
+                line += 1;
+            }
+            if c != '\\' {
+                value.push(c);
+                continue;
+            }
+
+            // The scan loop never leaves a backslash last in the body
+            let escape = chars.next().unwrap_or('\\');
+            if escape == '\n' {
+                line += 1;
+                while chars.next_if(|c| *c == ' ' || *c == '\t').is_some() {}
+                continue;
+            }
+            let decoded = match escape {
+                'n' => Ok('\n'),
+                't' => Ok('\t'),
+                'r' => Ok('\r'),
+                '0' => Ok('\0'),
+                '\\' => Ok('\\'),
+                '"' => Ok('"'),
+                'x' => {
+                    let digits: String = chars.by_ref().take(2).collect();
+                    let hex = digits.len() == 2 && digits.chars().all(|d| d.is_ascii_hexdigit());
+                    match u8::from_str_radix(&digits, 16) {
+                        Ok(code) if hex => Ok(char::from(code)),
+                        _ => Err("Expect two hex digits after '\\x'.".to_string()),
+                    }
+                }
+                'u' => Self::unicode_escape(&mut chars),
+                other => Err(format!("Unknown escape sequence '\\{}'.", other)),
+            };
+
+            match decoded {
+                Ok(decoded) => value.push(decoded),
+                Err(message) => self.error_at(line, ErrorKind::InvalidEscape, &message),
+            }
+        }
+
+        value
+    }
+
+    // Rest of a `\u{...}` escape after the `u`
+    fn unicode_escape(chars: &mut impl Iterator<Item = char>) -> Result<char, String> {
+        let malformed = || "Expect '\\u{' followed by 1 to 6 hex digits and '}'.".to_string();
+
+        if chars.next() != Some('{') {
+            return Err(malformed());
+        }
+        let mut digits = String::new();
+        loop {
+            match chars.next() {
+                Some('}') => break,
+                Some(c) if c.is_ascii_hexdigit() && digits.len() < 6 => digits.push(c),
+                _ => return Err(malformed()),
+            }
+        }
+        if digits.is_empty() {
+            return Err(malformed());
+        }
+
+        u32::from_str_radix(&digits, 16)
+            .ok()
+            .and_then(char::from_u32)
+            .ok_or_else(|| format!("Invalid Unicode code point '{}' in escape.", digits))
+    }
+
     fn number(&mut self) {
         while self.is_digit(self.peek()) {
             self.advance();
@@ -170,9 +562,47 @@ impl Scanner {
             while self.is_digit(self.peek()) {
                 self.advance();
             }
+        } else if self.peek() == '.' && !self.is_alpha(self.peek_next()) {
+            // `5.` is rejected rather than read as `5` followed by a Dot.
+            // A dot followed by a name (`5.foo`) is left as property access.
+            self.advance();
+            self.error(
+                ErrorKind::InvalidNumber,
+                "Expect digit after '.' in number literal.",
+            );
+        }
+
+        self.add_number_token();
+    }
+
+    // `.5` is rejected rather than read as a Dot followed by `5`.
+    // The number token is still emitted so parsing can continue.
+    fn leading_dot_number(&mut self) {
+        while self.is_digit(self.peek()) {
+            self.advance();
         }
 
-        let value: f64 = self.source[self.start..self.current].parse().unwrap();
+        self.error(
+            ErrorKind::InvalidNumber,
+            "Expect digit before '.' in number literal.",
+        );
+        self.add_number_token();
+    }
+
+    // Huge literals would parse to infinity; they are errors instead.
+    // The token is still emitted so parsing can continue.
+    fn add_number_token(&mut self) {
+        let value = match self.substring(self.start, self.current).parse::<f64>() {
+            Ok(value) if value.is_finite() => value,
+            Ok(value) => {
+                self.error(ErrorKind::InvalidNumber, "Number literal out of range.");
+                value
+            }
+            Err(_) => {
+                self.error(ErrorKind::InvalidNumber, "Invalid number literal.");
+                0.0
+            }
+        };
         self.add_token_literal(TokenType::Number, Some(Literal::Number(value)));
     }
 
@@ -181,11 +611,10 @@ impl Scanner {
             self.advance();
         }
 
-        let text = &self.source[self.start..self.current];
-        let token_type = self
-            .keywords
-            .get(text)
-            .cloned()
+        let text = self.substring(self.start, self.current);
+        let token_type = keywords()
+            .get(text.as_str())
+            .copied()
             .unwrap_or(TokenType::Identifier);
 
         self.add_token(token_type);
@@ -195,7 +624,7 @@ impl Scanner {
         if self.is_at_end() {
             return false;
         }
-        if self.source.chars().nth(self.current) != Some(expected) {
+        if self.source[self.current] != expected {
             return false;
         }
         self.current += 1;
@@ -206,38 +635,60 @@ impl Scanner {
         if self.is_at_end() {
             return '\0';
         }
-        self.source.chars().nth(self.current).unwrap()
+        self.source[self.current]
     }
 
     fn peek_next(&self) -> char {
         if self.current + 1 >= self.source.len() {
             return '\0';
         }
-        self.source.chars().nth(self.current + 1).unwrap()
+        self.source[self.current + 1]
     }
 
+    // Identifier start: `_` or XID_Start, e.g. `길이` or `naïve`.
+    // ASCII behaves exactly as before.
     fn is_alpha(&self, c: char) -> bool {
-        (c >= 'a' && c <= 'z') || (c >= 'A' && c <= 'Z') || c == '_'
+        unicode_ident::is_xid_start(c) || c == '_'
     }
 
+    // Identifier continuation: XID_Continue, which includes `_` and digits
     fn is_alpha_numeric(&self, c: char) -> bool {
-        self.is_alpha(c) || self.is_digit(c)
+        unicode_ident::is_xid_continue(c)
     }
 
     fn is_digit(&self, c: char) -> bool {
-        c >= '0' && c <= '9'
+        c.is_ascii_digit()
     }
 
     fn is_at_end(&self) -> bool {
         self.current >= self.source.len()
     }
+
+    fn error(&mut self, kind: ErrorKind, message: &str) {
+        self.error_at(self.line, kind, message);
+    }
+
+    fn error_at(&mut self, line: usize, kind: ErrorKind, message: &str) {
+        self.pending.push_back(Err(ScanError {
+            line,
+            kind,
+            message: message.to_string(),
+            source_name: self.source_name.clone(),
+        }));
+    }
+
+    // Collect source chars in [start, end) into a String
+    fn substring(&self, start: usize, end: usize) -> String {
+        self.source[start..end].iter().collect()
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use crate::{
-        scanner::{self, Scanner},
-        token::{self, Literal, TokenType},
+        lox_error::ErrorKind,
+        scanner::{ScanOutcome, Scanner},
+        token::{Literal, TokenType, format_tokens},
     };
 
     /**
@@ -248,7 +699,7 @@ mod tests {
         let mut scanner = Scanner::new("(".to_string());
         let tokens = scanner.scan_tokens();
 
-        assert_eq!(tokens.len(), 2);
+        assert_eq!(tokens.len(), 2, "{}", format_tokens(&tokens));
         assert_eq!(tokens[0].token_type, TokenType::LeftParen);
         assert_eq!(tokens[0].lexeme, "(");
     }
@@ -296,7 +747,7 @@ mod tests {
         let mut scanner = Scanner::new("(){},.;-+*/".to_string());
         let tokens = scanner.scan_tokens();
 
-        assert_eq!(tokens.len(), 12); // 10 tokens + EOF
+        assert_eq!(tokens.len(), 12, "{}", format_tokens(&tokens)); // 10 tokens + EOF
 
         assert_eq!(tokens[0].token_type, TokenType::LeftParen);
         assert_eq!(tokens[1].token_type, TokenType::RightParen);
@@ -343,6 +794,48 @@ mod tests {
         assert_eq!(tokens[2].token_type, TokenType::Less);
         assert_eq!(tokens[3].token_type, TokenType::Greater);
     }
+    #[test]
+    fn scan_compound_assignment_operators() {
+        let mut scanner = Scanner::new("+= -= *= /=".to_string());
+        let tokens = scanner.scan_tokens();
+
+        assert_eq!(tokens[0].token_type, TokenType::PlusEqual);
+        assert_eq!(tokens[1].token_type, TokenType::MinusEqual);
+        assert_eq!(tokens[2].token_type, TokenType::StarEqual);
+        assert_eq!(tokens[3].token_type, TokenType::SlashEqual);
+        assert_eq!(tokens[3].lexeme, "/=");
+    }
+
+    #[test]
+    fn scan_increment_decrement_operators() {
+        let mut scanner = Scanner::new("++x y-- - -z".to_string());
+        let tokens = scanner.scan_tokens();
+
+        assert_eq!(tokens[0].token_type, TokenType::PlusPlus);
+        assert_eq!(tokens[1].token_type, TokenType::Identifier);
+        assert_eq!(tokens[2].token_type, TokenType::Identifier);
+        assert_eq!(tokens[3].token_type, TokenType::MinusMinus);
+        assert_eq!(tokens[4].token_type, TokenType::Minus);
+        assert_eq!(tokens[5].token_type, TokenType::Minus);
+    }
+
+    #[test]
+    fn scan_question_question() {
+        let mut scanner = Scanner::new("a ?? b".to_string());
+        let tokens = scanner.scan_tokens();
+
+        assert_eq!(tokens[1].token_type, TokenType::QuestionQuestion);
+        assert_eq!(tokens[1].lexeme, "??");
+    }
+
+    #[test]
+    fn single_question_mark_is_unexpected() {
+        let mut scanner = Scanner::new("?".to_string());
+        scanner.scan_tokens();
+
+        assert_eq!(scanner.errors()[0].message, "Unexpected character.");
+    }
+
     #[test]
     fn distinguish_single_bang_from_bang_equal() {
         let mut scanner = Scanner::new("! !=".to_string());
@@ -369,7 +862,7 @@ mod tests {
         let mut scanner = Scanner::new("// This is a comment".to_string());
         let tokens = scanner.scan_tokens();
 
-        assert_eq!(tokens.len(), 1);
+        assert_eq!(tokens.len(), 1, "{}", format_tokens(&tokens));
         assert_eq!(tokens[0].token_type, TokenType::Eof);
     }
 
@@ -378,7 +871,7 @@ mod tests {
         let mut scanner = Scanner::new("var // comment".to_string());
         let tokens = scanner.scan_tokens();
 
-        assert_eq!(tokens.len(), 2);
+        assert_eq!(tokens.len(), 2, "{}", format_tokens(&tokens));
         assert_eq!(tokens[0].token_type, TokenType::Var);
     }
 
@@ -390,6 +883,39 @@ mod tests {
         assert_eq!(tokens[0].token_type, TokenType::Var);
     }
 
+    #[test]
+    fn comment_hides_rest_of_line_by_default() {
+        let mut scanner = Scanner::new("a // b".to_string());
+        let tokens = scanner.scan_tokens();
+
+        assert_eq!(tokens.len(), 2, "{}", format_tokens(&tokens));
+        assert_eq!(tokens[0].lexeme, "a");
+        assert_eq!(tokens[1].token_type, TokenType::Eof);
+    }
+
+    #[test]
+    fn without_comments_double_slash_is_two_slashes() {
+        // Given
+        let mut scanner = Scanner::new("a // b".to_string()).without_comments();
+
+        // When
+        let tokens = scanner.scan_tokens();
+
+        // Then
+        let types: Vec<TokenType> = tokens.iter().map(|token| token.token_type).collect();
+        assert_eq!(
+            types,
+            vec![
+                TokenType::Identifier,
+                TokenType::Slash,
+                TokenType::Slash,
+                TokenType::Identifier,
+                TokenType::Eof,
+            ]
+        );
+        assert!(scanner.errors().is_empty());
+    }
+
     #[test]
     fn comment_does_not_affect_next_line() {
         let mut scanner = Scanner::new("var x // comment\nvar y".to_string());
@@ -401,6 +927,37 @@ mod tests {
         assert_eq!(tokens[3].token_type, TokenType::Identifier);
     }
 
+    #[test]
+    fn shebang_line_is_skipped() {
+        // Given
+        let mut scanner = Scanner::new("#!/usr/bin/env rlox\nprint 1;".to_string());
+
+        // When
+        let tokens = scanner.scan_tokens();
+
+        // Then
+        let types: Vec<TokenType> = tokens.iter().map(|token| token.token_type).collect();
+        assert_eq!(
+            types,
+            vec![
+                TokenType::Print,
+                TokenType::Number,
+                TokenType::Semicolon,
+                TokenType::Eof,
+            ]
+        );
+        assert!(tokens.iter().all(|token| token.line == 2));
+        assert!(scanner.errors().is_empty());
+    }
+
+    #[test]
+    fn shebang_only_counts_at_start() {
+        let mut scanner = Scanner::new(" #!x".to_string());
+        scanner.scan_tokens();
+
+        assert_eq!(scanner.errors()[0].kind, ErrorKind::UnexpectedChar);
+    }
+
     /**
      * Whitespace
      */
@@ -409,7 +966,7 @@ mod tests {
         let mut scanner = Scanner::new("      var  ".to_string());
         let tokens = scanner.scan_tokens();
 
-        assert_eq!(tokens.len(), 2);
+        assert_eq!(tokens.len(), 2, "{}", format_tokens(&tokens));
         assert_eq!(tokens[0].token_type, TokenType::Var);
         assert_eq!(tokens[1].token_type, TokenType::Eof);
     }
@@ -435,7 +992,7 @@ mod tests {
         let mut scanner = Scanner::new(" \t\r var \t\r ".to_string());
         let tokens = scanner.scan_tokens();
 
-        assert_eq!(tokens.len(), 2);
+        assert_eq!(tokens.len(), 2, "{}", format_tokens(&tokens));
     }
 
     /**
@@ -497,6 +1054,130 @@ mod tests {
         assert_eq!(tokens[1].line, 3); // var is on line 3
     }
 
+    fn scanned_string(source: &str) -> (String, Vec<String>) {
+        let mut scanner = Scanner::new(source.to_string());
+        let tokens = scanner.scan_tokens();
+        let value = match &tokens[0].literal {
+            Some(Literal::String(s)) => s.clone(),
+            other => panic!("Expected string literal, got {:?}", other),
+        };
+        let errors = scanner.errors().iter().map(|e| e.message.clone()).collect();
+        (value, errors)
+    }
+
+    #[test]
+    fn scan_unicode_escapes() {
+        assert_eq!(
+            scanned_string(r#""\u{48}\u{69}""#),
+            ("Hi".to_string(), vec![])
+        );
+        assert_eq!(scanned_string(r#""\u{1F600}""#).0, "😀");
+    }
+
+    #[test]
+    fn scan_hex_and_common_escapes() {
+        let (value, errors) = scanned_string(r#""\x41\t\"q\"\\\n""#);
+
+        assert_eq!(value, "A\t\"q\"\\\n");
+        assert!(errors.is_empty());
+    }
+
+    #[test]
+    fn escaped_quote_does_not_end_string() {
+        let mut scanner = Scanner::new(r#""a\"b" 1"#.to_string());
+        let tokens = scanner.scan_tokens();
+
+        assert_eq!(tokens.len(), 3, "{}", format_tokens(&tokens));
+        assert_eq!(tokens[1].token_type, TokenType::Number);
+    }
+
+    #[test]
+    fn reject_out_of_range_code_point() {
+        let (_, errors) = scanned_string(r#""\u{110000}""#);
+
+        assert_eq!(
+            errors,
+            vec!["Invalid Unicode code point '110000' in escape."]
+        );
+    }
+
+    #[test]
+    fn reject_malformed_escapes() {
+        let malformed_unicode = "Expect '\\u{' followed by 1 to 6 hex digits and '}'.";
+        assert_eq!(scanned_string(r#""\u48""#).1, vec![malformed_unicode]);
+        assert_eq!(scanned_string(r#""\u{}""#).1, vec![malformed_unicode]);
+        assert_eq!(scanned_string(r#""\u{48""#).1, vec![malformed_unicode]);
+        assert_eq!(scanned_string(r#""\u{D800}""#).1.len(), 1);
+        assert_eq!(
+            scanned_string(r#""\xG1""#).1,
+            vec!["Expect two hex digits after '\\x'."]
+        );
+        assert_eq!(
+            scanned_string(r#""\q""#).1,
+            vec!["Unknown escape sequence '\\q'."]
+        );
+    }
+
+    #[test]
+    fn escaped_newline_continues_the_line() {
+        let mut scanner = Scanner::new("\"one \\\n    two\" x".to_string());
+        let tokens = scanner.scan_tokens();
+
+        assert_eq!(
+            tokens[0].literal,
+            Some(Literal::String("one two".to_string()))
+        );
+        assert!(!scanner.had_error());
+        assert_eq!(tokens[1].line, 2);
+    }
+
+    #[test]
+    fn raw_string_keeps_backslashes() {
+        let (value, errors) = scanned_string(r#"r"a\nb\q""#);
+
+        assert_eq!(value, "a\\nb\\q");
+        assert_eq!(value.len(), 6);
+        assert!(errors.is_empty());
+    }
+
+    #[test]
+    fn normal_string_decodes_the_same_escape() {
+        assert_eq!(scanned_string(r#""a\nb""#).0, "a\nb");
+    }
+
+    #[test]
+    fn raw_string_spans_lines() {
+        let mut scanner = Scanner::new("r\"a\nb\" x".to_string());
+        let tokens = scanner.scan_tokens();
+
+        assert_eq!(tokens[0].token_type, TokenType::String);
+        assert_eq!(tokens[0].lexeme, "r\"a\nb\"");
+        assert_eq!(tokens[1].line, 2);
+    }
+
+    #[test]
+    fn unterminated_raw_string_is_incomplete() {
+        let mut scanner = Scanner::new("r\"abc".to_string());
+
+        assert_eq!(scanner.scan(), ScanOutcome::Incomplete);
+    }
+
+    #[test]
+    fn r_before_other_text_is_an_identifier() {
+        let tokens = Scanner::new("r rx".to_string()).scan_tokens();
+
+        assert_eq!(tokens[0].token_type, TokenType::Identifier);
+        assert_eq!(tokens[1].lexeme, "rx");
+    }
+
+    #[test]
+    fn escape_error_reports_its_line() {
+        let mut scanner = Scanner::new("\"a\nb\n\\u{zz}\"".to_string());
+        scanner.scan_tokens();
+
+        assert_eq!(scanner.errors()[0].line, 3);
+    }
+
     // Edge case: Unterminated string
     #[test]
     fn handle_unterminated_string() {
@@ -505,6 +1186,124 @@ mod tests {
 
         // Should still produce EOF token
         assert!(tokens.last().unwrap().token_type == TokenType::Eof);
+        assert_eq!(scanner.errors()[0].message, "Unterminated string.");
+    }
+
+    #[test]
+    fn unexpected_character_is_skipped_by_default() {
+        let mut scanner = Scanner::new("a @ b".to_string());
+        let tokens = scanner.scan_tokens();
+
+        assert_eq!(tokens.len(), 3, "{}", format_tokens(&tokens));
+        assert_eq!(scanner.errors().len(), 1);
+    }
+
+    #[test]
+    fn error_tokens_keep_unexpected_characters() {
+        // Given
+        let mut scanner = Scanner::new("a @ b".to_string()).with_error_tokens();
+
+        // When
+        let tokens = scanner.scan_tokens();
+
+        // Then
+        let kinds: Vec<(TokenType, &str)> = tokens
+            .iter()
+            .map(|token| (token.token_type, token.lexeme.as_str()))
+            .collect();
+        assert_eq!(
+            kinds,
+            vec![
+                (TokenType::Identifier, "a"),
+                (TokenType::Error, "@"),
+                (TokenType::Identifier, "b"),
+                (TokenType::Eof, ""),
+            ]
+        );
+        assert_eq!(scanner.errors()[0].kind, ErrorKind::UnexpectedChar);
+    }
+
+    #[test]
+    fn unterminated_string_kind() {
+        let mut scanner = Scanner::new("\"unterminated".to_string());
+        scanner.scan_tokens();
+
+        assert_eq!(scanner.errors()[0].kind, ErrorKind::UnterminatedString);
+    }
+
+    #[test]
+    fn scan_error_kinds() {
+        for (source, kind) in [
+            ("@", ErrorKind::UnexpectedChar),
+            ("\"\\q\"", ErrorKind::InvalidEscape),
+            ("5.", ErrorKind::InvalidNumber),
+        ] {
+            let mut scanner = Scanner::new(source.to_string());
+            scanner.scan_tokens();
+            assert_eq!(scanner.errors()[0].kind, kind, "{}", source);
+        }
+    }
+
+    #[test]
+    fn unterminated_string_reports_opening_line() {
+        // Given: a string opened on line 2 that runs to the end of input
+        let mut scanner = Scanner::new("var a;\n\"first\nsecond\nthird".to_string());
+
+        // When
+        scanner.scan_tokens();
+
+        // Then
+        assert_eq!(scanner.errors().len(), 1);
+        assert_eq!(scanner.errors()[0].line, 2);
+        assert_eq!(scanner.errors()[0].message, "Unterminated string.");
+    }
+
+    #[test]
+    fn unterminated_string_is_incomplete() {
+        let mut scanner = Scanner::new("\"abc".to_string());
+
+        assert_eq!(scanner.scan(), ScanOutcome::Incomplete);
+    }
+
+    #[test]
+    fn unclosed_brackets_and_trailing_operators_are_complete() {
+        // Only an unterminated string is incomplete; balancing is left to the REPL
+        let mut scanner = Scanner::new("fun f() {\n  print (1".to_string());
+        assert!(matches!(scanner.scan(), ScanOutcome::Complete(_)));
+
+        scanner.reset("var a = 1 +".to_string());
+        assert!(matches!(scanner.scan(), ScanOutcome::Complete(_)));
+    }
+
+    #[test]
+    fn unexpected_character_is_error_not_incomplete() {
+        let mut scanner = Scanner::new("@".to_string());
+
+        match scanner.scan() {
+            ScanOutcome::Error(errors) => {
+                assert_eq!(errors[0].message, "Unexpected character.")
+            }
+            other => panic!("Expected scan error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn genuine_error_wins_over_incomplete() {
+        let mut scanner = Scanner::new("@ \"abc".to_string());
+
+        assert!(matches!(scanner.scan(), ScanOutcome::Error(_)));
+    }
+
+    #[test]
+    fn complete_scan_returns_tokens() {
+        let mut scanner = Scanner::new("\"abc\"".to_string());
+
+        match scanner.scan() {
+            ScanOutcome::Complete(tokens) => {
+                assert_eq!(tokens.len(), 2, "{}", format_tokens(&tokens))
+            }
+            other => panic!("Expected tokens, got {:?}", other),
+        }
     }
 
     /**
@@ -575,6 +1374,73 @@ mod tests {
         }
     }
 
+    #[test]
+    fn reject_trailing_dot_number() {
+        let mut scanner = Scanner::new("5.".to_string());
+        let tokens = scanner.scan_tokens();
+
+        assert_eq!(tokens.len(), 2, "{}", format_tokens(&tokens)); // number + EOF, no Dot
+        assert_eq!(tokens[0].token_type, TokenType::Number);
+        assert_eq!(scanner.errors().len(), 1);
+        assert_eq!(
+            scanner.errors()[0].message,
+            "Expect digit after '.' in number literal."
+        );
+    }
+
+    #[test]
+    fn reject_leading_dot_number() {
+        let mut scanner = Scanner::new(".5".to_string());
+        let tokens = scanner.scan_tokens();
+
+        assert_eq!(tokens.len(), 2, "{}", format_tokens(&tokens)); // number + EOF, no Dot
+        assert_eq!(tokens[0].token_type, TokenType::Number);
+        assert_eq!(scanner.errors().len(), 1);
+        assert_eq!(
+            scanner.errors()[0].message,
+            "Expect digit before '.' in number literal."
+        );
+    }
+
+    #[test]
+    fn decimal_with_digits_on_both_sides_is_valid() {
+        let mut scanner = Scanner::new("5.0".to_string());
+        let tokens = scanner.scan_tokens();
+
+        match tokens[0].literal {
+            Some(Literal::Number(n)) => assert_eq!(n, 5.0),
+            _ => panic!("Expected 5.0"),
+        }
+        assert!(!scanner.had_error());
+    }
+
+    #[test]
+    fn member_access_dot_is_not_a_number() {
+        let mut scanner = Scanner::new("a.b".to_string());
+        let tokens = scanner.scan_tokens();
+
+        assert_eq!(tokens[0].token_type, TokenType::Identifier);
+        assert_eq!(tokens[1].token_type, TokenType::Dot);
+        assert_eq!(tokens[2].token_type, TokenType::Identifier);
+        assert!(!scanner.had_error());
+    }
+
+    #[test]
+    fn scan_decimal_after_multibyte_char() {
+        let mut scanner = Scanner::new("\"héllo\" 1.5".to_string());
+        let tokens = scanner.scan_tokens();
+
+        match &tokens[0].literal {
+            Some(Literal::String(s)) => assert_eq!(s, "héllo"),
+            _ => panic!("Expected string literal"),
+        }
+        assert_eq!(tokens[1].lexeme, "1.5");
+        match tokens[1].literal {
+            Some(Literal::Number(n)) => assert_eq!(n, 1.5),
+            _ => panic!("Expected 1.5"),
+        }
+    }
+
     /**
      * Keywords
      */
@@ -715,6 +1581,49 @@ mod tests {
         assert_eq!(tokens[0].lexeme, "varsity");
     }
 
+    #[test]
+    fn scan_unicode_identifiers() {
+        let mut scanner = Scanner::new("길이 naïve x٣".to_string());
+        let tokens = scanner.scan_tokens();
+
+        assert!(!scanner.had_error());
+        assert_eq!(tokens[0].token_type, TokenType::Identifier);
+        assert_eq!(tokens[0].lexeme, "길이");
+        assert_eq!(tokens[1].token_type, TokenType::Identifier);
+        assert_eq!(tokens[1].lexeme, "naïve");
+        assert_eq!(tokens[2].lexeme, "x٣");
+    }
+
+    #[test]
+    fn superscripts_and_fractions_are_not_identifier_characters() {
+        // `²` and `½` are numeric but outside XID_Continue
+        let mut scanner = Scanner::new("x² ½".to_string());
+        let tokens = scanner.scan_tokens();
+
+        assert_eq!(tokens[0].lexeme, "x");
+        assert_eq!(scanner.errors().len(), 2);
+        assert_eq!(scanner.errors()[0].message, "Unexpected character.");
+    }
+
+    #[test]
+    fn digit_cannot_start_unicode_identifier() {
+        let mut scanner = Scanner::new("1é".to_string());
+        let tokens = scanner.scan_tokens();
+
+        assert_eq!(tokens[0].token_type, TokenType::Number);
+        assert_eq!(tokens[1].token_type, TokenType::Identifier);
+        assert_eq!(tokens[1].lexeme, "é");
+    }
+
+    #[test]
+    fn keywords_are_ascii_only() {
+        // Fullwidth letters look like `var` but are an ordinary name
+        let mut scanner = Scanner::new("ｖａｒ".to_string());
+        let tokens = scanner.scan_tokens();
+
+        assert_eq!(tokens[0].token_type, TokenType::Identifier);
+    }
+
     #[test]
     fn keyword_suffix_is_identifier() {
         let mut scanner = Scanner::new("myvar".to_string());
@@ -754,6 +1663,32 @@ mod tests {
         assert_eq!(tokens[1].line, 4); // x
     }
 
+    #[test]
+    fn track_line_number_from_start_line() {
+        // Given: a snippet embedded at line 10 of a host document
+        let mut scanner = Scanner::new_at("var x\n\"oops".to_string(), 10);
+
+        // When
+        let tokens = scanner.scan_tokens();
+
+        // Then
+        assert_eq!(tokens[0].line, 10); // var
+        assert_eq!(tokens[1].line, 10); // x
+        assert_eq!(scanner.errors()[0].line, 11);
+    }
+
+    #[test]
+    fn start_line_changes_only_lines() {
+        let source = "var x = 1;\nprint x;";
+
+        let shifted = Scanner::new_at(source.to_string(), 10).scan_tokens();
+        let plain = Scanner::new(source.to_string()).scan_tokens();
+
+        assert_eq!(shifted.len(), plain.len());
+        assert!(shifted.iter().zip(&plain).all(|(a, b)| a.same_kind(b)));
+        assert_eq!(shifted[5].line, plain[5].line + 9);
+    }
+
     /**
      * Complex Expressions
      */
@@ -855,7 +1790,7 @@ mod tests {
         let mut scanner = Scanner::new("".to_string());
         let tokens = scanner.scan_tokens();
 
-        assert_eq!(tokens.len(), 1);
+        assert_eq!(tokens.len(), 1, "{}", format_tokens(&tokens));
         assert_eq!(tokens[0].token_type, TokenType::Eof);
     }
 
@@ -864,7 +1799,7 @@ mod tests {
         let mut scanner = Scanner::new("   \n\t\r  ".to_string());
         let tokens = scanner.scan_tokens();
 
-        assert_eq!(tokens.len(), 1);
+        assert_eq!(tokens.len(), 1, "{}", format_tokens(&tokens));
         assert_eq!(tokens[0].token_type, TokenType::Eof);
     }
 
@@ -873,7 +1808,7 @@ mod tests {
         let mut scanner = Scanner::new("// comment\n// another".to_string());
         let tokens = scanner.scan_tokens();
 
-        assert_eq!(tokens.len(), 1);
+        assert_eq!(tokens.len(), 1, "{}", format_tokens(&tokens));
         assert_eq!(tokens[0].token_type, TokenType::Eof);
     }
 
@@ -894,4 +1829,138 @@ mod tests {
         assert_eq!(eof.token_type, TokenType::Eof);
         assert_eq!(eof.line, 3);
     }
+
+    #[test]
+    fn number_literal_out_of_range_is_error() {
+        // Lox has no exponent syntax, so 1e400 is written out in full
+        let source = format!("1{}", "0".repeat(400));
+        let mut scanner = Scanner::new(source);
+        let tokens = scanner.scan_tokens();
+
+        assert_eq!(scanner.errors().len(), 1);
+        assert_eq!(scanner.errors()[0].message, "Number literal out of range.");
+        assert_eq!(tokens[0].token_type, TokenType::Number);
+    }
+
+    #[test]
+    fn large_number_literals_in_range_still_parse() {
+        let source = format!("1{}.5 0.000001", "0".repeat(300));
+        let mut scanner = Scanner::new(source);
+        let tokens = scanner.scan_tokens();
+
+        assert!(!scanner.had_error());
+        assert_eq!(tokens[0].literal, Some(Literal::Number(1e300)));
+        assert_eq!(tokens[1].literal, Some(Literal::Number(0.000001)));
+    }
+
+    #[test]
+    fn reset_scans_a_new_source() {
+        // Given: a scanner that already scanned a source with an error
+        let mut scanner = Scanner::new("var a\n@".to_string());
+        scanner.scan_tokens();
+        assert!(scanner.had_error());
+
+        // When
+        scanner.reset("print 1;".to_string());
+        let tokens = scanner.scan_tokens();
+
+        // Then: nothing carries over from the first source
+        assert!(!scanner.had_error());
+        let types: Vec<TokenType> = tokens.iter().map(|t| t.token_type).collect();
+        assert_eq!(
+            types,
+            vec![
+                TokenType::Print,
+                TokenType::Number,
+                TokenType::Semicolon,
+                TokenType::Eof
+            ]
+        );
+        assert_eq!(tokens.last().unwrap().line, 1);
+    }
+
+    /**
+     * Incremental Scanning
+     */
+    #[test]
+    fn scan_one_matches_scan_tokens() {
+        // Given
+        let source = "fun add(a, b) {\n  return a + b; // sum\n}\nprint \"x\";";
+        let mut pull = Scanner::new(source.to_string());
+
+        // When
+        let mut tokens = Vec::new();
+        while let Some(item) = pull.scan_one() {
+            tokens.push(item.unwrap());
+        }
+
+        // Then
+        assert_eq!(tokens, Scanner::new(source.to_string()).scan_tokens());
+        assert_eq!(tokens.last().unwrap().token_type, TokenType::Eof);
+        assert_eq!(pull.scan_one(), None);
+    }
+
+    #[test]
+    fn scan_one_reports_errors_in_order() {
+        let mut scanner = Scanner::new("a @ b".to_string());
+
+        let items: Vec<_> = std::iter::from_fn(|| scanner.scan_one()).collect();
+
+        assert_eq!(items.len(), 4);
+        assert_eq!(items[0].as_ref().unwrap().lexeme, "a");
+        assert_eq!(
+            items[1].as_ref().unwrap_err().kind,
+            ErrorKind::UnexpectedChar
+        );
+        assert_eq!(items[2].as_ref().unwrap().lexeme, "b");
+        assert_eq!(items[3].as_ref().unwrap().token_type, TokenType::Eof);
+    }
+
+    #[test]
+    fn scan_tokens_collects_the_rest_after_scan_one() {
+        // Given: the first token was already pulled
+        let mut scanner = Scanner::new("a @ b".to_string());
+        scanner.scan_one();
+
+        // When
+        let tokens = scanner.scan_tokens();
+
+        // Then: nothing is handed out twice
+        assert_eq!(tokens.len(), 2, "{}", format_tokens(&tokens));
+        assert_eq!(tokens[0].lexeme, "b");
+        assert_eq!(scanner.errors().len(), 1);
+    }
+
+    /**
+     * Source names
+     */
+    #[test]
+    fn named_scanner_stamps_tokens_and_errors() {
+        // Given
+        let mut scanner = Scanner::named("var a;\n@".to_string(), "foo.lox");
+
+        // When
+        let tokens = scanner.scan_tokens();
+
+        // Then
+        assert!(
+            tokens.iter().all(
+                |token| token.source_name.as_ref().map(|name| name.as_str()) == Some("foo.lox")
+            )
+        );
+        assert_eq!(tokens[0].location(), "foo.lox:1");
+        assert_eq!(tokens.last().unwrap().location(), "foo.lox:2");
+        assert_eq!(scanner.errors()[0].location(), "foo.lox:2");
+    }
+
+    #[test]
+    fn unnamed_scanner_leaves_source_name_empty() {
+        let mut scanner = Scanner::new("a\n@".to_string());
+
+        let tokens = scanner.scan_tokens();
+
+        assert_eq!(tokens[0].source_name, None);
+        assert_eq!(tokens[0].location(), "line 1");
+        assert_eq!(scanner.errors()[0].source_name, None);
+    }
 }
@@ -0,0 +1,253 @@
+use crate::expr::{Expr, same_each, same_option};
+use crate::token::Token;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Stmt {
+    Block(Block),
+    Break(Break),
+    Class(Class),
+    Continue(Continue),
+    Expression(Expression),
+    Fallthrough(Fallthrough),
+    Function(Function),
+    If(If),
+    Print(Print),
+    Return(Return),
+    Switch(Switch),
+    Var(Var),
+    While(While),
+}
+
+impl Stmt {
+    /// Whether two statements match ignoring lines, source names and node
+    /// ids; see `Expr::structurally_eq`
+    pub fn structurally_eq(&self, other: &Stmt) -> bool {
+        let same_expr = |a: &Expr, b: &Expr| a.structurally_eq(b);
+        let same_stmts = |a: &Vec<Stmt>, b: &Vec<Stmt>| same_each(a, b, Stmt::structurally_eq);
+        match (self, other) {
+            (Stmt::Block(a), Stmt::Block(b)) => same_stmts(&a.statements, &b.statements),
+            (Stmt::Break(a), Stmt::Break(b)) => same_option(&a.label, &b.label, Token::same_kind),
+            (Stmt::Class(a), Stmt::Class(b)) => {
+                a.name.same_kind(&b.name)
+                    && same_each(&a.methods, &b.methods, Function::structurally_eq)
+                    && same_each(&a.getters, &b.getters, Function::structurally_eq)
+                    && same_each(
+                        &a.class_methods,
+                        &b.class_methods,
+                        Function::structurally_eq,
+                    )
+            }
+            (Stmt::Continue(a), Stmt::Continue(b)) => {
+                same_option(&a.label, &b.label, Token::same_kind)
+            }
+            (Stmt::Expression(a), Stmt::Expression(b)) => same_expr(&a.expression, &b.expression),
+            (Stmt::Fallthrough(_), Stmt::Fallthrough(_)) => true,
+            (Stmt::Function(a), Stmt::Function(b)) => a.structurally_eq(b),
+            (Stmt::If(a), Stmt::If(b)) => {
+                same_expr(&a.condition, &b.condition)
+                    && a.then_branch.structurally_eq(&b.then_branch)
+                    && same_option(&a.else_branch, &b.else_branch, |a, b| a.structurally_eq(b))
+            }
+            (Stmt::Print(a), Stmt::Print(b)) => {
+                same_each(&a.expressions, &b.expressions, same_expr)
+            }
+            (Stmt::Return(a), Stmt::Return(b)) => same_option(&a.value, &b.value, same_expr),
+            (Stmt::Switch(a), Stmt::Switch(b)) => {
+                same_expr(&a.discriminant, &b.discriminant)
+                    && same_each(
+                        &a.cases,
+                        &b.cases,
+                        |(a_value, a_body), (b_value, b_body)| {
+                            same_expr(a_value, b_value) && same_stmts(a_body, b_body)
+                        },
+                    )
+                    && same_option(&a.default, &b.default, same_stmts)
+            }
+            (Stmt::Var(a), Stmt::Var(b)) => {
+                a.name.same_kind(&b.name) && same_option(&a.initializer, &b.initializer, same_expr)
+            }
+            (Stmt::While(a), Stmt::While(b)) => {
+                same_option(&a.label, &b.label, Token::same_kind)
+                    && same_expr(&a.condition, &b.condition)
+                    && a.body.structurally_eq(&b.body)
+                    && same_option(&a.increment, &b.increment, |a, b| a.structurally_eq(b))
+            }
+            _ => false,
+        }
+    }
+}
+
+/// Block statement, a list of statements in a new scope
+///
+/// # Examples
+/// - `{ var a = 1; print a; }`
+/// - `{}`
+#[derive(Debug, Clone, PartialEq)]
+pub struct Block {
+    pub statements: Vec<Stmt>,
+}
+
+/// Break statement, leaves the innermost loop or the loop labeled `label`
+///
+/// # Examples
+/// - `break;`
+/// - `outer: while (a) { while (b) { break outer; } }`
+#[derive(Debug, Clone, PartialEq)]
+pub struct Break {
+    pub keyword: Token,
+    pub label: Option<Token>,
+}
+
+/// Class declaration with its methods; `init` is the initializer
+///
+/// Getters are methods declared without a parameter list. Reading the
+/// property runs them, so they are used without parentheses. Class
+/// methods, marked with `class`, are called on the class itself.
+///
+/// # Examples
+/// - `class Point { init(x, y) { this.x = x; this.y = y; } }`
+/// - `class Rect { area { return this.w * this.h; } }`
+/// - `class Math { class square(n) { return n * n; } }`
+/// - `class Empty {}`
+#[derive(Debug, Clone, PartialEq)]
+pub struct Class {
+    pub name: Token,
+    pub methods: Vec<Function>,
+    pub getters: Vec<Function>,
+    pub class_methods: Vec<Function>,
+}
+
+/// Continue statement, starts the next iteration of the innermost loop or
+/// of the loop labeled `label`
+///
+/// # Examples
+/// - `continue;`
+/// - `rows: for (...) { for (...) { continue rows; } }`
+#[derive(Debug, Clone, PartialEq)]
+pub struct Continue {
+    pub keyword: Token,
+    pub label: Option<Token>,
+}
+
+/// Expression statement, evaluated for its side effects
+///
+/// # Examples
+/// - `x = 5;`
+/// - `greet("Bob");`
+#[derive(Debug, Clone, PartialEq)]
+pub struct Expression {
+    pub expression: Expr,
+}
+
+/// Fallthrough statement, ends a case and runs the next case's body too
+///
+/// # Examples
+/// - `switch (x) { case 1: print "one"; fallthrough; case 2: print "small"; }`
+#[derive(Debug, Clone, PartialEq)]
+pub struct Fallthrough {
+    pub keyword: Token,
+}
+
+/// Function declaration
+///
+/// # Examples
+/// - `fun add(a, b) { return a + b; }`
+/// - `fun hello() { print "hello"; }`
+#[derive(Debug, Clone, PartialEq)]
+pub struct Function {
+    pub name: Token,
+    pub params: Vec<Token>,
+    pub body: Vec<Stmt>,
+}
+
+impl Function {
+    fn structurally_eq(&self, other: &Function) -> bool {
+        self.name.same_kind(&other.name)
+            && same_each(&self.params, &other.params, Token::same_kind)
+            && same_each(&self.body, &other.body, Stmt::structurally_eq)
+    }
+}
+
+/// If statement with an optional else branch
+///
+/// # Examples
+/// - `if (x > 0) print x;`
+/// - `if (ready) start(); else wait();`
+#[derive(Debug, Clone, PartialEq)]
+pub struct If {
+    pub condition: Expr,
+    pub then_branch: Box<Stmt>,
+    pub else_branch: Option<Box<Stmt>>,
+}
+
+/// Print statement, one or more values separated by spaces
+///
+/// # Examples
+/// - `print "hello";`
+/// - `print 1 + 2;`
+/// - `print "x =", x;` -> `x = 5`
+#[derive(Debug, Clone, PartialEq)]
+pub struct Print {
+    pub expressions: Vec<Expr>,
+}
+
+/// Return statement, `value` is None for a bare `return;`
+///
+/// # Examples
+/// - `return a + b;`
+/// - `return;`
+#[derive(Debug, Clone, PartialEq)]
+pub struct Return {
+    pub keyword: Token,
+    pub value: Option<Expr>,
+}
+
+/// Switch statement, runs the first case whose value equals the
+/// discriminant, or `default` if none does
+///
+/// Cases do not fall through by default, so no `break` is needed to end
+/// one; `break` inside a switch still leaves the enclosing loop. A case
+/// that should continue into the next one ends with `fallthrough;`, which
+/// runs the next case's body without testing its value. `default` never
+/// falls through, as it may be written between other cases.
+///
+/// # Examples
+/// - `switch (x) { case 1: print "one"; case 2: print "two"; }`
+/// - `switch (cmd) { case "quit": done = true; default: print "?"; }`
+/// - `switch (n) { case 0: print "zero"; fallthrough; case 1: print "small"; }`
+#[derive(Debug, Clone, PartialEq)]
+pub struct Switch {
+    pub discriminant: Expr,
+    pub cases: Vec<(Expr, Vec<Stmt>)>,
+    pub default: Option<Vec<Stmt>>,
+}
+
+/// Variable declaration, `initializer` is None for `var x;`
+///
+/// # Examples
+/// - `var x = 5;`
+/// - `var name;`
+#[derive(Debug, Clone, PartialEq)]
+pub struct Var {
+    pub name: Token,
+    pub initializer: Option<Expr>,
+}
+
+/// While loop, `for` loops are desugared into it by the parser
+///
+/// `increment` is the third clause of a `for` loop. It runs after the body
+/// even when `continue` cut the body short.
+///
+/// # Examples
+/// - `while (i < 10) i = i + 1;`
+/// - `for (var i = 0; i < 10; i++) print i;`
+/// - `outer: while (true) { ... }`
+#[derive(Debug, Clone, PartialEq)]
+pub struct While {
+    /// `while`, or `for` for a desugared `for` loop
+    pub keyword: Token,
+    pub label: Option<Token>,
+    pub condition: Expr,
+    pub body: Box<Stmt>,
+    pub increment: Option<Box<Expr>>,
+}
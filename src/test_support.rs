@@ -0,0 +1,33 @@
+//! Helpers shared by the unit tests of several modules
+
+use std::cell::RefCell;
+use std::io::{self, Write};
+use std::rc::Rc;
+
+/// Writer that keeps what was written readable after being boxed
+///
+/// # Example
+/// ```ignore
+/// let buffer = SharedBuffer::default();
+/// let mut interpreter = Interpreter::new().with_output(Box::new(buffer.clone()));
+/// interpreter.run("print 1;").unwrap();
+/// assert_eq!(buffer.contents(), "1\n");
+/// ```
+#[derive(Clone, Default)]
+pub(crate) struct SharedBuffer(Rc<RefCell<Vec<u8>>>);
+
+impl SharedBuffer {
+    pub(crate) fn contents(&self) -> String {
+        String::from_utf8(self.0.borrow().clone()).unwrap()
+    }
+}
+
+impl Write for SharedBuffer {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0.borrow_mut().write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
@@ -1,4 +1,6 @@
-#[derive(Debug, Clone, PartialEq)]
+use std::rc::Rc;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum TokenType {
     // Single-character tokens.
     LeftParen,
@@ -6,12 +8,14 @@ pub enum TokenType {
     LeftBrace,
     RightBrace,
     Comma,
+    Colon,
     Dot,
     Minus,
     Plus,
     Semicolon,
     Slash,
     Star,
+    Percent,
 
     // One or two character tokens.
     Bang,
@@ -22,6 +26,13 @@ pub enum TokenType {
     GreaterEqual,
     Less,
     LessEqual,
+    PlusEqual,
+    MinusEqual,
+    StarEqual,
+    SlashEqual,
+    PlusPlus,
+    MinusMinus,
+    QuestionQuestion,
 
     // Literals.
     Identifier,
@@ -30,8 +41,13 @@ pub enum TokenType {
 
     // Keywords.
     And,
+    Break,
+    Case,
     Class,
+    Continue,
+    Default,
     Else,
+    Fallthrough,
     False,
     Fun,
     For,
@@ -41,20 +57,26 @@ pub enum TokenType {
     Print,
     Return,
     Super,
+    Switch,
     This,
     True,
     Var,
     While,
 
+    // Unexpected character, emitted only by `Scanner::with_error_tokens`
+    Error,
+
     Eof,
 }
 
+/// Value carried by a literal token
+///
+/// Only number and string tokens carry one; `true`, `false` and `nil`
+/// are keywords and the parser turns them into values itself.
 #[derive(Debug, Clone, PartialEq)]
 pub enum Literal {
     Number(f64),
     String(String),
-    Bool(bool),
-    Nil,
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -63,6 +85,10 @@ pub struct Token {
     pub lexeme: String,
     pub literal: Option<Literal>,
     pub line: usize,
+    /// File the token was scanned from, None for a single unnamed source
+    // Not `Rc<str>`: a thin pointer keeps `Token`, and every error holding
+    // one, small
+    pub source_name: Option<Rc<String>>,
 }
 
 impl Token {
@@ -73,10 +99,281 @@ impl Token {
         line: usize,
     ) -> Self {
         Self {
-            token_type: token_type,
-            lexeme: lexeme,
-            literal: literal,
-            line: line,
+            token_type,
+            lexeme,
+            literal,
+            line,
+            source_name: None,
         }
     }
+
+    /// End-of-input token: `Eof` type, empty lexeme, no literal
+    ///
+    /// Also used wherever a token is needed but none exists in the source.
+    pub fn eof(line: usize) -> Self {
+        Self::new(TokenType::Eof, String::new(), None, line)
+    }
+
+    /// Where the token is, for diagnostics
+    ///
+    /// # Examples
+    /// - from `Scanner::named(.., "foo.lox")`, line 12 -> `foo.lox:12`
+    /// - from `Scanner::new`, line 12 -> `line 12`
+    pub fn location(&self) -> String {
+        location(&self.source_name, self.line)
+    }
+
+    pub fn is_at_line(&self, line: usize) -> bool {
+        self.line == line
+    }
+
+    /// Same type, lexeme and literal, wherever each token is
+    ///
+    /// `==` also compares lines; this is for tests and tools that only
+    /// care what a token is.
+    pub fn same_kind(&self, other: &Token) -> bool {
+        self.token_type == other.token_type
+            && self.lexeme == other.lexeme
+            && self.literal == other.literal
+    }
+}
+
+// `name:line` for a named source, `line N` otherwise
+pub(crate) fn location(source_name: &Option<Rc<String>>, line: usize) -> String {
+    match source_name {
+        Some(name) => format!("{}:{}", name, line),
+        None => format!("line {}", line),
+    }
+}
+
+/// Tokens on the given source line, for per-line tooling such as highlighting
+///
+/// `tokens` must be in source order, as the scanner returns them.
+/// A line without tokens yields an empty slice.
+///
+/// # Example
+/// ```ignore
+/// let tokens = Scanner::new("var a;\nprint a;".to_string()).scan_tokens();
+/// assert_eq!(tokens_on_line(&tokens, 2).len(), 3);
+/// ```
+pub fn tokens_on_line(tokens: &[Token], line: usize) -> &[Token] {
+    let start = tokens.partition_point(|token| token.line < line);
+    let end = tokens.partition_point(|token| token.line <= line);
+    &tokens[start..end]
+}
+
+// Longest lexeme or literal `format_tokens` shows in full
+const MAX_CELL: usize = 16;
+
+/// Aligned table of tokens, one row each, for debugging and `--tokens`
+///
+/// Columns are index, line, type, lexeme and literal. Newlines and tabs in
+/// lexemes and literals are escaped as `\n` and `\t`, so every token stays
+/// on its row, and cells longer than 16 characters are cut short with an
+/// ellipsis.
+///
+/// # Example
+/// ```ignore
+/// let tokens = Scanner::new("var a = 1;".to_string()).scan_tokens();
+/// print!("{}", format_tokens(&tokens));
+/// // #  line  type        lexeme  literal
+/// // 0  1     Var         var
+/// // 1  1     Identifier  a
+/// // ...
+/// ```
+pub fn format_tokens(tokens: &[Token]) -> String {
+    let header = ["#", "line", "type", "lexeme", "literal"].map(str::to_string);
+    let rows: Vec<[String; 5]> = std::iter::once(header)
+        .chain(tokens.iter().enumerate().map(|(index, token)| {
+            let literal = match &token.literal {
+                Some(Literal::Number(n)) => n.to_string(),
+                Some(Literal::String(s)) => format!("\"{}\"", s),
+                None => String::new(),
+            };
+            [
+                index.to_string(),
+                token.line.to_string(),
+                format!("{:?}", token.token_type),
+                truncate(&escape(&token.lexeme)),
+                truncate(&escape(&literal)),
+            ]
+        }))
+        .collect();
+
+    let mut widths = [0; 5];
+    for row in &rows {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.chars().count());
+        }
+    }
+
+    let mut table = String::new();
+    for row in &rows {
+        let cells: Vec<String> = row
+            .iter()
+            .zip(widths)
+            .map(|(cell, width)| format!("{:<width$}", cell))
+            .collect();
+        table.push_str(cells.join("  ").trim_end());
+        table.push('\n');
+    }
+    table
+}
+
+fn escape(cell: &str) -> String {
+    cell.replace('\n', "\\n").replace('\t', "\\t")
+}
+
+fn truncate(cell: &str) -> String {
+    if cell.chars().count() <= MAX_CELL {
+        cell.to_string()
+    } else {
+        let kept: String = cell.chars().take(MAX_CELL - 1).collect();
+        kept + "…"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scanner::Scanner;
+    use std::collections::HashMap;
+
+    #[test]
+    fn test_token_type_is_copy() {
+        let token_type = TokenType::Plus;
+        let copied = token_type;
+
+        assert_eq!(token_type, copied);
+    }
+
+    #[test]
+    fn test_token_type_as_hash_map_key() {
+        let precedence = HashMap::from([
+            (TokenType::Plus, 1),
+            (TokenType::Minus, 1),
+            (TokenType::Star, 2),
+            (TokenType::Slash, 2),
+        ]);
+
+        assert_eq!(precedence.get(&TokenType::Star), Some(&2));
+        assert_eq!(precedence.get(&TokenType::Plus), Some(&1));
+        assert_eq!(precedence.get(&TokenType::Bang), None);
+    }
+
+    #[test]
+    fn test_same_kind_ignores_line() {
+        let first = Token::new(
+            TokenType::Number,
+            "1".to_string(),
+            Some(Literal::Number(1.0)),
+            1,
+        );
+        let later = Token::new(
+            TokenType::Number,
+            "1".to_string(),
+            Some(Literal::Number(1.0)),
+            7,
+        );
+
+        assert!(first.same_kind(&later));
+        assert_ne!(first, later);
+    }
+
+    #[test]
+    fn test_same_kind_compares_type_lexeme_and_literal() {
+        let name = Token::new(TokenType::Identifier, "a".to_string(), None, 1);
+
+        assert!(!name.same_kind(&Token::new(TokenType::Identifier, "b".to_string(), None, 1)));
+        assert!(!name.same_kind(&Token::new(TokenType::String, "a".to_string(), None, 1)));
+        assert!(!name.same_kind(&Token::new(
+            TokenType::Identifier,
+            "a".to_string(),
+            Some(Literal::String("a".to_string())),
+            1
+        )));
+    }
+
+    #[test]
+    fn test_eof_token() {
+        let token = Token::eof(1);
+
+        assert_eq!(token.token_type, TokenType::Eof);
+        assert_eq!(token.lexeme, "");
+        assert!(token.literal.is_none());
+        assert_eq!(token.line, 1);
+    }
+
+    #[test]
+    fn test_is_at_line() {
+        let token = Token::new(TokenType::Identifier, "a".to_string(), None, 3);
+
+        assert!(token.is_at_line(3));
+        assert!(!token.is_at_line(2));
+    }
+
+    #[test]
+    fn test_tokens_on_line_groups_by_line() {
+        // Given: line 3 is blank
+        let source = "var a = 1;\nprint a;\n\na = a + 1;";
+        let tokens = Scanner::new(source.to_string()).scan_tokens();
+
+        // When
+        let lexemes = |line| -> Vec<String> {
+            tokens_on_line(&tokens, line)
+                .iter()
+                .map(|token| token.lexeme.clone())
+                .collect()
+        };
+
+        // Then
+        assert_eq!(lexemes(1), vec!["var", "a", "=", "1", ";"]);
+        assert_eq!(lexemes(2), vec!["print", "a", ";"]);
+        assert!(lexemes(3).is_empty());
+        // The EOF token has an empty lexeme and sits on the last line
+        assert_eq!(lexemes(4), vec!["a", "=", "a", "+", "1", ";", ""]);
+        assert!(lexemes(5).is_empty());
+    }
+
+    #[test]
+    fn test_format_tokens_aligns_columns() {
+        // Given
+        let tokens = Scanner::new("var name = \"a very long string\";".to_string()).scan_tokens();
+
+        // When
+        let table = format_tokens(&tokens);
+
+        // Then
+        let rows: Vec<&str> = table.lines().collect();
+        assert_eq!(rows.len(), 7);
+        assert_eq!(rows[0], "#  line  type        lexeme            literal");
+        assert_eq!(rows[2], "1  1     Identifier  name");
+        assert_eq!(
+            rows[4],
+            "3  1     String      \"a very long st…  \"a very long st…"
+        );
+        assert_eq!(rows[6], "5  1     Eof");
+    }
+
+    #[test]
+    fn test_format_tokens_shows_number_literals() {
+        let tokens = Scanner::new("2.5".to_string()).scan_tokens();
+
+        let table = format_tokens(&tokens);
+
+        assert_eq!(table.lines().nth(1), Some("0  1     Number  2.5     2.5"));
+    }
+
+    #[test]
+    fn test_format_tokens_escapes_newlines_and_tabs() {
+        let tokens = Scanner::new("\"a\n\tb\"".to_string()).scan_tokens();
+
+        let table = format_tokens(&tokens);
+
+        assert_eq!(table.lines().count(), 3);
+        assert_eq!(
+            table.lines().nth(1),
+            Some("0  2     String  \"a\\n\\tb\"  \"a\\n\\tb\"")
+        );
+    }
 }
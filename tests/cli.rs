@@ -0,0 +1,192 @@
+//! End-to-end tests running the `rlox` binary
+
+use std::fs;
+use std::path::PathBuf;
+use std::process::{Command, Output};
+
+// Write `source` to a script file unique to this test
+fn script(name: &str, source: &str) -> PathBuf {
+    let path = std::env::temp_dir().join(format!("rlox-cli-{}-{}.lox", std::process::id(), name));
+    fs::write(&path, source).unwrap();
+    path
+}
+
+fn rlox(args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_rlox"))
+        .args(args)
+        .output()
+        .unwrap()
+}
+
+fn error_lines(output: &Output) -> Vec<String> {
+    String::from_utf8_lossy(&output.stderr)
+        .lines()
+        .filter(|line| line.contains("Error"))
+        .map(str::to_string)
+        .collect()
+}
+
+// ===== --check =====
+
+#[test]
+fn check_reports_every_syntax_error() {
+    // Given: two broken statements and one that would print if run
+    let path = script("two-errors", "print ;\nprint \"ran\";\nvar = 1;\n");
+
+    // When
+    let output = rlox(&["--no-color", "--check", path.to_str().unwrap()]);
+
+    // Then
+    assert_eq!(output.status.code(), Some(65));
+    let errors = error_lines(&output);
+    assert_eq!(errors.len(), 2, "{:?}", errors);
+    assert!(errors[0].starts_with("[line 1]"));
+    assert!(errors[1].starts_with("[line 3]"));
+    assert!(output.stdout.is_empty());
+}
+
+#[test]
+fn check_clean_script_exits_zero_without_running() {
+    let path = script("clean", "print \"ran\";\n");
+
+    let output = rlox(&["--check", path.to_str().unwrap()]);
+
+    assert_eq!(output.status.code(), Some(0));
+    assert!(output.stdout.is_empty());
+    assert!(output.stderr.is_empty());
+}
+
+#[test]
+fn check_reports_scan_and_parse_errors_together() {
+    let path = script("scan-and-parse", "var a = 1 @;\nprint ;\n");
+
+    let output = rlox(&["--no-color", "--check", path.to_str().unwrap()]);
+
+    assert_eq!(output.status.code(), Some(65));
+    assert_eq!(error_lines(&output).len(), 2);
+}
+
+#[test]
+fn check_lists_errors_in_line_order() {
+    // The scan error on line 2 is found before the parse error on line 1
+    let path = script("line-order", "print ;\nvar b = @;\n");
+
+    let output = rlox(&["--no-color", "--check", path.to_str().unwrap()]);
+
+    let errors = error_lines(&output);
+    assert_eq!(errors.len(), 3, "{:?}", errors);
+    assert!(errors[0].starts_with("[line 1]"));
+    assert!(errors[1].starts_with("[line 2]"));
+    assert!(errors[2].starts_with("[line 2]"));
+}
+
+#[test]
+fn check_without_script_is_usage_error() {
+    let output = rlox(&["--check"]);
+
+    assert_eq!(output.status.code(), Some(64));
+}
+
+// ===== Running =====
+
+#[test]
+fn run_lists_scan_and_parse_errors_in_line_order() {
+    // The scan error on line 2 is found before the parse error on line 1
+    let path = script("run-line-order", "print ;\nvar b = @;\n");
+
+    let output = rlox(&["--no-color", path.to_str().unwrap()]);
+
+    assert_eq!(output.status.code(), Some(65));
+    let errors = error_lines(&output);
+    assert_eq!(errors.len(), 3, "{:?}", errors);
+    assert!(errors[0].starts_with("[line 1]"));
+    assert!(errors[1].starts_with("[line 2]"));
+    assert!(errors[2].starts_with("[line 2]"));
+    assert!(output.stdout.is_empty());
+}
+
+#[test]
+fn run_rejects_undefined_loop_label_before_running() {
+    let path = script(
+        "undefined-label",
+        "print 1;\nouter: while (true) {\n  while (true) { break inner; }\n}\n",
+    );
+
+    let output = rlox(&["--no-color", path.to_str().unwrap()]);
+
+    assert_eq!(output.status.code(), Some(65));
+    assert_eq!(
+        error_lines(&output),
+        ["[line 3] Error at 'inner': Undefined loop label 'inner'."]
+    );
+    assert!(output.stdout.is_empty());
+}
+
+#[test]
+fn run_allows_deep_recursion() {
+    let path = script(
+        "deep-recursion",
+        "fun count(n) { if (n == 0) return 0; return 1 + count(n - 1); }\nprint count(5000);\n",
+    );
+
+    let output = rlox(&[path.to_str().unwrap()]);
+
+    assert_eq!(output.status.code(), Some(0));
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "5000\n");
+}
+
+// ===== --ast =====
+
+#[test]
+fn ast_prints_each_statement_without_running() {
+    let path = script("ast", "var a = 1;\nif (a) print a; else print -a;\n");
+
+    let output = rlox(&["--ast", path.to_str().unwrap()]);
+
+    assert_eq!(output.status.code(), Some(0));
+    assert_eq!(
+        String::from_utf8_lossy(&output.stdout),
+        "(var a 1)\n(if a (print a) (print (- a)))\n"
+    );
+}
+
+// ===== --tokens =====
+
+#[test]
+fn tokens_prints_table_without_running() {
+    let path = script("tokens", "print 1;\n");
+
+    let output = rlox(&["--tokens", path.to_str().unwrap()]);
+
+    assert_eq!(output.status.code(), Some(0));
+    assert_eq!(
+        String::from_utf8_lossy(&output.stdout),
+        "#  line  type       lexeme  literal\n\
+         0  1     Print      print\n\
+         1  1     Number     1       1\n\
+         2  1     Semicolon  ;\n\
+         3  2     Eof\n"
+    );
+}
+
+// ===== --time =====
+
+#[test]
+fn time_reports_phases_on_stderr_only() {
+    // Given
+    let path = script("time", "print 1 + 2;\n");
+
+    // When
+    let output = rlox(&["--time", path.to_str().unwrap()]);
+
+    // Then
+    assert_eq!(output.status.code(), Some(0));
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "3\n");
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    let labels: Vec<&str> = stderr
+        .lines()
+        .filter_map(|line| line.split_once(':'))
+        .map(|(label, _)| label)
+        .collect();
+    assert_eq!(labels, ["scan", "parse", "interpret"], "{}", stderr);
+}